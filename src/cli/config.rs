@@ -0,0 +1,359 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use toml::{Table, Value};
+use llm_holdem::game::poker::agent::Agent;
+use llm_holdem::game::poker::betting::{BettingStructure, DEFAULT_RAISE_CAP};
+use llm_holdem::game::poker::variant::GameVariant;
+use llm_holdem::game::poker::bots::{CallingStation, CommandAgent, EquityAgent, RandomAgent};
+use llm_holdem::game::poker::human::HumanAgent;
+use llm_holdem::game::poker::table::TableState;
+use llm_holdem::game::poker::tournament::Tournament;
+use llm_holdem::game::poker::{CreatePokerGame, PokerGame};
+use llm_holdem::player::Player;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentConfig {
+    CallingStation,
+    Random { seed: u64 },
+    Equity { iterations: u32 },
+    Command { command: String },
+    /// Someone at this terminal, typing actions on stdin
+    Human,
+    /// A client connecting to the seat over `serve`
+    Remote,
+}
+
+impl AgentConfig {
+    /// Reads the agent names used in the config file, with default settings
+    #[cfg(feature = "server")]
+    pub fn from_name(kind: &str) -> Option<AgentConfig> {
+        match kind {
+            "calling-station" => Some(AgentConfig::CallingStation),
+            "random" => Some(AgentConfig::Random { seed: 0 }),
+            "equity" => Some(AgentConfig::Equity { iterations: 500 }),
+            "human" => Some(AgentConfig::Human),
+            "remote" => Some(AgentConfig::Remote),
+            _ => None,
+        }
+    }
+
+    /// The agent playing as `name`; remote players have none, as `serve` seats them
+    pub fn build(&self, name: &str, seed: u64, table: &Rc<RefCell<TableState>>) -> Option<Box<dyn Agent>> {
+        let name = name.to_string();
+        let agent: Box<dyn Agent> = match self {
+            AgentConfig::CallingStation => Box::new(CallingStation { name }),
+            AgentConfig::Random { seed: offset } => Box::new(RandomAgent::new(&name, seed ^ offset)),
+            AgentConfig::Equity { iterations } => Box::new(EquityAgent { name, iterations: *iterations, seed }),
+            AgentConfig::Command { command } => Box::new(CommandAgent { name, command: command.clone() }),
+            AgentConfig::Human => Box::new(HumanAgent::stdin(&name, Some(table.clone()))),
+            AgentConfig::Remote => return None,
+        };
+        Some(agent)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerConfig {
+    pub id: usize,
+    pub name: String,
+    pub seat: u32,
+    pub agent: AgentConfig,
+}
+
+/// Table, lineup and tournament settings read from a TOML file:
+///
+/// ```toml
+/// [table]
+/// name = "Table 1"
+/// seats = 6
+/// buy_in = 1000
+/// buy_in_limit = 3
+/// blind_price = 5
+/// show_hud = false
+/// seed = 42
+///
+/// [tournament]
+/// blind_levels = [5, 10, 20, 40]
+/// hands_per_level = 20
+/// max_hands = 1000
+///
+/// [[players]]
+/// name = "Station"
+/// seat = 1
+/// agent = "calling-station"    # or "random", "equity", "command", "human", "remote"
+/// command = "python3 client.py" # for "command" agents
+/// ```
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub table_name: String,
+    pub seats: u32,
+    pub buy_in: u32,
+    pub buy_in_limit: u32,
+    pub blind_price: u32,
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    pub ante: u32,
+    pub show_hud: bool,
+    pub seed: u64,
+    pub blind_levels: Vec<u32>,
+    pub hands_per_level: u32,
+    pub max_hands: u32,
+    pub players: Vec<PlayerConfig>,
+}
+
+fn table<'a>(root: &'a Table, key: &str) -> Result<&'a Table, String> {
+    match root.get(key) {
+        Some(Value::Table(table)) => Ok(table),
+        Some(_) => Err(format!("`{}` must be a table", key)),
+        None => Err(format!("missing [{}]", key)),
+    }
+}
+
+fn number(table: &Table, key: &str, default: Option<u32>) -> Result<u32, String> {
+    match table.get(key) {
+        Some(value) => value.as_integer()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| format!("`{}` must be a non-negative integer", key)),
+        None => default.ok_or_else(|| format!("missing `{}`", key)),
+    }
+}
+
+fn string(table: &Table, key: &str) -> Result<String, String> {
+    table.get(key).and_then(Value::as_str).map(String::from).ok_or_else(|| format!("missing `{}`", key))
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Config::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let root: Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let settings = table(&root, "table")?;
+        let blind_price = number(settings, "blind_price", None)?;
+        let betting = match settings.get("betting") {
+            Some(_) => {
+                let code = string(settings, "betting")?;
+                BettingStructure::from_code(&code, number(settings, "raise_cap", Some(DEFAULT_RAISE_CAP))?)
+                    .ok_or_else(|| format!("unknown betting `{}`, use no-limit, pot-limit or fixed-limit", code))?
+            }
+            None => BettingStructure::NoLimit,
+        };
+        let variant = match settings.get("variant") {
+            Some(_) => {
+                let code = string(settings, "variant")?;
+                GameVariant::from_code(&code)
+                    .ok_or_else(|| format!("unknown variant `{}`, use holdem, short-deck, omaha, omaha-hi-lo, stud, razz, five-card-draw or 2-7-triple-draw", code))?
+            }
+            None => GameVariant::Holdem,
+        };
+
+        let empty = Table::new();
+        let tournament = match root.get("tournament") {
+            Some(_) => table(&root, "tournament")?,
+            None => &empty,
+        };
+        let blind_levels = match tournament.get("blind_levels").and_then(Value::as_array) {
+            Some(levels) => levels.iter()
+                .map(|v| v.as_integer().and_then(|n| u32::try_from(n).ok()).ok_or("`blind_levels` must hold integers"))
+                .collect::<Result<_, _>>()?,
+            None => vec![blind_price],
+        };
+
+        let players = match root.get("players") {
+            Some(Value::Array(players)) => players,
+            _ => return Err(String::from("missing [[players]]")),
+        };
+        let players = players.iter().enumerate().map(|(index, player)| {
+            let player = player.as_table().ok_or("`players` entries must be tables")?;
+            let agent = match string(player, "agent")?.as_str() {
+                "calling-station" => AgentConfig::CallingStation,
+                "random" => AgentConfig::Random { seed: number(player, "seed", Some(index as u32))? as u64 },
+                "equity" => AgentConfig::Equity { iterations: number(player, "iterations", Some(500))? },
+                "command" => AgentConfig::Command { command: string(player, "command")? },
+                "human" => AgentConfig::Human,
+                "remote" => AgentConfig::Remote,
+                other => return Err(format!("unknown agent `{}`", other)),
+            };
+            Ok(PlayerConfig {
+                id: number(player, "id", Some(index as u32 + 1))? as usize,
+                name: string(player, "name")?,
+                seat: number(player, "seat", Some(index as u32 + 1))?,
+                agent,
+            })
+        }).collect::<Result<Vec<_>, String>>()?;
+        if players.len() > variant.max_players() {
+            return Err(format!("{} deals to at most {} players, {} are configured", variant, variant.max_players(), players.len()));
+        }
+        let seed = match settings.get("seed") {
+            Some(value) => value.as_integer()
+                .and_then(|n| u64::try_from(n).ok())
+                .ok_or("`seed` must be a non-negative integer")?,
+            None => 0,
+        };
+
+        Ok(Config {
+            table_name: settings.get("name").and_then(Value::as_str).unwrap_or("Table 1").to_string(),
+            seats: number(settings, "seats", Some(players.len() as u32))?,
+            buy_in: number(settings, "buy_in", None)?,
+            buy_in_limit: number(settings, "buy_in_limit", Some(1))?,
+            blind_price,
+            betting,
+            variant,
+            ante: number(settings, "ante", Some(0))?,
+            show_hud: settings.get("show_hud").and_then(Value::as_bool).unwrap_or(false),
+            seed,
+            blind_levels,
+            hands_per_level: number(tournament, "hands_per_level", Some(20))?,
+            max_hands: number(tournament, "max_hands", Some(10_000))?,
+            players,
+        })
+    }
+
+    /// The configured table with every player seated and bought in
+    pub fn game(&self) -> Result<PokerGame, String> {
+        let mut game = PokerGame::new(CreatePokerGame {
+            seats: self.seats,
+            default_buy_in_value: self.buy_in,
+            buy_in_limit: self.buy_in_limit,
+            blind_price: self.blind_price,
+            betting: self.betting,
+            variant: self.variant,
+            ante: self.ante,
+            show_hud: self.show_hud,
+        });
+        game.seed = self.seed;
+
+        for player in &self.players {
+            game.add_player(Player::create(player.id, &player.name, ""), player.seat)
+                .map_err(|e| format!("cannot seat {}: {}", player.name, e))?;
+        }
+
+        Ok(game)
+    }
+
+    /// Seat of the human player, if one is configured
+    pub fn human_seat(&self) -> Option<u32> {
+        self.players.iter().find(|p| p.agent == AgentConfig::Human).map(|p| p.seat)
+    }
+
+    /// One agent per player id, remote players aside; human players are shown `table`,
+    /// which the caller keeps fed with the events of the hand being played.
+    pub fn agents(&self, table: &Rc<RefCell<TableState>>) -> BTreeMap<usize, Box<dyn Agent>> {
+        self.players.iter()
+            .filter_map(|player| Some((player.id, player.agent.build(&player.name, self.seed, table)?)))
+            .collect()
+    }
+
+    #[cfg(feature = "server")]
+    pub fn remote_players(&self) -> Vec<&PlayerConfig> {
+        self.players.iter().filter(|p| p.agent == AgentConfig::Remote).collect()
+    }
+
+    pub fn tournament(&self) -> Tournament {
+        Tournament {
+            blind_levels: self.blind_levels.clone(),
+            hands_per_level: self.hands_per_level,
+            max_hands: self.max_hands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [table]
+        seats = 3
+        buy_in = 500
+        blind_price = 5
+        seed = 7
+
+        [tournament]
+        blind_levels = [5, 10]
+
+        [[players]]
+        name = "Station"
+        agent = "calling-station"
+
+        [[players]]
+        name = "Script"
+        seat = 3
+        agent = "command"
+        command = "echo call"
+    "#;
+
+    #[test]
+    fn test_parse_with_defaults() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        assert_eq!(config.table_name, "Table 1");
+        assert_eq!(config.buy_in_limit, 1);
+        assert_eq!(config.blind_levels, vec![5, 10]);
+        assert_eq!(config.players[0], PlayerConfig { id: 1, name: String::from("Station"), seat: 1, agent: AgentConfig::CallingStation });
+        assert_eq!(config.players[1].seat, 3);
+        assert_eq!(config.players[1].agent, AgentConfig::Command { command: String::from("echo call") });
+
+        let game = config.game().unwrap();
+        assert_eq!(game.players.len(), 2);
+        assert_eq!(game.seed, 7);
+        assert_eq!(config.agents(&Rc::default())[&2].name(), "Script");
+        assert_eq!(config.human_seat(), None);
+        assert_eq!(game.betting, BettingStructure::NoLimit);
+    }
+
+    #[test]
+    fn test_parse_betting() {
+        let fixed = Config::parse(&CONFIG.replace("seed = 7", "betting = \"fixed-limit\"\nraise_cap = 3")).unwrap();
+        assert_eq!(fixed.betting, BettingStructure::FixedLimit { raise_cap: 3 });
+
+        let pot = Config::parse(&CONFIG.replace("seed = 7", "betting = \"pot-limit\"")).unwrap();
+        assert_eq!(pot.game().unwrap().betting, BettingStructure::PotLimit);
+
+        assert!(Config::parse(&CONFIG.replace("seed = 7", "betting = \"spread-limit\"")).unwrap_err().contains("spread-limit"));
+    }
+
+    #[test]
+    fn test_parse_variant() {
+        assert_eq!(Config::parse(CONFIG).unwrap().variant, GameVariant::Holdem);
+
+        let omaha = Config::parse(&CONFIG.replace("seed = 7", "variant = \"omaha-hi-lo\"")).unwrap();
+        assert_eq!(omaha.game().unwrap().variant, GameVariant::OmahaHiLo);
+
+        let short_deck = Config::parse(&CONFIG.replace("seed = 7", "variant = \"short-deck\"\nante = 5")).unwrap();
+        let game = short_deck.game().unwrap();
+        assert_eq!((game.variant, game.ante), (GameVariant::ShortDeck, 5));
+
+        let razz = Config::parse(&CONFIG.replace("seed = 7", "variant = \"razz\"\nbetting = \"fixed-limit\"")).unwrap();
+        assert_eq!(razz.game().unwrap().variant, GameVariant::Razz);
+
+        let triple_draw = Config::parse(&CONFIG.replace("seed = 7", "variant = \"2-7-triple-draw\"\nbetting = \"fixed-limit\"")).unwrap();
+        assert_eq!(triple_draw.game().unwrap().variant, GameVariant::TripleDraw);
+
+        assert!(Config::parse(&CONFIG.replace("seed = 7", "variant = \"courchevel\"")).unwrap_err().contains("courchevel"));
+        assert_eq!(Config::parse(CONFIG).unwrap().ante, 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Config::parse("[table]\nbuy_in = 1").unwrap_err().contains("blind_price"));
+        assert!(Config::parse(&CONFIG.replace("calling-station", "telepath")).unwrap_err().contains("telepath"));
+        assert_eq!(Config::parse(&CONFIG.replace("calling-station", "human")).unwrap().human_seat(), Some(1));
+
+        assert!(Config::parse(&CONFIG.replace("seed = 7", "seed = -7")).unwrap_err().contains("seed"));
+        assert!(Config::parse(&CONFIG.replace("seed = 7", "seed = \"7\"")).unwrap_err().contains("seed"));
+        assert_eq!(Config::parse(&CONFIG.replace("seed = 7", "")).unwrap().seed, 0);
+
+        let stud = CONFIG.replace("seed = 7", "variant = \"stud\"\nbetting = \"fixed-limit\"")
+            + &(3..=8).map(|seat| format!("[[players]]\nname = \"P{}\"\nseat = {}\nagent = \"calling-station\"\n", seat, seat + 1)).collect::<String>();
+        assert!(Config::parse(&stud).unwrap_err().contains("at most 7 players"));
+
+        let remote = Config::parse(&CONFIG.replace("calling-station", "remote")).unwrap();
+        assert_eq!(remote.agents(&Rc::default()).keys().collect::<Vec<_>>(), vec![&2]);
+    }
+}
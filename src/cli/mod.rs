@@ -0,0 +1,911 @@
+mod config;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use clap::{Args, Parser, Subcommand};
+use crate::cli::config::Config;
+#[cfg(feature = "server")]
+use crate::cli::config::AgentConfig;
+use llm_holdem::deck::Card;
+use llm_holdem::game::poker::agent::Agent;
+use llm_holdem::game::poker::dealer::{play_hand, PlayedHand};
+use llm_holdem::game::poker::error::PokerGameError;
+use llm_holdem::game::poker::evaluator::{best_hand, equity};
+use llm_holdem::game::poker::variant::GameVariant;
+use llm_holdem::game::poker::event::{write_json_lines, HandEvent, HandEventListener};
+use llm_holdem::game::poker::hand::StartHandParams;
+use llm_holdem::game::poker::match_runner::{HandResult, MatchReport, MatchRunner};
+use llm_holdem::game::poker::phh::PhhHand;
+use llm_holdem::game::poker::pokerstars::{to_pokerstars, ExportParams};
+use llm_holdem::game::poker::rating::RatingLadder;
+use llm_holdem::game::poker::stats::{HandRecord, StatsTracker};
+use llm_holdem::game::poker::table::{describe, TableState};
+use llm_holdem::game::poker::PokerGame;
+#[cfg(feature = "tui")]
+use llm_holdem::game::poker::spectator::Spectator;
+#[cfg(feature = "server")]
+use llm_holdem::game::poker::api::TableApi;
+#[cfg(feature = "server")]
+use llm_holdem::game::poker::server::{play_remote, spectate, HoleCards, Hub, ServerMessage, TableServer};
+#[cfg(feature = "server")]
+use llm_holdem::game::poker::event::HandEventKind;
+#[cfg(feature = "server")]
+use std::time::Duration;
+#[cfg(feature = "sqlite")]
+use llm_holdem::game::poker::storage::Storage;
+
+#[derive(Parser)]
+#[command(name = "llm-holdem", about = "Texas Hold'em tables for LLM agents")]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play hands at the configured table, printing every action
+    Play {
+        #[arg(long, default_value = "table.toml")]
+        config: PathBuf,
+        #[arg(long, default_value = "output")]
+        output: PathBuf,
+        #[arg(long, default_value_t = 1)]
+        hands: u32,
+        #[command(flatten)]
+        view: ViewArgs,
+    },
+    /// Play a cash-game match and report bb/100 per player. A new match starts the hand logs in
+    /// the output directory over; ratings and the SQLite store carry on.
+    Match {
+        #[arg(long, default_value = "table.toml")]
+        config: PathBuf,
+        #[arg(long, default_value = "output")]
+        output: PathBuf,
+        /// Hands in the whole match, counting those played before a `--resume`
+        #[arg(long, default_value_t = 100)]
+        hands: u32,
+        /// Continue the unfinished match left in the output directory, from its table snapshot
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Play a freezeout with rising blinds until one player has every chip
+    Tournament {
+        #[arg(long, default_value = "table.toml")]
+        config: PathBuf,
+        #[arg(long, default_value = "output")]
+        output: PathBuf,
+    },
+    /// Replay a hand history in PHH format
+    Replay {
+        file: PathBuf,
+        #[command(flatten)]
+        view: ViewArgs,
+    },
+    /// Monte Carlo equity of hole cards against random hands
+    Equity {
+        /// Hole cards, e.g. `AsKd`
+        cards: String,
+        #[arg(long, default_value = "")]
+        board: String,
+        /// `holdem`, `short-deck`, `omaha`, `omaha-hi-lo`, `stud`, `razz`, `five-card-draw` or
+        /// `2-7-triple-draw`
+        #[arg(long, default_value = "holdem")]
+        variant: String,
+        #[arg(long, default_value_t = 1)]
+        opponents: usize,
+        #[arg(long, default_value_t = 10_000)]
+        iterations: u32,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Best five-card hand out of five to seven cards
+    Eval {
+        cards: String,
+    },
+    /// Ratings accumulated by matches and tournaments
+    Leaderboard {
+        #[arg(long, default_value = "output")]
+        output: PathBuf,
+    },
+    /// Host the configured table over WebSocket; players with `agent = "remote"` connect to
+    /// `/seat/<n>?token=<token>` with the token printed at start, spectators to `/spectate`
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(long, default_value = "table.toml")]
+        config: PathBuf,
+        #[arg(long, default_value = "output")]
+        output: PathBuf,
+        #[arg(long, default_value_t = 1)]
+        hands: u32,
+        #[arg(long, default_value = "127.0.0.1:9000")]
+        address: String,
+        /// Send spectators every player's hole cards once the hand is over
+        #[arg(long)]
+        reveal_after_hand: bool,
+        /// Seconds to wait for remote players to connect and to answer
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Manage tables over an HTTP REST API
+    #[cfg(feature = "server")]
+    Api {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+        /// Every table's seed derives from this and its id
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Play a seat of a served table, or watch it when the URL ends in `/spectate`
+    #[cfg(feature = "server")]
+    Connect {
+        /// e.g. `ws://127.0.0.1:9000/seat/2?token=<token printed by serve>`
+        url: String,
+        #[arg(long, default_value = "Remote")]
+        name: String,
+        /// "calling-station", "random", "equity" or "human"
+        #[arg(long, default_value = "human")]
+        agent: String,
+        /// Answer with an external program instead, as `agent = "command"` does
+        #[arg(long)]
+        command: Option<String>,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+}
+
+#[derive(Args)]
+struct ViewArgs {
+    /// Follow the table in a full-screen view instead of the printed log
+    #[arg(long)]
+    tui: bool,
+    /// Show every hole card in the full-screen view; `g` toggles it while watching
+    #[arg(long)]
+    god: bool,
+    /// Milliseconds the full-screen view waits after each event
+    #[arg(long, default_value_t = 600)]
+    delay: u64,
+}
+
+impl ViewArgs {
+    #[cfg(feature = "tui")]
+    fn spectator(&self) -> Result<Option<Spectator>, String> {
+        if !self.tui {
+            return Ok(None);
+        }
+        let spectator = Spectator::open(std::time::Duration::from_millis(self.delay), self.god)
+            .map_err(|e| format!("cannot open the full-screen view: {}", e))?;
+        Ok(Some(spectator))
+    }
+
+    #[cfg(not(feature = "tui"))]
+    fn spectator(&self) -> Result<Option<std::convert::Infallible>, String> {
+        match self.tui {
+            true => Err(String::from("built without the `tui` feature")),
+            false => Ok(None),
+        }
+    }
+}
+
+/// Reads card codes with or without spaces between them, e.g. `As Kd` or `AsKd`
+fn parse_cards(text: &str) -> Result<Vec<Card>, String> {
+    let compact: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.chunks(2)
+        .map(|code| {
+            let code: String = code.iter().collect();
+            Card::from_code(&code).ok_or_else(|| format!("invalid card `{}`", code))
+        })
+        .collect()
+}
+
+/// Plays hands for a command and writes their logs to the output directory: the event log
+/// (`events.jsonl`), PokerStars histories (`hands.txt`), one PHH file per hand and, with the
+/// `sqlite` feature, `session.sqlite`.
+struct Session {
+    output: PathBuf,
+    config: Config,
+    agents: BTreeMap<usize, Box<dyn Agent>>,
+    /// What a human player sees, fed by every hand's events
+    table: Rc<RefCell<TableState>>,
+    #[cfg(feature = "tui")]
+    spectator: Option<Rc<RefCell<Spectator>>>,
+    #[cfg(feature = "server")]
+    hub: Option<Hub>,
+    stats: StatsTracker,
+    verbose: bool,
+    #[cfg(feature = "sqlite")]
+    storage: (Storage, i64),
+}
+
+impl Session {
+    /// With `table`, hands go on being recorded to that SQLite table of an interrupted match
+    /// instead of a new session's
+    fn open(config: Config, output: &Path, stats: StatsTracker, verbose: bool, table: Option<i64>) -> Result<Session, String> {
+        fs::create_dir_all(output.join("hands")).map_err(|e| format!("cannot create {}: {}", output.display(), e))?;
+
+        #[cfg(feature = "sqlite")]
+        let storage = {
+            let storage = Storage::open(&output.join("session.sqlite")).map_err(|e| e.to_string())?;
+            let table = match table {
+                Some(table) => table,
+                None => {
+                    let session = storage.create_session(&config.table_name).map_err(|e| e.to_string())?;
+                    storage.create_table(session, &config.table_name, config.seats, config.blind_price).map_err(|e| e.to_string())?
+                }
+            };
+            (storage, table)
+        };
+        #[cfg(not(feature = "sqlite"))]
+        let _ = table;
+
+        let table = Rc::new(RefCell::new(TableState::new()));
+        Ok(Session {
+            output: output.to_path_buf(),
+            agents: config.agents(&table),
+            table,
+            #[cfg(feature = "tui")]
+            spectator: None,
+            #[cfg(feature = "server")]
+            hub: None,
+            config,
+            stats,
+            verbose,
+            #[cfg(feature = "sqlite")]
+            storage,
+        })
+    }
+
+    #[cfg(feature = "tui")]
+    fn watching(&self) -> bool {
+        self.spectator.is_some()
+    }
+
+    #[cfg(not(feature = "tui"))]
+    fn watching(&self) -> bool {
+        false
+    }
+
+    /// The SQLite table the hands are recorded to
+    #[cfg(feature = "sqlite")]
+    fn table_id(&self) -> Option<i64> {
+        Some(self.storage.1)
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    fn table_id(&self) -> Option<i64> {
+        None
+    }
+
+    /// Drops every hand after the first `hands` from the logs. A match cut short between
+    /// logging a hand and saving its snapshot deals that hand again when resumed, so its first
+    /// logs go; a new match starts from empty ones.
+    fn trim(&self, hands: u32) -> Result<(), String> {
+        let keep = |file: &str, starts_hand: &dyn Fn(&str) -> bool| -> Result<(), String> {
+            let path = self.output.join(file);
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+            };
+            let mut started = 0;
+            let kept: String = text.split_inclusive('\n')
+                .take_while(|line| {
+                    if starts_hand(line) {
+                        started += 1;
+                    }
+                    started <= hands
+                })
+                .collect();
+            write_file(&path, &kept)
+        };
+        keep("events.jsonl", &|line| line.contains("\"event\":\"HandStarted\""))?;
+        keep("hands.txt", &|line| line.starts_with("PokerStars Hand #"))?;
+
+        let directory = self.output.join("hands");
+        let entries = fs::read_dir(&directory).map_err(|e| format!("cannot read {}: {}", directory.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let number = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse::<u32>().ok());
+            if path.extension().is_some_and(|e| e == "phh") && number.is_some_and(|number| number > hands) {
+                fs::remove_file(&path).map_err(|e| format!("cannot remove {}: {}", path.display(), e))?;
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        self.storage.0.delete_hands_after(self.storage.1, hands).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn append(&self, file: &str, text: &[u8]) -> Result<(), String> {
+        OpenOptions::new().create(true).append(true).open(self.output.join(file))
+            .and_then(|mut f| f.write_all(text))
+            .map_err(|e| format!("cannot write {}: {}", file, e))
+    }
+
+    fn play(&mut self, params: StartHandParams, hand_number: u32, show_hud: bool) -> Result<BTreeMap<usize, i64>, String> {
+        let table = self.table.clone();
+        let mut listeners: Vec<Box<dyn HandEventListener>> = vec![Box::new(move |event: &HandEvent| table.borrow_mut().apply(event))];
+        let human_seat = self.config.human_seat();
+        #[cfg(feature = "tui")]
+        let verbose = match &self.spectator {
+            Some(spectator) => {
+                let spectator = spectator.clone();
+                listeners.push(Box::new(move |event: &HandEvent| spectator.borrow_mut().on_event(event)));
+                false
+            }
+            None => self.verbose,
+        };
+        #[cfg(not(feature = "tui"))]
+        let verbose = self.verbose;
+        #[cfg(feature = "server")]
+        if let Some(hub) = &self.hub {
+            listeners.push(Box::new(hub.clone()));
+        }
+        // The full log deals every hole card face up, so a human only sees their own table
+        if verbose && human_seat.is_none() {
+            let names: BTreeMap<u32, String> = params.players.iter().map(|p| (p.seat, p.player.name.clone())).collect();
+            let variant = params.variant;
+            listeners.push(Box::new(move |event: &HandEvent| println!("{}", describe(event, &names, variant))));
+        }
+        #[cfg(feature = "sqlite")]
+        let recorder = {
+            let recorder = self.storage.0.recorder(self.storage.1, hand_number);
+            listeners.push(Box::new(recorder.clone()));
+            recorder
+        };
+
+        let hud = if show_hud { Some(&self.stats) } else { None };
+        let PlayedHand { hand, winnings } = play_hand(params, &mut self.agents, hud, listeners).map_err(|e| e.to_string())?;
+        self.stats.record_hand(&HandRecord::from_events(&hand.events));
+        #[cfg(feature = "server")]
+        if let Some(hub) = &self.hub {
+            hub.end_hand();
+        }
+        if let Some(seat) = human_seat {
+            print!("{}", self.table.borrow().render(Some(seat), false));
+        }
+
+        #[cfg(feature = "sqlite")]
+        if let Some(error) = recorder.take_error() {
+            return Err(error.to_string());
+        }
+        #[cfg(feature = "tui")]
+        if let Some(error) = self.spectator.as_ref().and_then(|s| s.borrow_mut().take_error()) {
+            return Err(error.to_string());
+        }
+
+        let mut events = vec![];
+        write_json_lines(&hand.events, &mut events).map_err(|e| e.to_string())?;
+        self.append("events.jsonl", &events)?;
+
+        let history = to_pokerstars(&hand.events, &ExportParams {
+            hand_number: hand_number as u64,
+            table_name: self.config.table_name.clone(),
+            max_seats: self.config.seats,
+        }).map_err(|e| e.to_string())?;
+        self.append("hands.txt", format!("{}\n\n", history).as_bytes())?;
+
+        let mut phh = PhhHand::from_events(&hand.events).map_err(|e| e.to_string())?;
+        phh.table = Some(self.config.table_name.clone());
+        phh.hand = Some(hand_number as u64);
+        let path = self.output.join("hands").join(format!("{:06}.phh", hand_number));
+        fs::write(&path, phh.to_toml()).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+
+        Ok(winnings)
+    }
+}
+
+/// A hand that failed outside the engine, carried through the match and tournament runners
+struct Failure(String);
+
+impl From<PokerGameError> for Failure {
+    fn from(error: PokerGameError) -> Self {
+        Failure(error.to_string())
+    }
+}
+
+fn update_ratings(output: &Path, record: impl FnOnce(&mut RatingLadder)) -> Result<RatingLadder, String> {
+    let path = output.join("ratings.tsv");
+    let mut ladder = RatingLadder::load(&path).map_err(|e| e.to_string())?;
+    record(&mut ladder);
+    ladder.save(&path).map_err(|e| e.to_string())?;
+    Ok(ladder)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+/// Plays up to `hands` hands, stopping early when a player is left alone or the viewer quits
+fn play_hands(game: &mut PokerGame, session: &mut Session, hands: u32) -> Result<(), String> {
+    for _ in 0..hands {
+        #[cfg(feature = "tui")]
+        if session.spectator.as_ref().is_some_and(|s| s.borrow().quit) {
+            break;
+        }
+        let hand_number = game.hand_number + 1;
+        let params = match game.next_hand() {
+            Some(params) => params,
+            None => break,
+        };
+        let winnings = session.play(params, hand_number, game.show_hud)?;
+        game.apply_winnings(&winnings);
+        if !session.watching() {
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn play(config: &Path, output: &Path, hands: u32, view: &ViewArgs) -> Result<(), String> {
+    let config = Config::load(config)?;
+    if view.tui && config.human_seat().is_some() {
+        return Err(String::from("a human player needs the terminal, so --tui cannot be used"));
+    }
+    let mut game = config.game()?;
+    let mut session = Session::open(config, output, StatsTracker::new(), true, None)?;
+    #[cfg(feature = "tui")]
+    {
+        session.spectator = view.spectator()?.map(|spectator| Rc::new(RefCell::new(spectator)));
+    }
+    #[cfg(not(feature = "tui"))]
+    view.spectator()?;
+
+    play_hands(&mut game, &mut session, hands)?;
+    #[cfg(feature = "tui")]
+    if let Some(spectator) = session.spectator.take() {
+        spectator.borrow_mut().hold().map_err(|e| e.to_string())?;
+    }
+
+    for player in &game.players {
+        println!("Seat {} {}: {}", player.seat, player.player.name, player.cash_amount);
+    }
+    Ok(())
+}
+
+fn run_match(config: &Path, output: &Path, hands: u32, resume: bool) -> Result<(), String> {
+    let config = Config::load(config)?;
+    let snapshot = output.join("snapshot.toml");
+    let results_path = output.join("results.tsv");
+    let (mut game, log) = if resume {
+        let game = PokerGame::load(&snapshot).map_err(|e| format!("cannot resume from {}: {}", snapshot.display(), e))?;
+        let log = read_results(&results_path, game.hand_number)?;
+        if log.finished {
+            return Err(format!("the match in {} is over", output.display()));
+        }
+        (game, log)
+    } else {
+        (config.game()?, MatchLog::default())
+    };
+    let mut session = Session::open(config, output, game.stats.clone(), false, log.table)?;
+    // The snapshot is the last hand the match is sure to have played
+    session.trim(game.hand_number)?;
+    let mut results = log.results;
+    write_file(&results_path, &results_header(session.table_id()))?;
+    session.append("results.tsv", results.iter().map(results_line).collect::<String>().as_bytes())?;
+    let runner = MatchRunner { hands: 1, auto_rebuy: true };
+
+    // One hand at a time, so the snapshot on disk is never more than a hand behind
+    while game.hand_number < hands {
+        let show_hud = game.show_hud;
+        let hand_number = game.hand_number + 1;
+        let played = runner.run(&mut game, |params| session.play(params, hand_number, show_hud).map_err(Failure))
+            .map_err(|Failure(e)| e)?;
+        if played.results.is_empty() {
+            break;
+        }
+
+        session.append("results.tsv", played.results.iter().map(results_line).collect::<String>().as_bytes())?;
+        game.stats = session.stats.clone();
+        game.save(&snapshot).map_err(|e| e.to_string())?;
+        results.extend(played.results);
+    }
+
+    // Rated once, on every hand of the match, however many runs it took
+    let report = MatchReport::new(&game, results);
+    write_file(&output.join("report.txt"), &report.to_string())?;
+    write_file(&output.join("report.json"), &report.to_json())?;
+    write_file(&output.join("stats.csv"), &game.stats.to_csv())?;
+    let ladder = update_ratings(output, |ladder| ladder.record_match(&report))?;
+    session.append("results.tsv", b"finished\n")?;
+
+    print!("{}", report);
+    println!();
+    print!("{}", ladder);
+    Ok(())
+}
+
+/// What `results.tsv` keeps about a match, so an interrupted one can be resumed
+#[derive(Default)]
+struct MatchLog {
+    /// SQLite table the match records its hands to
+    table: Option<i64>,
+    results: Vec<HandResult>,
+    finished: bool,
+}
+
+/// First line of `results.tsv`, naming the match's SQLite table when there is one
+fn results_header(table: Option<i64>) -> String {
+    match table {
+        Some(table) => format!("table\t{}\n", table),
+        None => String::new(),
+    }
+}
+
+/// One line of `results.tsv`: hand number, seed and `id=amount` winnings
+fn results_line(result: &HandResult) -> String {
+    let winnings: Vec<String> = result.winnings.iter().map(|(id, amount)| format!("{}={}", id, amount)).collect();
+    format!("{}\t{}\t{}\n", result.hand_number, result.seed, winnings.join(" "))
+}
+
+/// The match in `results.tsv`, keeping the results of the hands before `hand_number`; a last
+/// `finished` line closes the match
+fn read_results(path: &Path, hand_number: u32) -> Result<MatchLog, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let mut log = MatchLog::default();
+
+    for line in text.lines() {
+        let invalid = || format!("invalid result `{}` in {}", line, path.display());
+        if line == "finished" {
+            log.finished = true;
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let ["table", table] = fields[..] {
+            log.table = Some(table.parse().map_err(|_| invalid())?);
+            continue;
+        }
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        let mut winnings = BTreeMap::new();
+        for entry in fields[2].split_whitespace() {
+            let (id, amount) = entry.split_once('=').ok_or_else(invalid)?;
+            winnings.insert(id.parse().map_err(|_| invalid())?, amount.parse().map_err(|_| invalid())?);
+        }
+        let result = HandResult {
+            hand_number: fields[0].parse().map_err(|_| invalid())?,
+            seed: fields[1].parse().map_err(|_| invalid())?,
+            winnings,
+        };
+        if result.hand_number < hand_number {
+            log.results.push(result);
+        }
+    }
+
+    Ok(log)
+}
+
+fn run_tournament(config: &Path, output: &Path) -> Result<(), String> {
+    let config = Config::load(config)?;
+    let tournament = config.tournament();
+    let mut game = config.game()?;
+    let mut session = Session::open(config, output, StatsTracker::new(), false, None)?;
+    let mut hand_number = 0;
+    let show_hud = game.show_hud;
+
+    let result = tournament.run(&mut game, |params| {
+        hand_number += 1;
+        session.play(params, hand_number, show_hud).map_err(Failure)
+    }).map_err(|Failure(e)| e)?;
+
+    let names: BTreeMap<usize, String> = game.players.iter().map(|p| (p.player.id, p.player.name.clone())).collect();
+    let mut results = format!("{} hands\n", result.hands);
+    for (player_id, rank) in &result.finishing_order {
+        results.push_str(&format!("{:>3}. {}\n", rank, names[player_id]));
+    }
+    write_file(&output.join("results.txt"), &results)?;
+
+    let ladder = update_ratings(output, |ladder| {
+        for (player_id, name) in &names {
+            ladder.register(*player_id, name);
+        }
+        ladder.record_finishing_order(&result.finishing_order);
+    })?;
+
+    print!("{}", results);
+    println!();
+    print!("{}", ladder);
+    Ok(())
+}
+
+fn replay(file: &Path, view: &ViewArgs) -> Result<(), String> {
+    let text = fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file.display(), e))?;
+    let phh = PhhHand::parse(&text).map_err(|e| e.to_string())?;
+    let hand = phh.verify().map_err(|e| e.to_string())?;
+
+    #[cfg(feature = "tui")]
+    if let Some(mut spectator) = view.spectator()? {
+        for event in &hand.events {
+            spectator.show(event).map_err(|e| e.to_string())?;
+        }
+        return spectator.hold().map_err(|e| e.to_string());
+    }
+    #[cfg(not(feature = "tui"))]
+    view.spectator()?;
+
+    let names: BTreeMap<u32, String> = hand.active.iter()
+        .chain(hand.folds.iter().map(|fold| &fold.player))
+        .map(|p| (p.seat, p.player.name.clone()))
+        .collect();
+    for event in &hand.events {
+        println!("{}", describe(event, &names, hand.variant));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+fn serve(config: &Path, output: &Path, hands: u32, address: &str, reveal_after_hand: bool, timeout: u64) -> Result<(), String> {
+    let config = Config::load(config)?;
+    let remote: Vec<(usize, u32, String)> = config.remote_players().iter().map(|p| (p.id, p.seat, p.name.clone())).collect();
+    let mut game = config.game()?;
+    let hole_cards = if reveal_after_hand { HoleCards::AfterHand } else { HoleCards::Hidden };
+    let server = TableServer::bind(address, hole_cards).map_err(|e| format!("cannot listen on {}: {}", address, e))?;
+    let timeout = Duration::from_secs(timeout);
+
+    let mut session = Session::open(config, output, StatsTracker::new(), true, None)?;
+    for (player_id, seat, name) in &remote {
+        session.agents.insert(*player_id, Box::new(server.hub.agent(*seat, name, timeout)));
+    }
+    session.hub = Some(server.hub.clone());
+
+    println!("Listening on ws://{}: spectators on /spectate", server.address);
+    for (_, seat, name) in &remote {
+        let token = server.hub.token(*seat).expect("Expect remote seats to have a token");
+        println!("{} plays on ws://{}/seat/{}?token={}", name, server.address, seat, token);
+    }
+    let seats: Vec<u32> = remote.iter().map(|(_, seat, _)| *seat).collect();
+    if !seats.is_empty() && !server.hub.wait_for_seats(&seats, timeout) {
+        println!("Starting without every remote player; missing seats check or fold");
+    }
+
+    let played = play_hands(&mut game, &mut session, hands);
+    server.hub.close();
+    played?;
+
+    for player in &game.players {
+        println!("Seat {} {}: {}", player.seat, player.player.name, player.cash_amount);
+    }
+    Ok(())
+}
+
+/// Moves the client's table on by one message, printing what happened unless a human is
+/// following the rendered table instead
+#[cfg(feature = "server")]
+fn follow(table: &RefCell<TableState>, message: &ServerMessage, print: bool) {
+    let mut table = table.borrow_mut();
+    match message {
+        ServerMessage::Welcome { seat: Some(seat) } => println!("Connected to seat {}", seat),
+        ServerMessage::Welcome { seat: None } => println!("Watching the table"),
+        ServerMessage::Event { event } => {
+            let seen = table.history.len();
+            table.apply(event);
+            let names: BTreeMap<u32, String> = table.seats.iter().map(|s| (s.seat, s.name.clone())).collect();
+            if print {
+                match &event.kind {
+                    HandEventKind::HandStarted { .. } | HandEventKind::HoleCardsDealt { .. } | HandEventKind::CardsDrawn { .. } => println!("{}", describe(event, &names, table.variant)),
+                    _ => table.history[seen.min(table.history.len())..].iter().for_each(|line| println!("{}", line)),
+                }
+            }
+        }
+        ServerMessage::Turn { .. } => {}
+        ServerMessage::HandEnded => {
+            if print {
+                println!();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+fn connect(url: &str, name: &str, agent: &str, command: Option<String>, seed: u64) -> Result<(), String> {
+    let table = Rc::new(RefCell::new(TableState::new()));
+    if url.ends_with("/spectate") {
+        return spectate(url, |message| follow(&table, message, true)).map_err(|e| e.to_string());
+    }
+
+    let agent = match command {
+        Some(command) => AgentConfig::Command { command },
+        None => AgentConfig::from_name(agent).ok_or_else(|| format!("unknown agent `{}`", agent))?,
+    };
+    let print = agent != AgentConfig::Human;
+    let mut player = agent.build(name, seed, &table).ok_or("a remote seat cannot be played by another remote agent")?;
+    play_remote(url, player.as_mut(), |message| follow(&table, message, print)).map_err(|e| e.to_string())
+}
+
+fn show_equity(cards: &str, board: &str, variant: &str, opponents: usize, iterations: u32, seed: u64) -> Result<(), String> {
+    let variant = GameVariant::from_code(variant).ok_or_else(|| format!("unknown variant `{}`", variant))?;
+    let hole = parse_cards(cards)?;
+    let board = parse_cards(board)?;
+    if board.len() > variant.board_cards() {
+        return Err(format!("the board of {} has at most {} cards", variant, variant.board_cards()));
+    }
+    // Stud hands can be weighed on any street, the cards still to come dealt at random
+    if variant.is_stud() && (hole.len() < 3 || hole.len() > variant.hole_cards()) {
+        return Err(format!("{} is weighed with three to {} cards", variant, variant.hole_cards()));
+    }
+    if !variant.is_stud() && hole.len() != variant.hole_cards() {
+        return Err(format!("{} is played with {} hole cards", variant, variant.hole_cards()));
+    }
+
+    let share = equity(variant, &hole, &board, opponents, iterations, seed);
+    println!("{:.2}% against {} random hand(s) over {} boards", share * 100.0, opponents, iterations);
+    Ok(())
+}
+
+fn eval(cards: &str) -> Result<(), String> {
+    let cards = parse_cards(cards)?;
+    if cards.len() < 5 || cards.len() > 7 {
+        return Err(String::from("give five to seven cards"));
+    }
+
+    let value = best_hand(&cards);
+    let ranks: Vec<String> = value.ranks.iter().map(|r| r.code().to_string()).collect();
+    println!("{:?} ({})", value.category, ranks.join(" "));
+    Ok(())
+}
+
+fn leaderboard(output: &Path) -> Result<(), String> {
+    let ladder = RatingLadder::load(&output.join("ratings.tsv")).map_err(|e| e.to_string())?;
+    print!("{}", ladder);
+    Ok(())
+}
+
+impl Cli {
+    pub fn run(self) -> Result<(), String> {
+        match self.command {
+            Command::Play { config, output, hands, view } => play(&config, &output, hands, &view),
+            Command::Match { config, output, hands, resume } => run_match(&config, &output, hands, resume),
+            Command::Tournament { config, output } => run_tournament(&config, &output),
+            Command::Replay { file, view } => replay(&file, &view),
+            Command::Equity { cards, board, variant, opponents, iterations, seed } => show_equity(&cards, &board, &variant, opponents, iterations, seed),
+            Command::Eval { cards } => eval(&cards),
+            Command::Leaderboard { output } => leaderboard(&output),
+            #[cfg(feature = "server")]
+            Command::Serve { config, output, hands, address, reveal_after_hand, timeout } =>
+                serve(&config, &output, hands, &address, reveal_after_hand, timeout),
+            #[cfg(feature = "server")]
+            Command::Api { address, seed } => TableApi::new(seed)
+                .serve(&address, |bound| println!("REST API on http://{}/tables", bound))
+                .map_err(|e| format!("cannot serve on {}: {}", address, e)),
+            #[cfg(feature = "server")]
+            Command::Connect { url, name, agent, command, seed } => connect(&url, &name, &agent, command, seed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_holdem::deck::{Rank, Suit};
+
+    #[test]
+    fn test_parse_cards() {
+        assert_eq!(parse_cards("As Kd").unwrap(), parse_cards("AsKd").unwrap());
+        assert_eq!(parse_cards("Th").unwrap(), vec![Card { rank: Rank::Ten, suit: Suit::Hearts }]);
+        assert!(parse_cards("AsK").is_err());
+        assert!(parse_cards("").unwrap().is_empty());
+    }
+
+    /// Leaves `dir` as a match cut short after its last snapshot would: unfinished and unrated
+    fn interrupt(dir: &Path) {
+        let results = fs::read_to_string(dir.join("results.tsv")).unwrap();
+        fs::write(dir.join("results.tsv"), results.trim_end_matches("finished\n")).unwrap();
+        fs::remove_file(dir.join("ratings.tsv")).unwrap();
+    }
+
+    /// Plays the next hand of the interrupted match in `dir` as far as its logs, as a run killed
+    /// before it saves the hand's result and snapshot would
+    fn play_unsaved(config: &Path, dir: &Path) {
+        let mut game = PokerGame::load(&dir.join("snapshot.toml")).unwrap();
+        let log = read_results(&dir.join("results.tsv"), game.hand_number).unwrap();
+        let mut session = Session::open(Config::load(config).unwrap(), dir, game.stats.clone(), false, log.table).unwrap();
+        let hand_number = game.hand_number + 1;
+        MatchRunner { hands: 1, auto_rebuy: true }
+            .run(&mut game, |params| session.play(params, hand_number, false).map_err(Failure))
+            .map_err(|Failure(e)| e)
+            .unwrap();
+    }
+
+    fn hands_started(dir: &Path) -> usize {
+        fs::read_to_string(dir.join("events.jsonl")).unwrap().matches("\"event\":\"HandStarted\"").count()
+    }
+
+    #[test]
+    fn test_match_writes_reports_and_resumes() {
+        let dir = std::env::temp_dir().join(format!("llm-holdem-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("table.toml");
+        fs::write(&config, "
+            [table]
+            buy_in = 500
+            blind_price = 5
+            seed = 3
+
+            [[players]]
+            name = \"Station\"
+            agent = \"calling-station\"
+
+            [[players]]
+            name = \"Random\"
+            agent = \"random\"
+        ").unwrap();
+
+        run_match(&config, &dir, 3, false).unwrap();
+        assert!(run_match(&config, &dir, 5, true).unwrap_err().contains("is over"));
+        interrupt(&dir);
+        run_match(&config, &dir, 5, true).unwrap();
+
+        for file in ["report.txt", "report.json", "stats.csv", "ratings.tsv", "snapshot.toml", "events.jsonl", "hands.txt"] {
+            assert!(dir.join(file).exists(), "{} is missing", file);
+        }
+        assert_eq!(PokerGame::load(&dir.join("snapshot.toml")).unwrap().hand_number, 5);
+        assert!(dir.join("hands").join("000005.phh").exists());
+        replay(&dir.join("hands").join("000001.phh"), &ViewArgs { tui: false, god: false, delay: 0 }).unwrap();
+
+        // A new match starts its logs over
+        run_match(&config, &dir, 2, false).unwrap();
+        assert_eq!(hands_started(&dir), 2);
+        assert_eq!(fs::read_to_string(dir.join("hands.txt")).unwrap().matches("PokerStars Hand #").count(), 2);
+        assert!(!dir.join("hands").join("000003.phh").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resumed_match_plays_like_uninterrupted_one() {
+        let dir = std::env::temp_dir().join(format!("llm-holdem-cli-resume-{}", std::process::id()));
+        let (whole, split) = (dir.join("whole"), dir.join("split"));
+        fs::create_dir_all(&whole).unwrap();
+        fs::create_dir_all(&split).unwrap();
+        let config = dir.join("table.toml");
+        fs::write(&config, "
+            [table]
+            buy_in = 500
+            blind_price = 5
+            seed = 11
+
+            [[players]]
+            name = \"Random\"
+            agent = \"random\"
+
+            [[players]]
+            name = \"Equity\"
+            agent = \"equity\"
+            iterations = 20
+        ").unwrap();
+
+        run_match(&config, &whole, 6, false).unwrap();
+        run_match(&config, &split, 3, false).unwrap();
+        interrupt(&split);
+        play_unsaved(&config, &split);
+        assert_eq!(hands_started(&split), 4);
+        run_match(&config, &split, 6, true).unwrap();
+
+        // Everything but the timestamp, which leads every line
+        let events = |dir: &Path| fs::read_to_string(dir.join("events.jsonl")).unwrap().lines()
+            .map(|line| line[line.find(',').unwrap()..].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(events(&split), events(&whole));
+        for file in ["results.tsv", "report.json", "ratings.tsv"] {
+            assert_eq!(fs::read_to_string(split.join(file)).unwrap(), fs::read_to_string(whole.join(file)).unwrap(), "{} differs", file);
+        }
+        let histories = |dir: &Path| fs::read_to_string(dir.join("hands.txt")).unwrap().matches("PokerStars Hand #").count();
+        assert_eq!(histories(&split), 6);
+        assert_eq!(fs::read_dir(split.join("hands")).unwrap().count(), 6);
+        #[cfg(feature = "sqlite")]
+        {
+            let storage = Storage::open(&split.join("session.sqlite")).unwrap();
+            let hands: Vec<u32> = storage.connection().prepare("SELECT hand_number FROM hands ORDER BY id").unwrap()
+                .query_map([], |row| row.get(0)).unwrap()
+                .collect::<Result<_, _>>().unwrap();
+            assert_eq!(hands, vec![1, 2, 3, 4, 5, 6]);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::{rng};
+use rand::{rng, SeedableRng};
 use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -23,7 +24,9 @@ pub struct Card {
     pub suit: Suit,
 }
 
-#[derive(Debug)]
+/// With the `serde` feature a deck serialises the cards left in it, as codes.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deck {
     cards: Vec<Card>,
 }
@@ -43,16 +46,152 @@ impl fmt::Display for Card {
     }
 }
 
-#[derive(Debug)]
+impl Rank {
+    /// Single-character notation, `T` standing for ten
+    pub fn code(&self) -> char {
+        match self {
+            Rank::Two => '2', Rank::Three => '3', Rank::Four => '4', Rank::Five => '5',
+            Rank::Six => '6', Rank::Seven => '7', Rank::Eight => '8', Rank::Nine => '9',
+            Rank::Ten => 'T', Rank::Jack => 'J', Rank::Queen => 'Q', Rank::King => 'K',
+            Rank::Ace => 'A'
+        }
+    }
+
+    pub fn from_code(code: char) -> Option<Rank> {
+        let rank = match code {
+            '2' => Rank::Two, '3' => Rank::Three, '4' => Rank::Four, '5' => Rank::Five,
+            '6' => Rank::Six, '7' => Rank::Seven, '8' => Rank::Eight, '9' => Rank::Nine,
+            'T' => Rank::Ten, 'J' => Rank::Jack, 'Q' => Rank::Queen, 'K' => Rank::King,
+            'A' => Rank::Ace, _ => return None,
+        };
+        Some(rank)
+    }
+}
+
+impl Suit {
+    /// Lower-case initial of the suit
+    pub fn code(&self) -> char {
+        match self {
+            Suit::Hearts => 'h', Suit::Diamonds => 'd', Suit::Clubs => 'c', Suit::Spades => 's'
+        }
+    }
+
+    pub fn from_code(code: char) -> Option<Suit> {
+        let suit = match code {
+            'h' => Suit::Hearts, 'd' => Suit::Diamonds, 'c' => Suit::Clubs, 's' => Suit::Spades,
+            _ => return None,
+        };
+        Some(suit)
+    }
+}
+
+impl Card {
+    /// Two-character notation used by hand histories and JSON, e.g. `As`, `Td`, `2c`
+    pub fn code(&self) -> String {
+        format!("{}{}", self.rank.code(), self.suit.code())
+    }
+
+    /// Parses the notation produced by `code`
+    pub fn from_code(code: &str) -> Option<Card> {
+        let mut chars = code.chars();
+        let rank = Rank::from_code(chars.next()?)?;
+        let suit = Suit::from_code(chars.next()?)?;
+        if chars.next().is_some() {
+            return None;
+        }
+
+        Some(Card { rank, suit })
+    }
+}
+
+/// With the `serde` feature, cards serialise as their code (`"As"`), ranks as a one-character
+/// string (`"A"`, `"T"`, `"2"`) and suits as their lower-case initial (`"s"`).
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use super::{Card, Rank, Suit};
+
+    fn single_char<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(String, Option<char>), D::Error> {
+        let code = String::deserialize(deserializer)?;
+        let mut chars = code.chars();
+        let first = chars.next();
+        if chars.next().is_some() {
+            return Ok((code, None));
+        }
+        Ok((code, first))
+    }
+
+    impl Serialize for Rank {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.code().to_string())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Rank {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let (code, first) = single_char(deserializer)?;
+            first.and_then(Rank::from_code).ok_or_else(|| D::Error::custom(format!("invalid rank `{}`", code)))
+        }
+    }
+
+    impl Serialize for Suit {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.code().to_string())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Suit {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let (code, first) = single_char(deserializer)?;
+            first.and_then(Suit::from_code).ok_or_else(|| D::Error::custom(format!("invalid suit `{}`", code)))
+        }
+    }
+
+    impl Serialize for Card {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.code())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Card {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let code = String::deserialize(deserializer)?;
+            Card::from_code(&code).ok_or_else(|| D::Error::custom(format!("invalid card `{}`", code)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DeckError {
     ShuffleRoundsMustBeGreaterThanZero, 
 }
 
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            DeckError::ShuffleRoundsMustBeGreaterThanZero => "the deck must be shuffled at least once",
+        };
+        write!(f, "{}", res)
+    }
+}
+
+impl std::error::Error for DeckError {}
+
 impl Deck {
     pub fn create() -> Deck {
-        let mut deck = Vec::with_capacity(52);
+        Deck::with_ranks_from(2)
+    }
+
+    /// The 36-card short deck of sixes through aces
+    pub fn create_short() -> Deck {
+        Deck::with_ranks_from(6)
+    }
+
+    fn with_ranks_from(lowest: u8) -> Deck {
+        let mut deck = Vec::with_capacity(4 * (15 - lowest as usize));
         for suit in &[Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades] {
-            for rank in 2..=14 {
+            for rank in lowest..=14 {
                 let rank = match rank {
                     2 => Rank::Two, 3 => Rank::Three, 4 => Rank::Four, 5 => Rank::Five,
                     6 => Rank::Six, 7 => Rank::Seven, 8 => Rank::Eight, 9 => Rank::Nine,
@@ -65,8 +204,13 @@ impl Deck {
         Deck { cards: deck }
     }
 
+    /// A deck that deals exactly `cards`, in order
+    pub fn from_cards(cards: Vec<Card>) -> Deck {
+        Deck { cards }
+    }
+
     pub fn shuffle(&mut self, rounds: usize) -> Result<(), DeckError> {
-        if(rounds <= 0) {
+        if rounds == 0 {
            return Err(DeckError::ShuffleRoundsMustBeGreaterThanZero)
         }
 
@@ -77,12 +221,36 @@ impl Deck {
         Ok(())
     }
 
+    /// Shuffles the deck deterministically, so the same seed always yields the same card order.
+    ///
+    /// ```
+    /// use llm_holdem::deck::Deck;
+    ///
+    /// let mut first = Deck::create();
+    /// let mut second = Deck::create();
+    /// first.shuffle_with_seed(7);
+    /// second.shuffle_with_seed(7);
+    /// assert_eq!(first.get_next_card(), second.get_next_card());
+    /// ```
+    pub fn shuffle_with_seed(&mut self, seed: u64) {
+        self.cards.shuffle(&mut StdRng::seed_from_u64(seed));
+    }
+
+    /// Cards left to deal
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
     pub fn get_next_card(&mut self) -> Card {
         let first_card = self.cards.first().cloned().unwrap();
 
         self.cards.remove(0);
 
-        return first_card;
+        first_card
     }
 }
 
@@ -96,6 +264,13 @@ mod tests {
         assert_eq!(deck.cards.len(), 52, "Deck should have 52 cards");
     }
 
+    #[test]
+    fn test_create_short_deck_has_sixes_to_aces() {
+        let deck = Deck::create_short();
+        assert_eq!(deck.len(), 36);
+        assert!(deck.cards.iter().all(|card| card.rank >= Rank::Six));
+    }
+
     #[test]
     fn test_create_deck_has_unique_cards() {
         let deck = Deck::create();
@@ -110,9 +285,9 @@ mod tests {
     #[test]
     fn test_shuffle_changes_order() {
         let mut deck1 = Deck::create();
-        let mut deck2 = Deck::create();
+        let deck2 = Deck::create();
 
-        deck1.shuffle(1);
+        deck1.shuffle(1).unwrap();
 
         // It is *possible* for two shuffled decks to have the same order, but highly unlikely
         let has_same_order = deck1.cards.iter().zip(deck2.cards.iter()).all(|(a, b)| a == b);
@@ -128,6 +303,55 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_shuffle_with_same_seed_gives_same_order() {
+        let mut deck1 = Deck::create();
+        let mut deck2 = Deck::create();
+
+        deck1.shuffle_with_seed(42);
+        deck2.shuffle_with_seed(42);
+
+        assert_eq!(deck1.cards, deck2.cards, "Same seed should produce the same order");
+    }
+
+    #[test]
+    fn test_shuffle_with_different_seeds_gives_different_order() {
+        let mut deck1 = Deck::create();
+        let mut deck2 = Deck::create();
+
+        deck1.shuffle_with_seed(1);
+        deck2.shuffle_with_seed(2);
+
+        assert_ne!(deck1.cards, deck2.cards, "Different seeds should likely produce different orders");
+    }
+
+    #[test]
+    fn test_card_code() {
+        assert_eq!(Card { rank: Rank::Ace, suit: Suit::Spades }.code(), "As");
+        assert_eq!(Card { rank: Rank::Ten, suit: Suit::Diamonds }.code(), "Td");
+        assert_eq!(Card { rank: Rank::Two, suit: Suit::Clubs }.code(), "2c");
+    }
+
+    #[test]
+    fn test_card_from_code() {
+        for card in Deck::create().cards {
+            assert_eq!(Card::from_code(&card.code()), Some(card));
+        }
+        assert_eq!(Card::from_code("10h"), None);
+        assert_eq!(Card::from_code("Ax"), None);
+        assert_eq!(Card::from_code("??"), None);
+    }
+
+    #[test]
+    fn test_from_cards_deals_in_order() {
+        let ace = Card { rank: Rank::Ace, suit: Suit::Spades };
+        let king = Card { rank: Rank::King, suit: Suit::Hearts };
+        let mut deck = Deck::from_cards(vec![ace, king]);
+
+        assert_eq!(deck.get_next_card(), ace);
+        assert_eq!(deck.get_next_card(), king);
+    }
+
     #[test]
     fn test_get_next_card_reduces_deck_size() {
         let mut deck = Deck::create();
@@ -155,4 +379,18 @@ mod tests {
 
         assert_eq!(expected_card, actual_card, "Should return and remove the second card");
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_uses_card_codes() {
+        let card = Card { rank: Rank::Ace, suit: Suit::Spades };
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"As\"");
+        assert_eq!(serde_json::to_string(&Rank::Ten).unwrap(), "\"T\"");
+        assert_eq!(serde_json::to_string(&Suit::Hearts).unwrap(), "\"h\"");
+
+        assert_eq!(serde_json::from_str::<Card>("\"Td\"").unwrap(), Card { rank: Rank::Ten, suit: Suit::Diamonds });
+        assert_eq!(serde_json::from_str::<Rank>("\"2\"").unwrap(), Rank::Two);
+        assert!(serde_json::from_str::<Card>("\"10d\"").is_err());
+        assert!(serde_json::from_str::<Suit>("\"sh\"").is_err());
+    }
+}
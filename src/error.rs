@@ -0,0 +1,131 @@
+//! One error type for code that mixes the modules of this crate, each variant keeping the
+//! original error as its [`source`](std::error::Error::source).
+
+use std::{fmt, io};
+use crate::deck::DeckError;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::phh::PhhError;
+
+#[derive(Debug)]
+pub enum Error {
+    Game(PokerGameError),
+    Deck(DeckError),
+    Phh(PhhError),
+    Io(io::Error),
+    #[cfg(feature = "sqlite")]
+    Storage(rusqlite::Error),
+    #[cfg(feature = "server")]
+    WebSocket(tungstenite::Error),
+    #[cfg(feature = "server")]
+    Json(serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Game(error) => write!(f, "{}", error),
+            Error::Deck(error) => write!(f, "{}", error),
+            Error::Phh(error) => write!(f, "invalid hand history: {}", error),
+            Error::Io(error) => write!(f, "{}", error),
+            #[cfg(feature = "sqlite")]
+            Error::Storage(error) => write!(f, "database error: {}", error),
+            #[cfg(feature = "server")]
+            Error::WebSocket(error) => write!(f, "connection error: {}", error),
+            #[cfg(feature = "server")]
+            Error::Json(error) => write!(f, "invalid JSON: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Game(error) => Some(error),
+            Error::Deck(error) => Some(error),
+            Error::Phh(error) => Some(error),
+            Error::Io(error) => Some(error),
+            #[cfg(feature = "sqlite")]
+            Error::Storage(error) => Some(error),
+            #[cfg(feature = "server")]
+            Error::WebSocket(error) => Some(error),
+            #[cfg(feature = "server")]
+            Error::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<PokerGameError> for Error {
+    fn from(error: PokerGameError) -> Self {
+        Error::Game(error)
+    }
+}
+
+impl From<DeckError> for Error {
+    fn from(error: DeckError) -> Self {
+        Error::Deck(error)
+    }
+}
+
+impl From<PhhError> for Error {
+    fn from(error: PhhError) -> Self {
+        Error::Phh(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Storage(error)
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<tungstenite::Error> for Error {
+    fn from(error: tungstenite::Error) -> Self {
+        Error::WebSocket(error)
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+    use crate::game::poker::phh::PhhHand;
+
+    fn replay(text: &str) -> Result<u32> {
+        let hand = PhhHand::parse(text)?.verify()?;
+        Ok(hand.pot)
+    }
+
+    #[test]
+    fn test_question_mark_keeps_the_original_error() {
+        let error = replay("variant = \"FB\"").unwrap_err();
+
+        assert!(matches!(error, Error::Phh(PhhError::UnsupportedVariant(_))));
+        assert_eq!(error.to_string(), "invalid hand history: unsupported variant FB");
+        assert_eq!(error.source().unwrap().to_string(), "unsupported variant FB");
+    }
+
+    #[test]
+    fn test_source_chain_reaches_the_engine() {
+        let error = Error::from(PhhError::from(PokerGameError::NotEnoughPlayers(1)));
+
+        let engine = error.source().and_then(|phh| phh.source()).unwrap();
+        assert_eq!(engine.to_string(), "a hand needs at least 2 players, 1 available");
+    }
+}
@@ -1,4 +1,4 @@
-mod poker;
+pub mod poker;
 
 use crate::player::Player;
 
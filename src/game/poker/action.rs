@@ -0,0 +1,234 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::deck::Card;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::hand::HandStage;
+
+/// A betting decision. Amounts are the chips put in by that action, a raise being the
+/// total contributed by the action including the call part.
+///
+/// With the `serde` feature an action is `{"type":"raise","amount":40}`, or `{"type":"fold"}`
+/// for actions without an amount, the same shape as in `HandEvent::to_json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "amount", rename_all = "lowercase"))]
+pub enum PokerAction {
+    Fold,
+    Check,
+    Call(u32),
+    Bet(u32),
+    Raise(u32),
+}
+
+impl PokerAction {
+    pub fn is_aggressive(&self) -> bool {
+        matches!(self, PokerAction::Bet(_) | PokerAction::Raise(_))
+    }
+
+    pub fn amount(&self) -> u32 {
+        match self {
+            PokerAction::Fold | PokerAction::Check => 0,
+            PokerAction::Call(amount) | PokerAction::Bet(amount) | PokerAction::Raise(amount) => *amount,
+        }
+    }
+
+    /// Reads `fold`, `check`, `call`, `bet 40` or `raise 120`, case-insensitively. A call
+    /// without an amount reads as `Call(0)`, to be fixed up by `LegalActions::coerce`.
+    pub fn parse(text: &str) -> Option<PokerAction> {
+        let text = text.trim().to_lowercase();
+        let mut words = text.split_whitespace();
+        let verb = words.next()?;
+        let amount = match words.next() {
+            Some(word) => Some(word.parse::<u32>().ok()?),
+            None => None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+
+        match (verb, amount) {
+            ("fold" | "folds", None) => Some(PokerAction::Fold),
+            ("check" | "checks", None) => Some(PokerAction::Check),
+            ("call" | "calls", amount) => Some(PokerAction::Call(amount.unwrap_or(0))),
+            ("bet" | "bets", Some(amount)) => Some(PokerAction::Bet(amount)),
+            ("raise" | "raises", Some(amount)) => Some(PokerAction::Raise(amount)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PokerAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PokerAction::Fold => write!(f, "folds"),
+            PokerAction::Check => write!(f, "checks"),
+            PokerAction::Call(amount) => write!(f, "calls {}", amount),
+            PokerAction::Bet(amount) => write!(f, "bets {}", amount),
+            PokerAction::Raise(amount) => write!(f, "raises {}", amount),
+        }
+    }
+}
+
+/// What the player to act may do. Amounts are chips put in by the action, as in
+/// `PokerAction`; folding is always allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegalActions {
+    pub can_check: bool,
+    /// Chips needed to call, when facing a bet
+    pub call: Option<u32>,
+    /// Smallest and largest opening bet, when nobody has bet this street
+    pub bet: Option<(u32, u32)>,
+    /// Smallest and largest raise, calling part included
+    pub raise: Option<(u32, u32)>,
+}
+
+impl LegalActions {
+    pub fn contains(&self, action: &PokerAction) -> bool {
+        let within = |range: Option<(u32, u32)>, amount: u32| range.is_some_and(|(min, max)| min <= amount && amount <= max);
+
+        match *action {
+            PokerAction::Fold => true,
+            PokerAction::Check => self.can_check,
+            PokerAction::Call(amount) => self.call == Some(amount),
+            PokerAction::Bet(amount) => within(self.bet, amount),
+            PokerAction::Raise(amount) => within(self.raise, amount),
+        }
+    }
+
+    /// `action` itself when it is legal, a bare call (`Call(0)` from `PokerAction::parse`)
+    /// taking the amount owed; otherwise an `IllegalAction` error saying what is allowed.
+    pub fn check(&self, action: PokerAction) -> Result<PokerAction, PokerGameError> {
+        let action = match (action, self.call) {
+            (PokerAction::Call(0), Some(amount)) => PokerAction::Call(amount),
+            _ => action,
+        };
+        if !self.contains(&action) {
+            return Err(PokerGameError::IllegalAction(action, *self));
+        }
+        Ok(action)
+    }
+
+    /// The legal action closest to `action`: checking when facing a bet folds, calling when
+    /// there is nothing to call checks, and bet or raise sizes are clamped to the allowed range.
+    pub fn coerce(&self, action: PokerAction) -> PokerAction {
+        let check_or_fold = if self.can_check { PokerAction::Check } else { PokerAction::Fold };
+        let call_or_check = self.call.map(PokerAction::Call).unwrap_or(check_or_fold);
+
+        match action {
+            PokerAction::Fold => PokerAction::Fold,
+            PokerAction::Check => check_or_fold,
+            PokerAction::Call(_) => call_or_check,
+            PokerAction::Bet(amount) | PokerAction::Raise(amount) => match (self.bet, self.raise) {
+                (Some((min, max)), _) => PokerAction::Bet(amount.clamp(min, max)),
+                (None, Some((min, max))) => PokerAction::Raise(amount.clamp(min, max)),
+                (None, None) => call_or_check,
+            },
+        }
+    }
+}
+
+impl fmt::Display for LegalActions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut options = vec![String::from("fold")];
+        if self.can_check {
+            options.push(String::from("check"));
+        }
+        if let Some(amount) = self.call {
+            options.push(format!("call {}", amount));
+        }
+        if let Some((min, max)) = self.bet {
+            options.push(if min == max { format!("bet {}", max) } else { format!("bet {}-{}", min, max) });
+        }
+        if let Some((min, max)) = self.raise {
+            options.push(if min == max { format!("raise {}", max) } else { format!("raise {}-{}", min, max) });
+        }
+
+        write!(f, "{}", options.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerAction {
+    pub seat: u32,
+    pub stage: HandStage,
+    pub action: PokerAction,
+}
+
+/// Reads the answer to a draw, case-insensitively: `discard 7s Th` names the cards to throw
+/// away by their codes, `stand pat` or a bare `discard` keeps them all.
+pub fn parse_discards(text: &str) -> Option<Vec<Card>> {
+    let text = text.trim().to_lowercase();
+    let mut words = text.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty());
+
+    match (words.next()?, words.clone().next()) {
+        ("stand" | "stands", Some("pat")) if words.nth(1).is_none() => Some(vec![]),
+        ("discard" | "discards", _) => words.map(|word| {
+            let mut chars = word.chars();
+            let rank = chars.next()?.to_ascii_uppercase();
+            Card::from_code(&format!("{}{}", rank, chars.as_str()))
+        }).collect(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facing_bet() -> LegalActions {
+        LegalActions { can_check: false, call: Some(20), bet: None, raise: Some((40, 500)) }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(PokerAction::parse(" Raise 120 "), Some(PokerAction::Raise(120)));
+        assert_eq!(PokerAction::parse("call"), Some(PokerAction::Call(0)));
+        assert_eq!(PokerAction::parse("checks"), Some(PokerAction::Check));
+        assert_eq!(PokerAction::parse("bet"), None);
+        assert_eq!(PokerAction::parse("fold 10"), None);
+        assert_eq!(PokerAction::parse("raise lots"), None);
+    }
+
+    #[test]
+    fn test_parse_discards() {
+        let card = |code| Card::from_code(code).unwrap();
+        assert_eq!(parse_discards("discard 7s Th"), Some(vec![card("7s"), card("Th")]));
+        assert_eq!(parse_discards("Discards 2C, kd"), Some(vec![card("2c"), card("Kd")]));
+        assert_eq!(parse_discards("stand pat"), Some(vec![]));
+        assert_eq!(parse_discards("discard"), Some(vec![]));
+        assert_eq!(parse_discards("discard 7x"), None);
+        assert_eq!(parse_discards("stand up"), None);
+        assert_eq!(parse_discards("fold"), None);
+    }
+
+    #[test]
+    fn test_contains_and_coerce() {
+        let legal = facing_bet();
+
+        assert!(legal.contains(&PokerAction::Call(20)));
+        assert!(!legal.contains(&PokerAction::Call(10)));
+        assert!(!legal.contains(&PokerAction::Check));
+        assert!(legal.contains(&PokerAction::Raise(500)));
+
+        assert_eq!(legal.coerce(PokerAction::Check), PokerAction::Fold);
+        assert_eq!(legal.coerce(PokerAction::Call(0)), PokerAction::Call(20));
+        assert_eq!(legal.coerce(PokerAction::Bet(25)), PokerAction::Raise(40));
+        assert_eq!(legal.coerce(PokerAction::Raise(900)), PokerAction::Raise(500));
+
+        let unopened = LegalActions { can_check: true, call: None, bet: Some((10, 300)), raise: None };
+        assert_eq!(unopened.coerce(PokerAction::Call(20)), PokerAction::Check);
+        assert_eq!(unopened.coerce(PokerAction::Raise(5)), PokerAction::Bet(10));
+        assert_eq!(legal.to_string(), "fold, call 20, raise 40-500");
+    }
+
+    #[test]
+    fn test_check_fills_in_a_bare_call() {
+        let legal = facing_bet();
+
+        assert_eq!(legal.check(PokerAction::Call(0)), Ok(PokerAction::Call(20)));
+        assert_eq!(legal.check(PokerAction::Raise(40)), Ok(PokerAction::Raise(40)));
+        assert_eq!(legal.check(PokerAction::Check), Err(PokerGameError::IllegalAction(PokerAction::Check, legal)));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::deck::Card;
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::view::PlayerView;
+
+/// Anything that can take a seat and decide: an LLM, a scripted bot or a human.
+///
+/// ```
+/// use llm_holdem::game::poker::action::PokerAction;
+/// use llm_holdem::game::poker::agent::Agent;
+/// use llm_holdem::game::poker::view::PlayerView;
+///
+/// /// Checks when it can, folds otherwise
+/// struct Nit;
+///
+/// impl Agent for Nit {
+///     fn name(&self) -> &str {
+///         "Nit"
+///     }
+///
+///     fn decide(&mut self, view: &PlayerView) -> PokerAction {
+///         if view.legal_actions.can_check { PokerAction::Check } else { PokerAction::Fold }
+///     }
+/// }
+/// ```
+pub trait Agent {
+    fn name(&self) -> &str;
+    fn decide(&mut self, view: &PlayerView) -> PokerAction;
+
+    /// Called before every hand with a seed derived from its deck seed. Agents drawing random
+    /// numbers reseed from it, so a hand plays the same however the session reached it,
+    /// resumed from a snapshot or not. Does nothing unless implemented.
+    fn new_hand(&mut self, _seed: u64) {}
+
+    /// Cards to throw away when `view.drawing` is set, in draw games. Stands pat unless
+    /// implemented.
+    fn discard(&mut self, _view: &PlayerView) -> Vec<Card> {
+        vec![]
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use serde_json::{json, Value};
+use crate::game::poker::agent::Agent;
+use crate::game::poker::bots::{CallingStation, EquityAgent, RandomAgent};
+use crate::game::poker::dealer::play_hand;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::event::HandEvent;
+use crate::game::poker::stats::HandRecord;
+use crate::game::poker::{CreatePokerGame, PokerGame};
+use crate::player::Player;
+
+/// Who plays a seat, as given when seating a player:
+/// `{"type":"equity","iterations":300}`. Only the crate's bots: a request never names a program
+/// for the host to run.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum AgentSpec {
+    CallingStation,
+    Random { seed: u64 },
+    Equity { iterations: u32 },
+}
+
+impl AgentSpec {
+    pub fn build(&self, name: &str, seed: u64) -> Box<dyn Agent> {
+        let name = name.to_string();
+        match self {
+            AgentSpec::CallingStation => Box::new(CallingStation { name }),
+            AgentSpec::Random { seed: offset } => Box::new(RandomAgent::new(&name, seed ^ offset)),
+            AgentSpec::Equity { iterations } => Box::new(EquityAgent { name, iterations: *iterations, seed }),
+        }
+    }
+}
+
+/// Body of `POST /tables/{id}/players`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SeatRequest {
+    pub player: Player,
+    pub seat: u32,
+    pub agent: AgentSpec,
+}
+
+/// Optional body of `POST /tables/{id}/start`: the number of hands to play before pausing
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct StartRequest {
+    pub hands: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStatus {
+    /// Seating players; no hand dealt yet
+    Open,
+    Running,
+    Paused,
+    /// Stopped by a request or because fewer than two players have chips; final
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    Game(PokerGameError),
+    NotFound(String),
+    BadRequest(String),
+    /// The table is in the wrong status for the request
+    Conflict(String),
+    /// A `POST` not declared as `application/json`
+    UnsupportedMediaType(String),
+}
+
+impl From<PokerGameError> for ApiError {
+    fn from(error: PokerGameError) -> Self {
+        ApiError::Game(error)
+    }
+}
+
+impl ApiError {
+    pub fn status(&self) -> u16 {
+        match self {
+            ApiError::Game(error) => match error {
+                PokerGameError::PlayerSeatNotFound(_) | PokerGameError::AgentNotFound(_) => 404,
+                PokerGameError::SeatAlreadyInUse
+                | PokerGameError::YouAreAlreadyAtTheGame()
+                | PokerGameError::NameAlreadyUsed(_)
+                | PokerGameError::NotEnoughPlayers(_)
+                | PokerGameError::TooManyPlayers(_, _) => 409,
+                PokerGameError::SeatMustBeGreaterThanZero
+                | PokerGameError::SeatGreaterThanAllowed(_, _)
+                | PokerGameError::CannotFundNonGreaterThanZeroValues(_)
+                | PokerGameError::NotEnoughChips(_, _)
+                | PokerGameError::CardAlreadyInHand(_)
+                | PokerGameError::CardNotInHand(_)
+                | PokerGameError::IllegalAction(_, _) => 422,
+                PokerGameError::NoMoreStages
+                | PokerGameError::AwardExceedsPot(_, _)
+                | PokerGameError::HandNotStarted
+                | PokerGameError::NoPendingDecision
+                | PokerGameError::NotDrawing(_)
+                | PokerGameError::DrawPending(_) => 500,
+            },
+            ApiError::NotFound(_) => 404,
+            ApiError::BadRequest(_) => 400,
+            ApiError::Conflict(_) => 409,
+            ApiError::UnsupportedMediaType(_) => 415,
+        }
+    }
+
+    /// `{"error":"SeatGreaterThanAllowed","detail":{"SeatGreaterThanAllowed":[9,6]},"message":"seat 9 does not exist, the table has 6 seats"}`
+    /// for game errors, `{"error":"NotFound","detail":"no table 3"}` for the others
+    pub fn body(&self) -> Value {
+        match self {
+            ApiError::Game(error) => {
+                let detail = serde_json::to_value(error).expect("Expect errors to serialise");
+                let code = match &detail {
+                    Value::String(name) => name.clone(),
+                    Value::Object(map) => map.keys().next().cloned().unwrap_or_default(),
+                    _ => String::new(),
+                };
+                json!({ "error": code, "detail": detail, "message": error.to_string() })
+            }
+            ApiError::NotFound(message) => json!({ "error": "NotFound", "detail": message }),
+            ApiError::BadRequest(message) => json!({ "error": "BadRequest", "detail": message }),
+            ApiError::Conflict(message) => json!({ "error": "Conflict", "detail": message }),
+            ApiError::UnsupportedMediaType(message) => json!({ "error": "UnsupportedMediaType", "detail": message }),
+        }
+    }
+}
+
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok(status: u16, body: Value) -> ApiResponse {
+        ApiResponse { status, body }
+    }
+}
+
+impl From<ApiError> for ApiResponse {
+    fn from(error: ApiError) -> Self {
+        ApiResponse { status: error.status(), body: error.body() }
+    }
+}
+
+pub struct ManagedTable {
+    pub game: PokerGame,
+    pub status: TableStatus,
+    pub agents: BTreeMap<usize, Box<dyn Agent>>,
+    /// Events of every hand played, oldest first
+    pub histories: Vec<Vec<HandEvent>>,
+    /// Hands left before pausing, when the start request set a limit
+    pub hands_left: Option<u32>,
+}
+
+impl ManagedTable {
+    fn state(&self, id: u32) -> Value {
+        let players: Vec<Value> = self.game.players.iter().map(|p| json!({
+            "seat": p.seat,
+            "id": p.player.id,
+            "name": p.player.name,
+            "stack": p.cash_amount,
+            "buy_ins": p.buy_ins,
+        })).collect();
+        json!({
+            "id": id,
+            "status": self.status,
+            "seats": self.game.seats,
+            "blind_price": self.game.blind_price,
+            "betting": self.game.betting,
+            "variant": self.game.variant,
+            "ante": self.game.ante,
+            "hand_number": self.game.hand_number,
+            "dealer_seat": self.game.dealer_seat,
+            "players": players,
+        })
+    }
+
+    /// Plays the next hand, stopping the table when it cannot be dealt
+    fn play_next(&mut self) -> Result<(), PokerGameError> {
+        let params = match self.game.next_hand() {
+            Some(params) => params,
+            None => {
+                self.status = TableStatus::Stopped;
+                return Ok(());
+            }
+        };
+
+        let hud = if self.game.show_hud { Some(&self.game.stats) } else { None };
+        let played = play_hand(params, &mut self.agents, hud, vec![])?;
+        self.game.apply_winnings(&played.winnings);
+        self.game.record_hand(&HandRecord::from_events(&played.hand.events));
+        self.histories.push(played.hand.events);
+
+        if let Some(left) = self.hands_left.as_mut() {
+            *left -= 1;
+            if *left == 0 {
+                self.hands_left = None;
+                self.status = TableStatus::Paused;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tables managed over HTTP. Requests and hands take turns on one thread, so a running table
+/// plays a hand between requests; agents need not be thread-safe.
+///
+/// | Method | Path | |
+/// |---|---|---|
+/// | `POST` | `/tables` | create from a `CreatePokerGame` body |
+/// | `GET` | `/tables` | every table's state |
+/// | `GET` | `/tables/{id}` | one table's state |
+/// | `POST` | `/tables/{id}/players` | seat a player, `SeatRequest` body |
+/// | `POST` | `/tables/{id}/start` | start or resume, optional `StartRequest` body |
+/// | `POST` | `/tables/{id}/pause` | pause after the current hand |
+/// | `POST` | `/tables/{id}/stop` | stop for good |
+/// | `GET` | `/tables/{id}/hands` | number and event count of every hand played |
+/// | `GET` | `/tables/{id}/hands/{n}` | events of hand `n`, counting from 1 |
+///
+/// Every `POST` must be sent as `Content-Type: application/json`, even without a body, which a
+/// web page cannot do across sites without the browser asking first.
+#[derive(Default)]
+pub struct TableApi {
+    pub tables: BTreeMap<u32, ManagedTable>,
+    /// Seeds every new table, so runs can be reproduced
+    pub seed: u64,
+}
+
+fn parse_body<T: serde::de::DeserializeOwned + Default>(body: &str) -> Result<T, ApiError> {
+    if body.trim().is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_str(body).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+fn parse_required<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+impl TableApi {
+    pub fn new(seed: u64) -> TableApi {
+        TableApi { tables: BTreeMap::new(), seed }
+    }
+
+    fn table(&mut self, id: &str) -> Result<(u32, &mut ManagedTable), ApiError> {
+        let not_found = || ApiError::NotFound(format!("no table {}", id));
+        let id: u32 = id.parse().map_err(|_| not_found())?;
+        self.tables.get_mut(&id).map(|table| (id, table)).ok_or_else(not_found)
+    }
+
+    /// Answers one request; `path` may carry a query string, which is ignored
+    pub fn handle(&mut self, method: &str, path: &str, body: &str) -> ApiResponse {
+        let path = path.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match self.route(method, &segments, body) {
+            Ok(response) => response,
+            Err(error) => error.into(),
+        }
+    }
+
+    fn route(&mut self, method: &str, segments: &[&str], body: &str) -> Result<ApiResponse, ApiError> {
+        match (method, segments) {
+            ("POST", ["tables"]) => {
+                let params: CreatePokerGame = parse_required(body)?;
+                let id = self.tables.keys().next_back().map_or(1, |last| last + 1);
+                let mut game = PokerGame::new(params);
+                game.seed = self.seed ^ id as u64;
+                let table = ManagedTable { game, status: TableStatus::Open, agents: BTreeMap::new(), histories: vec![], hands_left: None };
+                let state = table.state(id);
+                self.tables.insert(id, table);
+                Ok(ApiResponse::ok(201, state))
+            }
+            ("GET", ["tables"]) => {
+                let states: Vec<Value> = self.tables.iter().map(|(id, table)| table.state(*id)).collect();
+                Ok(ApiResponse::ok(200, Value::Array(states)))
+            }
+            ("GET", ["tables", id]) => {
+                let (id, table) = self.table(id)?;
+                Ok(ApiResponse::ok(200, table.state(id)))
+            }
+            ("POST", ["tables", id, "players"]) => {
+                let request: SeatRequest = parse_required(body)?;
+                let (id, table) = self.table(id)?;
+                if table.status == TableStatus::Stopped {
+                    return Err(ApiError::Conflict(String::from("the table is stopped")));
+                }
+                let player_id = request.player.id;
+                let agent = request.agent.build(&request.player.name, table.game.seed);
+                table.game.add_player(request.player, request.seat)?;
+                table.agents.insert(player_id, agent);
+                Ok(ApiResponse::ok(201, table.state(id)))
+            }
+            ("POST", ["tables", id, "start"]) => {
+                let request: StartRequest = parse_body(body)?;
+                let (id, table) = self.table(id)?;
+                if table.status == TableStatus::Stopped {
+                    return Err(ApiError::Conflict(String::from("the table is stopped")));
+                }
+                let funded = table.game.players.iter().filter(|p| p.cash_amount > 0).count();
+                if funded < 2 {
+                    return Err(PokerGameError::NotEnoughPlayers(funded as u32).into());
+                }
+                table.hands_left = request.hands.filter(|&hands| hands > 0);
+                table.status = TableStatus::Running;
+                Ok(ApiResponse::ok(200, table.state(id)))
+            }
+            ("POST", ["tables", id, "pause"]) => {
+                let (id, table) = self.table(id)?;
+                if table.status != TableStatus::Running {
+                    return Err(ApiError::Conflict(String::from("the table is not running")));
+                }
+                table.status = TableStatus::Paused;
+                Ok(ApiResponse::ok(200, table.state(id)))
+            }
+            ("POST", ["tables", id, "stop"]) => {
+                let (id, table) = self.table(id)?;
+                table.status = TableStatus::Stopped;
+                Ok(ApiResponse::ok(200, table.state(id)))
+            }
+            ("GET", ["tables", id, "hands"]) => {
+                let (_, table) = self.table(id)?;
+                let hands: Vec<Value> = table.histories.iter().enumerate()
+                    .map(|(i, events)| json!({ "hand": i + 1, "events": events.len() }))
+                    .collect();
+                Ok(ApiResponse::ok(200, Value::Array(hands)))
+            }
+            ("GET", ["tables", id, "hands", number]) => {
+                let (_, table) = self.table(id)?;
+                let events = number.parse::<usize>().ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|index| table.histories.get(index))
+                    .ok_or_else(|| ApiError::NotFound(format!("no hand {}", number)))?;
+                Ok(ApiResponse::ok(200, serde_json::to_value(events).expect("Expect events to serialise")))
+            }
+            _ => Err(ApiError::NotFound(format!("no route for {} /{}", method, segments.join("/")))),
+        }
+    }
+
+    /// Plays one hand at every running table. A table whose hand fails is stopped.
+    pub fn play_running(&mut self) -> bool {
+        let mut played = false;
+        for table in self.tables.values_mut().filter(|t| t.status == TableStatus::Running) {
+            if table.play_next().is_err() {
+                table.status = TableStatus::Stopped;
+            }
+            played = true;
+        }
+        played
+    }
+
+    /// Serves the API on `address` until the process ends, calling `on_listening` with the
+    /// bound address first
+    pub fn serve(mut self, address: &str, on_listening: impl FnOnce(SocketAddr)) -> io::Result<()> {
+        let server = tiny_http::Server::http(address).map_err(io::Error::other)?;
+        if let Some(bound) = server.server_addr().to_ip() {
+            on_listening(bound);
+        }
+        let json_header = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("Expect a valid header");
+
+        loop {
+            // Running tables keep playing; idle ones only wait for requests
+            let running = self.tables.values().any(|t| t.status == TableStatus::Running);
+            let wait = if running { Duration::ZERO } else { Duration::from_millis(200) };
+            if let Some(mut request) = server.recv_timeout(wait)? {
+                let json = request.headers().iter()
+                    .any(|h| h.field.equiv("Content-Type") && h.value.as_str().starts_with("application/json"));
+                let mut body = String::new();
+                let response = if request.method() == &tiny_http::Method::Post && !json {
+                    ApiError::UnsupportedMediaType(String::from("expected application/json")).into()
+                } else {
+                    match request.as_reader().read_to_string(&mut body) {
+                        Ok(_) => self.handle(request.method().as_str(), request.url(), &body),
+                        Err(error) => ApiError::BadRequest(error.to_string()).into(),
+                    }
+                };
+                let reply = tiny_http::Response::from_string(response.body.to_string())
+                    .with_status_code(response.status)
+                    .with_header(json_header.clone());
+                // A client that went away is no reason to stop serving
+                let _ = request.respond(reply);
+            }
+            self.play_running();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(api: &mut TableApi, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let response = api.handle(method, path, body);
+        (response.status, response.body)
+    }
+
+    fn seat(api: &mut TableApi, id: usize, seat: u32) -> (u16, Value) {
+        let body = json!({
+            "player": { "id": id, "name": format!("P{}", id), "logo": "" },
+            "seat": seat,
+            "agent": { "type": "calling-station" },
+        });
+        call(api, "POST", "/tables/1/players", &body.to_string())
+    }
+
+    fn table() -> TableApi {
+        let mut api = TableApi::new(5);
+        let body = r#"{"seats":3,"default_buy_in_value":100,"buy_in_limit":1,"blind_price":5,"betting":{"type":"pot-limit"},"variant":"omaha","ante":0,"show_hud":false}"#;
+        assert_eq!(call(&mut api, "POST", "/tables", body).0, 201);
+        api
+    }
+
+    #[test]
+    fn test_create_seat_start_pause_and_fetch_hands() {
+        let mut api = table();
+        assert_eq!(seat(&mut api, 1, 1).0, 201);
+        let (status, state) = seat(&mut api, 2, 2);
+        assert_eq!(status, 201);
+        assert_eq!(state["status"], "open");
+        assert_eq!(state["betting"], json!({ "type": "pot-limit" }));
+        assert_eq!(state["variant"], "omaha");
+        assert_eq!(state["players"][1]["stack"], 100);
+
+        let (status, state) = call(&mut api, "POST", "/tables/1/start", r#"{"hands":2}"#);
+        assert_eq!((status, &state["status"]), (200, &json!("running")));
+        assert!(api.play_running());
+        assert!(api.play_running());
+        assert!(!api.play_running());
+
+        let (_, state) = call(&mut api, "GET", "/tables/1", "");
+        assert_eq!(state["status"], "paused");
+        assert_eq!(state["hand_number"], 2);
+        let stacks: u64 = state["players"].as_array().unwrap().iter().map(|p| p["stack"].as_u64().unwrap()).sum();
+        assert_eq!(stacks, 200);
+
+        let (_, hands) = call(&mut api, "GET", "/tables/1/hands", "");
+        assert_eq!(hands.as_array().unwrap().len(), 2);
+        let (status, events) = call(&mut api, "GET", "/tables/1/hands/2", "");
+        assert_eq!(status, 200);
+        assert_eq!(events[0]["event"], "HandStarted");
+        assert_eq!(call(&mut api, "GET", "/tables/1/hands/3", "").0, 404);
+
+        assert_eq!(call(&mut api, "POST", "/tables/1/stop", "").1["status"], "stopped");
+        assert_eq!(call(&mut api, "POST", "/tables/1/start", "").0, 409);
+    }
+
+    #[test]
+    fn test_game_errors_map_to_statuses_with_structured_bodies() {
+        let mut api = table();
+        seat(&mut api, 1, 1);
+
+        let (status, body) = seat(&mut api, 2, 9);
+        assert_eq!(status, 422);
+        assert_eq!(body, json!({ "error": "SeatGreaterThanAllowed", "detail": { "SeatGreaterThanAllowed": [9, 3] }, "message": "seat 9 does not exist, the table has 3 seats" }));
+
+        let (status, body) = seat(&mut api, 2, 1);
+        assert_eq!(status, 409);
+        assert_eq!(body["error"], "SeatAlreadyInUse");
+
+        let (status, body) = call(&mut api, "POST", "/tables/1/start", "");
+        assert_eq!(status, 409);
+        assert_eq!(body["detail"], json!({ "NotEnoughPlayers": 1 }));
+    }
+
+    #[test]
+    fn test_bad_requests() {
+        let mut api = table();
+
+        assert_eq!(call(&mut api, "GET", "/tables/7", "").0, 404);
+        assert_eq!(call(&mut api, "GET", "/tables/x/hands", "").0, 404);
+        assert_eq!(call(&mut api, "GET", "/nowhere", "").0, 404);
+        let (status, body) = call(&mut api, "POST", "/tables", "{\"seats\":");
+        assert_eq!(status, 400);
+        assert_eq!(body["error"], "BadRequest");
+        assert_eq!(call(&mut api, "POST", "/tables/1/pause", "").0, 409);
+        let command = r#"{"player":{"id":1,"name":"P1","logo":""},"seat":1,"agent":{"type":"command","command":"true"}}"#;
+        assert_eq!(call(&mut api, "POST", "/tables/1/players", command).0, 400);
+    }
+
+    #[test]
+    fn test_serves_over_http() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || TableApi::new(1).serve("127.0.0.1:0", |address| sender.send(address).unwrap()));
+        let address = receiver.recv().unwrap();
+
+        use std::io::{Read, Write};
+        let post = |content_type: &str| {
+            let mut stream = std::net::TcpStream::connect(address).unwrap();
+            let body = r#"{"seats":2,"default_buy_in_value":50,"buy_in_limit":1,"blind_price":1,"betting":{"type":"no-limit"},"variant":"holdem","ante":0,"show_hud":false}"#;
+            write!(stream, "POST /tables HTTP/1.1\r\nHost: localhost\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", content_type, body.len(), body).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        // What a cross-site form could send
+        assert!(post("text/plain").starts_with("HTTP/1.1 415"));
+        let response = post("application/json");
+        assert!(response.starts_with("HTTP/1.1 201"));
+        assert!(response.contains("application/json"));
+        assert!(response.contains("\"status\":\"open\""));
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::game::poker::hand::HandStage;
+
+/// Bets and raises a fixed-limit street allows by default: a bet, a raise, a re-raise and a cap
+pub const DEFAULT_RAISE_CAP: u32 = 4;
+
+/// How much a player may bet or raise, chosen per table.
+///
+/// With the `serde` feature a structure is `{"type":"pot-limit"}`, or
+/// `{"type":"fixed-limit","raise_cap":4}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "kebab-case"))]
+pub enum BettingStructure {
+    /// Any amount from the minimum bet or raise up to the whole stack
+    NoLimit,
+    /// Raises up to the size of the pot after calling
+    PotLimit,
+    /// Bets and raises of exactly one small bet (the big blind) pre-flop and on the flop, and
+    /// one big bet (two big blinds) on the turn and river; in stud games small bets on third
+    /// and fourth street, big bets from fifth street on. `raise_cap` counts every bet and
+    /// raise of a street, the big blind being the first one pre-flop.
+    FixedLimit { raise_cap: u32 },
+}
+
+impl BettingStructure {
+    /// The size of every bet and raise on `stage` of a fixed-limit hand
+    pub fn fixed_bet(stage: HandStage, big_blind: u32) -> u32 {
+        match stage {
+            HandStage::INIT | HandStage::FLOP => big_blind,
+            HandStage::TURN | HandStage::SIXTH | HandStage::RIVER => 2 * big_blind,
+        }
+    }
+
+    /// `no-limit`, `pot-limit` or `fixed-limit`, as written in table configs and snapshots
+    pub fn code(&self) -> &'static str {
+        match self {
+            BettingStructure::NoLimit => "no-limit",
+            BettingStructure::PotLimit => "pot-limit",
+            BettingStructure::FixedLimit { .. } => "fixed-limit",
+        }
+    }
+
+    /// Reads a `code`, fixed limit taking `raise_cap`
+    pub fn from_code(code: &str, raise_cap: u32) -> Option<BettingStructure> {
+        match code {
+            "no-limit" => Some(BettingStructure::NoLimit),
+            "pot-limit" => Some(BettingStructure::PotLimit),
+            "fixed-limit" => Some(BettingStructure::FixedLimit { raise_cap }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BettingStructure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            BettingStructure::NoLimit => String::from("No-Limit"),
+            BettingStructure::PotLimit => String::from("Pot-Limit"),
+            BettingStructure::FixedLimit { raise_cap } => format!("Fixed-Limit, {} bets per street", raise_cap),
+        };
+        write!(f, "{}", res)
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt;
 use std::fmt::Formatter;
-use crate::deck::{Card, Suit};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Blind {
     NONE = 0,
     SMALL = 1,
@@ -16,7 +17,6 @@ impl fmt::Display for Blind {
             Blind::NONE => "",
             Blind::SMALL => "SMALL BLIND",
             Blind::BIG => "BIG BLIND",
-            _ => "unknown blind"
         };
 
         write!(f, "{}", res)
@@ -0,0 +1,226 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::deck::Card;
+use crate::game::poker::action::{parse_discards, PokerAction};
+use crate::game::poker::agent::Agent;
+use crate::game::poker::evaluator::{best_hand, equity, evaluate_deuce_to_seven, HandCategory};
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::view::PlayerView;
+
+/// Never folds, never raises and stands pat at every draw
+pub struct CallingStation {
+    pub name: String,
+}
+
+impl Agent for CallingStation {
+    fn name(&self) -> &str { &self.name }
+
+    fn decide(&mut self, view: &PlayerView) -> PokerAction {
+        match view.legal_actions.call {
+            Some(amount) => PokerAction::Call(amount),
+            None => PokerAction::Check,
+        }
+    }
+}
+
+/// Picks uniformly among folding (only when facing a bet), checking or calling, and a bet or
+/// raise of random size. Its choices depend on `seed` and, once dealt in by the dealer, on
+/// the hand's seed.
+pub struct RandomAgent {
+    pub name: String,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn new(name: &str, seed: u64) -> Self {
+        RandomAgent { name: name.to_string(), seed, rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str { &self.name }
+
+    fn new_hand(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed ^ self.seed);
+    }
+
+    fn decide(&mut self, view: &PlayerView) -> PokerAction {
+        let legal = &view.legal_actions;
+        let passive = match legal.call {
+            Some(amount) => PokerAction::Call(amount),
+            None => PokerAction::Check,
+        };
+
+        match self.rng.random_range(0..3) {
+            0 if !legal.can_check => PokerAction::Fold,
+            2 => match (legal.bet, legal.raise) {
+                (Some((min, max)), _) => PokerAction::Bet(self.rng.random_range(min..=max)),
+                (None, Some((min, max))) => PokerAction::Raise(self.rng.random_range(min..=max)),
+                (None, None) => passive,
+            },
+            _ => passive,
+        }
+    }
+
+    /// Throws away every card with even odds
+    fn discard(&mut self, view: &PlayerView) -> Vec<Card> {
+        view.hole_cards.iter().copied().filter(|_| self.rng.random_bool(0.5)).collect()
+    }
+}
+
+/// Draws to the hand at face value: a straight or better stands pat and otherwise the paired
+/// ranks are kept, or the highest card without a pair. In deuce-to-seven lowball a ten-low
+/// or better stands pat and otherwise one card of each rank up to an eight is kept.
+fn standard_discards(variant: GameVariant, hand: &[Card]) -> Vec<Card> {
+    if variant == GameVariant::TripleDraw {
+        let value = evaluate_deuce_to_seven(hand);
+        if value.category == HandCategory::HighCard && value.ranks[0] <= 10 {
+            return vec![];
+        }
+        return hand.iter().enumerate()
+            .filter(|&(index, card)| card.rank as u8 > 8 || hand[..index].iter().any(|c| c.rank == card.rank))
+            .map(|(_, card)| *card)
+            .collect();
+    }
+
+    if best_hand(hand).category >= HandCategory::Straight {
+        return vec![];
+    }
+    let paired = |card: &Card| hand.iter().filter(|c| c.rank == card.rank).count() > 1;
+    let highest = hand.iter().map(|c| c.rank).max();
+    let keep_highest = !hand.iter().any(paired);
+    hand.iter()
+        .filter(|card| !paired(card) && (!keep_highest || Some(card.rank) != highest))
+        .copied()
+        .collect()
+}
+
+/// Plays its Monte Carlo equity against random hands: bets or raises the pot when well ahead,
+/// calls when the price is right and otherwise checks or folds
+pub struct EquityAgent {
+    pub name: String,
+    pub iterations: u32,
+    pub seed: u64,
+}
+
+impl Agent for EquityAgent {
+    fn name(&self) -> &str { &self.name }
+
+    fn new_hand(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn decide(&mut self, view: &PlayerView) -> PokerAction {
+        let opponents = view.players.len().saturating_sub(1);
+        self.seed = self.seed.wrapping_add(1);
+        let equity = equity(view.variant, &view.hole_cards, &view.board, opponents, self.iterations, self.seed);
+        let pot_odds = view.to_call as f64 / (view.pot + view.to_call) as f64;
+        let legal = &view.legal_actions;
+
+        if equity > 0.5 + 0.5 / (opponents as f64 + 1.0) {
+            if let Some((min, max)) = legal.bet {
+                return PokerAction::Bet(view.pot.clamp(min, max));
+            }
+            if let Some((min, max)) = legal.raise {
+                return PokerAction::Raise((view.to_call * 2 + view.pot).clamp(min, max));
+            }
+        }
+
+        match legal.call {
+            Some(amount) if equity >= pot_odds => PokerAction::Call(amount),
+            Some(_) => PokerAction::Fold,
+            None => PokerAction::Check,
+        }
+    }
+
+    fn discard(&mut self, view: &PlayerView) -> Vec<Card> {
+        standard_discards(view.variant, &view.hole_cards)
+    }
+}
+
+/// Runs a shell command for every decision, writing the prompt to its standard input and
+/// reading the action (`fold`, `check`, `call`, `bet 40`, `raise 120`), or at a draw the
+/// cards to throw away (`discard 7s Th`, `stand pat`), from the last line of its output. This is how LLM clients and other external programs take a seat. An illegal
+/// answer runs the command again, the prompt ending with why the answer was rejected.
+pub struct CommandAgent {
+    pub name: String,
+    pub command: String,
+}
+
+impl CommandAgent {
+    fn ask(&self, prompt: &str) -> Option<String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+        child.stdin.take()?.write_all(prompt.as_bytes()).ok()?;
+        let output = child.wait_with_output().ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl Agent for CommandAgent {
+    fn name(&self) -> &str { &self.name }
+
+    /// Output that cannot be read as an action checks, or folds when facing a bet
+    fn decide(&mut self, view: &PlayerView) -> PokerAction {
+        self.ask(&view.prompt())
+            .and_then(|output| output.lines().rev().find(|l| !l.trim().is_empty()).and_then(PokerAction::parse))
+            .unwrap_or(PokerAction::Check)
+    }
+
+    /// Output that cannot be read as a discard stands pat
+    fn discard(&mut self, view: &PlayerView) -> Vec<Card> {
+        self.ask(&view.prompt())
+            .and_then(|output| output.lines().rev().find(|l| !l.trim().is_empty()).and_then(parse_discards))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{HandStage, PokerHand, StartHandParams};
+
+    fn view() -> PlayerView {
+        let players = funded_players(&[(1, "P1"), (2, "P2")], 500);
+        let hand = PokerHand::start(StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 2, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap();
+        PlayerView::create(&hand, 1, HandStage::INIT, None).unwrap()
+    }
+
+    #[test]
+    fn test_bots_answer_legally() {
+        let view = view();
+        let mut bots: Vec<Box<dyn Agent>> = vec![
+            Box::new(CallingStation { name: String::from("Station") }),
+            Box::new(RandomAgent::new("Random", 5)),
+            Box::new(EquityAgent { name: String::from("Equity"), iterations: 100, seed: 1 }),
+        ];
+
+        for bot in bots.iter_mut() {
+            for _ in 0..20 {
+                let action = bot.decide(&view);
+                assert!(view.legal_actions.contains(&action), "{} chose {:?}", bot.name(), action);
+            }
+        }
+        assert_eq!(bots[0].decide(&view), PokerAction::Call(5));
+    }
+
+    #[test]
+    fn test_command_agent_reads_last_line() {
+        let mut agent = CommandAgent { name: String::from("Script"), command: String::from("cat > /dev/null; echo thinking; echo 'raise 40'") };
+        assert_eq!(agent.decide(&view()), PokerAction::Raise(40));
+
+        let mut confused = CommandAgent { name: String::from("Confused"), command: String::from("echo 'I am not sure'") };
+        assert_eq!(confused.decide(&view()), PokerAction::Check);
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::mem::discriminant;
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::agent::Agent;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::evaluator::equity;
+use crate::game::poker::event::HandEvent;
+use crate::game::poker::replay::HandReplay;
+use crate::game::poker::view::PlayerView;
+
+/// A logged decision: exactly what the player saw and what they did
+#[derive(Debug, Clone)]
+pub struct DecisionPoint {
+    pub view: PlayerView,
+    pub actual: PokerAction,
+}
+
+impl DecisionPoint {
+    /// Every decision of a recorded hand, in the order they were taken
+    pub fn from_events(events: &[HandEvent]) -> Result<Vec<DecisionPoint>, PokerGameError> {
+        let mut replay = HandReplay::from_events(events)?;
+        let mut points = vec![];
+
+        while let Some(decision) = replay.decision() {
+            let actual = decision.action;
+            points.push(DecisionPoint { view: replay.view()?, actual });
+            replay.step_forward();
+        }
+
+        Ok(points)
+    }
+}
+
+pub struct CounterfactualParams {
+    /// Monte Carlo boards dealt per decision when estimating equity
+    pub iterations: u32,
+    pub seed: u64,
+}
+
+/// Rough chip EV of `action` given the player's showdown equity: folding is worth nothing,
+/// and chips put in are assumed to be matched by one opponent with no further betting.
+pub fn estimate_ev(action: &PokerAction, pot: u32, equity: f64) -> f64 {
+    let pot = pot as f64;
+    match action {
+        PokerAction::Fold => 0.0,
+        PokerAction::Check => equity * pot,
+        PokerAction::Call(amount) => equity * (pot + *amount as f64) - *amount as f64,
+        PokerAction::Bet(amount) | PokerAction::Raise(amount) => equity * (pot + 2.0 * *amount as f64) - *amount as f64,
+    }
+}
+
+/// Two actions agree when they are of the same kind, whatever the amount
+fn same_kind(a: &PokerAction, b: &PokerAction) -> bool {
+    discriminant(a) == discriminant(b)
+}
+
+/// Answers of every agent to a corpus of decisions, next to the recorded actions.
+pub struct CounterfactualReport {
+    pub agents: Vec<String>,
+    pub points: Vec<DecisionPoint>,
+    /// `answers[i][d]` is what agent `i` chose at decision `d`
+    pub answers: Vec<Vec<PokerAction>>,
+    /// Estimated showdown equity at each decision
+    pub equities: Vec<f64>,
+}
+
+impl CounterfactualReport {
+    /// Asks each agent for its action at every decision point
+    pub fn run(points: Vec<DecisionPoint>, agents: &mut [Box<dyn Agent>], params: &CounterfactualParams) -> CounterfactualReport {
+        let names = agents.iter().map(|agent| agent.name().to_string()).collect();
+        let answers = agents.iter_mut()
+            .map(|agent| points.iter().map(|point| agent.decide(&point.view)).collect())
+            .collect();
+        let equities = points.iter().enumerate()
+            .map(|(index, point)| {
+                let opponents = point.view.players.len().saturating_sub(1);
+                equity(point.view.variant, &point.view.hole_cards, &point.view.board, opponents, params.iterations, params.seed ^ index as u64)
+            })
+            .collect();
+
+        CounterfactualReport { agents: names, points, answers, equities }
+    }
+
+    /// Labels of the agreement matrix rows and columns: the agents, then the recorded action
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = self.agents.clone();
+        labels.push("actual".to_string());
+        labels
+    }
+
+    fn choices(&self, index: usize) -> Vec<PokerAction> {
+        match self.answers.get(index) {
+            Some(answers) => answers.clone(),
+            None => self.points.iter().map(|point| point.actual).collect(),
+        }
+    }
+
+    /// Share of decisions on which each pair of agents (and the recorded action, last) chose
+    /// the same kind of action
+    pub fn agreement_matrix(&self) -> Vec<Vec<f64>> {
+        let size = self.agents.len() + 1;
+        let choices: Vec<Vec<PokerAction>> = (0..size).map(|i| self.choices(i)).collect();
+
+        (0..size).map(|i| (0..size).map(|j| {
+            if self.points.is_empty() { return 0.0; }
+            let agreed = choices[i].iter().zip(&choices[j]).filter(|(a, b)| same_kind(a, b)).count();
+            agreed as f64 / self.points.len() as f64
+        }).collect()).collect()
+    }
+
+    /// Mean estimated chip EV of each agent's choices minus that of the recorded actions
+    pub fn ev_differences(&self) -> Vec<f64> {
+        self.answers.iter().map(|answers| {
+            if self.points.is_empty() { return 0.0; }
+            let total: f64 = self.points.iter().zip(answers).zip(&self.equities)
+                .map(|((point, answer), &equity)| {
+                    estimate_ev(answer, point.view.pot, equity) - estimate_ev(&point.actual, point.view.pot, equity)
+                })
+                .sum();
+            total / self.points.len() as f64
+        }).collect()
+    }
+}
+
+impl fmt::Display for CounterfactualReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let labels = self.labels();
+        writeln!(f, "{} decisions", self.points.len())?;
+
+        write!(f, "{:<20}", "Agreement")?;
+        for label in &labels {
+            write!(f, " {:>10}", label)?;
+        }
+        writeln!(f)?;
+
+        for (label, row) in labels.iter().zip(self.agreement_matrix()) {
+            write!(f, "{:<20}", label)?;
+            for share in row {
+                write!(f, " {:>9.1}%", share * 100.0)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "{:<20} {:>10}", "Agent", "EV diff")?;
+        for (name, difference) in self.agents.iter().zip(self.ev_differences()) {
+            writeln!(f, "{:<20} {:>10.2}", name, difference)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+    struct Fixed(&'static str, PokerAction);
+
+    impl Agent for Fixed {
+        fn name(&self) -> &str { self.0 }
+        fn decide(&mut self, _view: &PlayerView) -> PokerAction { self.1 }
+    }
+
+    fn recorded_events() -> Vec<HandEvent> {
+        let players = funded_players(&[(1, "P1"), (2, "P2")], 1000);
+
+        let mut hand = PokerHand::start(StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 4, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap();
+        hand.act(1, PokerAction::Call(5)).unwrap();
+        hand.act(2, PokerAction::Check).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.act(2, PokerAction::Bet(20)).unwrap();
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.award_pot(2, hand.pot).unwrap();
+        hand.events
+    }
+
+    #[test]
+    fn test_decision_points_from_events() {
+        let points = DecisionPoint::from_events(&recorded_events()).unwrap();
+
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0].view.seat, 1);
+        assert_eq!(points[0].actual, PokerAction::Call(5));
+        assert_eq!(points[3].view.board.len(), 3);
+        assert_eq!(points[3].actual, PokerAction::Fold);
+    }
+
+    #[test]
+    fn test_agreement_matrix() {
+        let points = DecisionPoint::from_events(&recorded_events()).unwrap();
+        let mut agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(Fixed("folder", PokerAction::Fold)),
+            Box::new(Fixed("checker", PokerAction::Check)),
+        ];
+        let report = CounterfactualReport::run(points, &mut agents, &CounterfactualParams { iterations: 50, seed: 1 });
+
+        assert_eq!(report.labels(), vec!["folder", "checker", "actual"]);
+        let matrix = report.agreement_matrix();
+        assert_eq!(matrix[0][0], 1.0);
+        assert_eq!(matrix[0][1], 0.0);
+        assert_eq!(matrix[0][2], 0.25);
+        assert_eq!(matrix[1][2], 0.25);
+        assert_eq!(matrix[2][0], matrix[0][2]);
+        assert!(report.to_string().contains("checker"));
+    }
+
+    #[test]
+    fn test_ev_estimates() {
+        assert_eq!(estimate_ev(&PokerAction::Fold, 100, 0.9), 0.0);
+        assert_eq!(estimate_ev(&PokerAction::Check, 100, 0.5), 50.0);
+        assert_eq!(estimate_ev(&PokerAction::Call(50), 150, 0.25), 0.0);
+        assert_eq!(estimate_ev(&PokerAction::Bet(50), 100, 0.5), 50.0);
+
+        let points = DecisionPoint::from_events(&recorded_events()).unwrap();
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(Fixed("folder", PokerAction::Fold))];
+        let report = CounterfactualReport::run(points, &mut agents, &CounterfactualParams { iterations: 50, seed: 1 });
+
+        let expected = report.points.iter().zip(&report.equities)
+            .map(|(point, &equity)| -estimate_ev(&point.actual, point.view.pot, equity))
+            .sum::<f64>() / 4.0;
+        assert!((report.ev_differences()[0] - expected).abs() < 1e-9);
+    }
+}
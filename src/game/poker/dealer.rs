@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use crate::game::poker::agent::Agent;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::event::HandEventListener;
+use crate::game::poker::hand::{PokerHand, StartHandParams};
+use crate::game::poker::stats::StatsTracker;
+use crate::game::poker::view::PlayerView;
+
+pub struct PlayedHand {
+    pub hand: PokerHand,
+    /// Net chips won or lost per player id
+    pub winnings: BTreeMap<usize, i64>,
+}
+
+/// Plays a hand from the blinds to the pot award, asking the agent of each player id for
+/// every decision, draws included. An agent whose answer is not legal is asked once more with
+/// the reason in `PlayerView::feedback`; a second illegal answer is replaced by the closest
+/// legal action, or standing pat at a draw.
+///
+/// Before the deal each agent gets a seed of its own derived from the hand's seed and its
+/// player id, so seeded bots play a hand the same way every time it is dealt.
+pub fn play_hand(
+    params: StartHandParams,
+    agents: &mut BTreeMap<usize, Box<dyn Agent>>,
+    hud: Option<&StatsTracker>,
+    listeners: Vec<Box<dyn HandEventListener>>,
+) -> Result<PlayedHand, PokerGameError> {
+    if let Some(player) = params.players.iter().find(|p| !agents.contains_key(&p.player.id)) {
+        return Err(PokerGameError::AgentNotFound(player.player.id));
+    }
+    let starting_stacks: BTreeMap<usize, u32> = params.players.iter().map(|p| (p.player.id, p.cash_amount)).collect();
+    for player in &params.players {
+        // Rotated, so player ids and hand numbers mixed into the same seed cannot cancel out
+        let seed = params.seed ^ (player.player.id as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(32);
+        agents.get_mut(&player.player.id).expect("Expect every player to have an agent").new_hand(seed);
+    }
+
+    let mut hand = PokerHand::start(params)?;
+    for listener in listeners {
+        hand.subscribe(listener);
+    }
+
+    loop {
+        while let Some(seat) = hand.next_to_draw() {
+            let mut view = PlayerView::create(&hand, seat, hand.stage, hud)?;
+            let player_id = hand.active.iter().find(|p| p.seat == seat).expect("Expect the next to draw to be in the hand").player.id;
+            let agent = agents.get_mut(&player_id).expect("Expect every player to have an agent");
+            if let Err(error) = hand.draw(seat, &agent.discard(&view)) {
+                view.feedback = Some(error.to_string());
+                if hand.draw(seat, &agent.discard(&view)).is_err() {
+                    hand.draw(seat, &[])?;
+                }
+            }
+        }
+        while !hand.is_round_complete() {
+            let seat = hand.speaker.seat;
+            let mut view = PlayerView::create(&hand, seat, hand.stage, hud)?;
+            let agent = agents.get_mut(&hand.speaker.player.id).expect("Expect every player to have an agent");
+            let action = match view.legal_actions.check(agent.decide(&view)) {
+                Ok(action) => action,
+                Err(error) => {
+                    view.feedback = Some(error.to_string());
+                    view.legal_actions.coerce(agent.decide(&view))
+                }
+            };
+            hand.act(seat, action)?;
+        }
+
+        // All-in players still take their draws, the betting rounds being skipped
+        let all_in = hand.active.iter().filter(|p| p.cash_amount > 0).count() < 2;
+        if hand.active.len() < 2 || (all_in && !hand.variant.has_draws()) {
+            break;
+        }
+        if hand.deal_next_stage().is_err() {
+            break;
+        }
+    }
+    hand.settle()?;
+
+    let winnings = hand.active.iter()
+        .chain(hand.folds.iter().map(|fold| &fold.player))
+        .map(|p| (p.player.id, p.cash_amount as i64 - starting_stacks[&p.player.id] as i64))
+        .collect();
+
+    Ok(PlayedHand { hand, winnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::deck::Card;
+    use crate::game::poker::action::PokerAction;
+    use crate::game::poker::event::HandEventKind;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::HandStage;
+
+    /// Always tries the same action, relying on the dealer to make it legal
+    struct Stubborn(PokerAction);
+
+    impl Agent for Stubborn {
+        fn name(&self) -> &str { "Stubborn" }
+        fn decide(&mut self, _view: &PlayerView) -> PokerAction { self.0 }
+    }
+
+    fn params(stacks: &[u32]) -> StartHandParams {
+        let mut players = funded_players(&[(1, "P1"), (2, "P2"), (3, "P3")][..stacks.len()], 1);
+        for (player, &stack) in players.iter_mut().zip(stacks) {
+            player.cash_amount = stack;
+        }
+        StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 11, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }
+    }
+
+    fn agents(actions: &[PokerAction]) -> BTreeMap<usize, Box<dyn Agent>> {
+        actions.iter().enumerate().map(|(i, &action)| (i + 1, Box::new(Stubborn(action)) as Box<dyn Agent>)).collect()
+    }
+
+    #[test]
+    fn test_everyone_folds_to_the_big_blind() {
+        let played = play_hand(params(&[500, 500, 500]), &mut agents(&[PokerAction::Fold; 3]), None, vec![]).unwrap();
+
+        assert_eq!(played.hand.active.len(), 1);
+        assert_eq!(played.winnings, BTreeMap::from([(1, 0), (2, -5), (3, 5)]));
+    }
+
+    #[test]
+    fn test_checked_down_hand_reaches_showdown() {
+        let played = play_hand(params(&[500, 500]), &mut agents(&[PokerAction::Call(0), PokerAction::Call(0)]), None, vec![]).unwrap();
+
+        assert_eq!(played.hand.stage, HandStage::RIVER);
+        assert_eq!(played.hand.pot, 0);
+        assert_eq!(played.winnings.values().sum::<i64>(), 0);
+        assert_eq!(played.winnings.values().map(|w| w.abs()).max(), Some(10));
+    }
+
+    #[test]
+    fn test_all_in_players_run_out_the_board() {
+        let played = play_hand(params(&[300, 100]), &mut agents(&[PokerAction::Raise(1000), PokerAction::Call(0)]), None, vec![]).unwrap();
+
+        assert_eq!(played.hand.stage, HandStage::RIVER);
+        assert_eq!(played.winnings.values().sum::<i64>(), 0);
+        assert_eq!(played.winnings[&2].abs(), 100);
+    }
+
+    /// Checks, and calls once told it cannot check, keeping what it was told
+    struct Learner(Rc<RefCell<Vec<String>>>);
+
+    impl Agent for Learner {
+        fn name(&self) -> &str { "Learner" }
+        fn decide(&mut self, view: &PlayerView) -> PokerAction {
+            match &view.feedback {
+                Some(feedback) => {
+                    self.0.borrow_mut().push(feedback.clone());
+                    PokerAction::Call(0)
+                }
+                None => PokerAction::Check,
+            }
+        }
+    }
+
+    #[test]
+    fn test_illegal_answer_is_asked_again_with_the_reason() {
+        let told = Rc::new(RefCell::new(vec![]));
+        let mut agents = agents(&[PokerAction::Check, PokerAction::Check]);
+        agents.insert(1, Box::new(Learner(told.clone())));
+
+        let played = play_hand(params(&[500, 500]), &mut agents, None, vec![]).unwrap();
+
+        // Coerced, the small blind's check would have been a fold
+        assert_eq!(played.hand.stage, HandStage::RIVER);
+        assert_eq!(*told.borrow(), vec![String::from("you cannot check now, legal actions are: fold, call 5, raise 15-495")]);
+    }
+
+    /// Throws its first card away twice, then once when told it cannot
+    struct Redrawer(Rc<RefCell<Vec<String>>>);
+
+    impl Agent for Redrawer {
+        fn name(&self) -> &str { "Redrawer" }
+        fn decide(&mut self, _view: &PlayerView) -> PokerAction { PokerAction::Call(0) }
+        fn discard(&mut self, view: &PlayerView) -> Vec<Card> {
+            match &view.feedback {
+                Some(feedback) => {
+                    self.0.borrow_mut().push(feedback.clone());
+                    vec![view.hole_cards[0]]
+                }
+                None => vec![view.hole_cards[0]; 2],
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw_game_asks_every_player_to_draw() {
+        let told = Rc::new(RefCell::new(vec![]));
+        let mut agents = agents(&[PokerAction::Call(0), PokerAction::Call(0)]);
+        agents.insert(1, Box::new(Redrawer(told.clone())));
+        let mut params = params(&[500, 500]);
+        params.variant = GameVariant::TripleDraw;
+        params.betting = BettingStructure::FixedLimit { raise_cap: 4 };
+
+        let played = play_hand(params, &mut agents, None, vec![]).unwrap();
+
+        let draws: Vec<(u32, u32)> = played.hand.events.iter().filter_map(|e| match e.kind {
+            HandEventKind::DrawTaken { seat, count, .. } => Some((seat, count)),
+            _ => None,
+        }).collect();
+        assert_eq!(draws, vec![(2, 0), (1, 1), (2, 0), (1, 1), (2, 0), (1, 1)]);
+        assert_eq!(told.borrow().len(), 3);
+        assert!(told.borrow()[0].ends_with("is not in the hand, only cards held can be discarded"));
+        assert_eq!(played.hand.stage, HandStage::RIVER);
+        assert_eq!(played.winnings.values().sum::<i64>(), 0);
+    }
+
+    #[test]
+    fn test_missing_agent() {
+        let result = play_hand(params(&[500, 500]), &mut agents(&[PokerAction::Check]), None, vec![]);
+        assert!(matches!(result, Err(PokerGameError::AgentNotFound(2))));
+    }
+}
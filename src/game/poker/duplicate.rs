@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::game_player::PokerGamePlayer;
+use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+/// A single deal of a duplicate match: one seeded deck with the lineup seated in one rotation.
+///
+/// The dealer button never moves between rotations, so the same seat always receives the same
+/// cards for a given seed; only the players sitting in those seats change.
+#[derive(Debug, Clone)]
+pub struct DuplicateDeal {
+    pub seed: u64,
+    pub rotation: usize,
+    pub dealer_seat: u32,
+    pub players: Vec<PokerGamePlayer>,
+}
+
+impl DuplicateDeal {
+    pub fn start_hand(&self, blind_price: u32, betting: BettingStructure, variant: GameVariant, ante: u32) -> Result<PokerHand, PokerGameError> {
+        PokerHand::start(StartHandParams {
+            blind_price,
+            players: self.players.clone(),
+            dealer_seat: self.dealer_seat,
+            seed: self.seed,
+            betting,
+            variant,
+            ante,
+        })
+    }
+}
+
+/// Net winnings per player id for one deck, summed over every rotation it was played in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckResult {
+    pub seed: u64,
+    pub winnings: BTreeMap<usize, i64>,
+}
+
+/// Plays every seeded deck once per seat rotation so the card distribution cancels out.
+pub struct DuplicateMatch {
+    pub lineup: Vec<PokerGamePlayer>,
+    pub seeds: Vec<u64>,
+}
+
+impl DuplicateMatch {
+    pub fn new(lineup: Vec<PokerGamePlayer>, seeds: Vec<u64>) -> Self {
+        DuplicateMatch { lineup, seeds }
+    }
+
+    /// Every rotation of the lineup for `seed`. Heads-up this is the original seating and the swap.
+    pub fn deals(&self, seed: u64) -> Vec<DuplicateDeal> {
+        let mut lineup = self.lineup.clone();
+        lineup.sort_by_key(|p| p.seat);
+
+        let seats: Vec<u32> = lineup.iter().map(|p| p.seat).collect();
+        let dealer_seat = seats.first().copied().unwrap_or_default();
+
+        (0..lineup.len()).map(|rotation| {
+            let players = lineup.iter().enumerate().map(|(index, player)| {
+                let mut seated = player.clone();
+                seated.seat = seats[(index + rotation) % seats.len()];
+                seated
+            }).collect();
+
+            DuplicateDeal { seed, rotation, dealer_seat, players }
+        }).collect()
+    }
+
+    /// Plays every deal through `play_hand`, which returns the net chips won per player id,
+    /// and aggregates the results per deck.
+    pub fn run<F>(&self, mut play_hand: F) -> Result<Vec<DeckResult>, PokerGameError>
+    where
+        F: FnMut(&DuplicateDeal) -> Result<BTreeMap<usize, i64>, PokerGameError>,
+    {
+        if self.lineup.len() < 2 {
+            return Err(PokerGameError::NotEnoughPlayers(self.lineup.len() as u32));
+        }
+
+        let mut results = Vec::with_capacity(self.seeds.len());
+
+        for &seed in &self.seeds {
+            let mut winnings: BTreeMap<usize, i64> =
+                self.lineup.iter().map(|p| (p.player.id, 0)).collect();
+
+            for deal in self.deals(seed) {
+                for (player_id, amount) in play_hand(&deal)? {
+                    *winnings.entry(player_id).or_insert(0) += amount;
+                }
+            }
+
+            results.push(DeckResult { seed, winnings });
+        }
+
+        Ok(results)
+    }
+
+    pub fn totals(results: &[DeckResult]) -> BTreeMap<usize, i64> {
+        let mut totals = BTreeMap::new();
+        for result in results {
+            for (&player_id, &amount) in &result.winnings {
+                *totals.entry(player_id).or_insert(0) += amount;
+            }
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::game_player::funded_players;
+
+    fn lineup(size: usize) -> Vec<PokerGamePlayer> {
+        funded_players(&[(1, "P1"), (2, "P2"), (3, "P3")][..size], 1000)
+    }
+
+    #[test]
+    fn test_heads_up_deals_swap_seats() {
+        let duplicate = DuplicateMatch::new(lineup(2), vec![3]);
+        let deals = duplicate.deals(3);
+
+        assert_eq!(deals.len(), 2);
+        assert_eq!(deals[0].players[0].seat, 1);
+        assert_eq!(deals[0].players[1].seat, 2);
+        assert_eq!(deals[1].players[0].seat, 2);
+        assert_eq!(deals[1].players[1].seat, 1);
+        assert!(deals.iter().all(|d| d.dealer_seat == 1 && d.seed == 3));
+    }
+
+    #[test]
+    fn test_multiway_deals_cover_every_rotation() {
+        let duplicate = DuplicateMatch::new(lineup(3), vec![3]);
+        let deals = duplicate.deals(3);
+
+        assert_eq!(deals.len(), 3);
+        for player_index in 0..3 {
+            let mut seats: Vec<u32> = deals.iter().map(|d| d.players[player_index].seat).collect();
+            seats.sort();
+            assert_eq!(seats, vec![1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn test_same_seat_gets_same_cards_across_rotations() {
+        let duplicate = DuplicateMatch::new(lineup(2), vec![11]);
+        let deals = duplicate.deals(11);
+
+        let first = deals[0].start_hand(10, BettingStructure::NoLimit, GameVariant::Holdem, 0).unwrap();
+        let second = deals[1].start_hand(10, BettingStructure::NoLimit, GameVariant::Holdem, 0).unwrap();
+
+        for seat in [1, 2] {
+            let a = first.active.iter().find(|p| p.seat == seat).unwrap();
+            let b = second.active.iter().find(|p| p.seat == seat).unwrap();
+            assert_eq!(a.hand, b.hand);
+            assert_ne!(a.player.id, b.player.id);
+        }
+        assert_eq!(first.deck, second.deck);
+    }
+
+    #[test]
+    fn test_run_aggregates_per_deck() {
+        let duplicate = DuplicateMatch::new(lineup(2), vec![1, 2]);
+
+        // Whoever sits in seat 1 wins 100 chips, so the luck of the seat cancels out
+        let results = duplicate.run(|deal| {
+            Ok(deal.players.iter()
+                .map(|p| (p.player.id, if p.seat == 1 { 100 } else { -100 }))
+                .collect())
+        }).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.winnings.values().all(|&w| w == 0)));
+        assert_eq!(DuplicateMatch::totals(&results), BTreeMap::from([(1, 0), (2, 0)]));
+    }
+
+    #[test]
+    fn test_run_requires_two_players() {
+        let duplicate = DuplicateMatch::new(lineup(1), vec![1]);
+        let result = duplicate.run(|_| Ok(BTreeMap::new()));
+        assert!(matches!(result, Err(PokerGameError::NotEnoughPlayers(1))));
+    }
+}
@@ -1,6 +1,12 @@
+use std::fmt;
+use std::fmt::Formatter;
 use crate::deck::Card;
+use crate::game::poker::action::{LegalActions, PokerAction};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// With the `serde` feature, unit variants are plain strings (`"HandNotStarted"`) and the others
+/// objects keyed by the variant name (`{"NotEnoughChips":[2,500]}`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PokerGameError {
     SeatAlreadyInUse,
     SeatMustBeGreaterThanZero,
@@ -8,6 +14,85 @@ pub enum PokerGameError {
     YouAreAlreadyAtTheGame(),
     NameAlreadyUsed(String),
     CardAlreadyInHand(Card),
+    CardNotInHand(Card),
     CannotFundNonGreaterThanZeroValues(u32),
-    PlayerSeatNotFound(u32)
-}
\ No newline at end of file
+    PlayerSeatNotFound(u32),
+    NotEnoughPlayers(u32),
+    /// Players at the table and the most the game can deal a whole hand to
+    TooManyPlayers(u32, u32),
+    NotEnoughChips(u32, u32),
+    NoMoreStages,
+    AwardExceedsPot(u32, u32),
+    HandNotStarted,
+    NoPendingDecision,
+    /// A seat tried to draw out of turn, or when no draw is due
+    NotDrawing(u32),
+    /// A seat tried to bet before every player drew
+    DrawPending(u32),
+    AgentNotFound(usize),
+    /// The action an agent answered and what it could have done instead
+    IllegalAction(PokerAction, LegalActions),
+}
+
+/// Messages are written to be read by the player at fault, an LLM included
+impl fmt::Display for PokerGameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            PokerGameError::SeatAlreadyInUse => String::from("the seat is already taken"),
+            PokerGameError::SeatMustBeGreaterThanZero => String::from("seats are numbered from 1"),
+            PokerGameError::SeatGreaterThanAllowed(seat, seats) => format!("seat {} does not exist, the table has {} seats", seat, seats),
+            PokerGameError::YouAreAlreadyAtTheGame() => String::from("the player is already at the table"),
+            PokerGameError::NameAlreadyUsed(name) => format!("the name {} is already used at the table", name),
+            PokerGameError::CardAlreadyInHand(card) => format!("{} is already in the hand", card),
+            PokerGameError::CardNotInHand(card) => format!("{} is not in the hand, only cards held can be discarded", card),
+            PokerGameError::CannotFundNonGreaterThanZeroValues(amount) => format!("cannot add {} chips, the amount must be greater than zero", amount),
+            PokerGameError::PlayerSeatNotFound(seat) => format!("no player in the hand at seat {}", seat),
+            PokerGameError::NotEnoughPlayers(count) => format!("a hand needs at least 2 players, {} available", count),
+            PokerGameError::TooManyPlayers(count, max) => format!("the game deals at most {} players, {} are seated", max, count),
+            PokerGameError::NotEnoughChips(seat, amount) => format!("seat {} does not have {} chips", seat, amount),
+            PokerGameError::NoMoreStages => String::from("the river has already been dealt"),
+            PokerGameError::AwardExceedsPot(amount, pot) => format!("cannot award {} from a pot of {}", amount, pot),
+            PokerGameError::HandNotStarted => String::from("the hand has not started"),
+            PokerGameError::NoPendingDecision => String::from("no player is waiting to act"),
+            PokerGameError::NotDrawing(seat) => format!("seat {} is not the next to draw", seat),
+            PokerGameError::DrawPending(seat) => format!("seat {} cannot bet before every player drew", seat),
+            PokerGameError::AgentNotFound(player_id) => format!("no agent plays for player {}", player_id),
+            PokerGameError::IllegalAction(action, legal) => {
+                let action = match action {
+                    PokerAction::Fold => String::from("fold"),
+                    PokerAction::Check => String::from("check"),
+                    PokerAction::Call(amount) => format!("call {}", amount),
+                    PokerAction::Bet(amount) => format!("bet {}", amount),
+                    PokerAction::Raise(amount) => format!("raise {}", amount),
+                };
+                format!("you cannot {} now, legal actions are: {}", action, legal)
+            }
+        };
+        write!(f, "{}", res)
+    }
+}
+
+impl std::error::Error for PokerGameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{Rank, Suit};
+
+    #[test]
+    fn test_messages_name_the_values_involved() {
+        let card = Card { rank: Rank::Ace, suit: Suit::Spades };
+
+        assert_eq!(PokerGameError::SeatGreaterThanAllowed(9, 6).to_string(), "seat 9 does not exist, the table has 6 seats");
+        assert_eq!(PokerGameError::NotEnoughChips(2, 500).to_string(), "seat 2 does not have 500 chips");
+        assert_eq!(PokerGameError::CardAlreadyInHand(card).to_string(), format!("{} is already in the hand", card));
+    }
+
+    #[test]
+    fn test_illegal_action_lists_the_legal_ones() {
+        let legal = LegalActions { can_check: false, call: Some(20), bet: None, raise: Some((40, 990)) };
+        let error = PokerGameError::IllegalAction(PokerAction::Check, legal);
+
+        assert_eq!(error.to_string(), "you cannot check now, legal actions are: fold, call 20, raise 40-990");
+    }
+}
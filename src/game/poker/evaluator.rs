@@ -0,0 +1,453 @@
+use std::cmp::Ordering;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::deck::{Card, Deck, Rank};
+use crate::game::poker::variant::GameVariant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// Strength of a five-card poker hand. Values compare by category, then by `ranks`, which
+/// lists the ranks that break ties in order of importance.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandValue {
+    pub category: HandCategory,
+    pub ranks: Vec<Rank>,
+}
+
+impl HandCategory {
+    /// Position of the category in short-deck rankings, where a flush beats a full house
+    fn short_deck_order(&self) -> u8 {
+        match self {
+            HandCategory::Flush => HandCategory::FullHouse as u8,
+            HandCategory::FullHouse => HandCategory::Flush as u8,
+            other => *other as u8,
+        }
+    }
+}
+
+/// Highest rank of the straight made by `ranks` (sorted descending, distinct), the ace playing
+/// low below `lowest` as well: the wheel A-2-3-4-5 counts as five high
+fn straight_high(ranks: &[Rank], lowest: Rank) -> Option<Rank> {
+    if ranks.len() != 5 {
+        return None;
+    }
+    if ranks.windows(2).all(|w| w[0] as u8 == w[1] as u8 + 1) {
+        return Some(ranks[0]);
+    }
+    if ranks[0] == Rank::Ace && ranks[4] == lowest && ranks[1..].windows(2).all(|w| w[0] as u8 == w[1] as u8 + 1) {
+        return Some(ranks[1]);
+    }
+    None
+}
+
+pub fn evaluate_five(cards: &[Card]) -> HandValue {
+    evaluate(cards, Rank::Two)
+}
+
+/// Five cards of a 36-card short deck, where A-6-7-8-9 is the lowest straight. Compare
+/// short-deck values with [`compare_short_deck`].
+pub fn evaluate_short_deck(cards: &[Card]) -> HandValue {
+    evaluate(cards, Rank::Six)
+}
+
+/// Orders short-deck values, a flush beating a full house
+pub fn compare_short_deck(a: &HandValue, b: &HandValue) -> Ordering {
+    a.category.short_deck_order().cmp(&b.category.short_deck_order()).then_with(|| a.ranks.cmp(&b.ranks))
+}
+
+/// Best short-deck hand out of five to seven cards
+pub fn best_short_deck_hand(cards: &[Card]) -> HandValue {
+    combinations(cards, 5).iter()
+        .map(|five| evaluate_short_deck(five))
+        .max_by(compare_short_deck)
+        .expect("Expect at least five cards")
+}
+
+/// Ranks grouped by how many times they appear, biggest groups then highest ranks first
+fn rank_groups<T: Copy + Ord>(ranks: impl Iterator<Item = T>) -> Vec<(usize, T)> {
+    let mut groups: Vec<(usize, T)> = vec![];
+    for rank in ranks {
+        match groups.iter_mut().find(|(_, r)| *r == rank) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, rank)),
+        }
+    }
+    groups.sort_by(|a, b| b.cmp(a));
+    groups
+}
+
+/// Category made by pairing alone, straights and flushes aside
+fn paired_category<T>(groups: &[(usize, T)]) -> HandCategory {
+    match (groups[0].0, groups.get(1).map(|g| g.0)) {
+        (4, _) => HandCategory::FourOfAKind,
+        (3, Some(2)) => HandCategory::FullHouse,
+        (3, _) => HandCategory::ThreeOfAKind,
+        (2, Some(2)) => HandCategory::TwoPair,
+        (2, _) => HandCategory::OnePair,
+        _ => HandCategory::HighCard,
+    }
+}
+
+fn evaluate(cards: &[Card], lowest: Rank) -> HandValue {
+    assert_eq!(cards.len(), 5, "A poker hand has five cards");
+
+    let groups = rank_groups(cards.iter().map(|c| c.rank));
+    let ranks: Vec<Rank> = groups.iter().map(|&(_, rank)| rank).collect();
+    let flush = cards.iter().all(|c| c.suit == cards[0].suit);
+    let straight = straight_high(&ranks, lowest);
+
+    let category = match (straight, flush, paired_category(&groups)) {
+        (Some(_), true, _) => HandCategory::StraightFlush,
+        (_, _, paired @ (HandCategory::FourOfAKind | HandCategory::FullHouse)) => paired,
+        (_, true, _) => HandCategory::Flush,
+        (Some(_), _, _) => HandCategory::Straight,
+        (_, _, paired) => paired,
+    };
+
+    match straight {
+        Some(high) => HandValue { category, ranks: vec![high] },
+        None => HandValue { category, ranks },
+    }
+}
+
+/// Strength of the up-cards of a stud hand, one to four of them, where only pairing counts
+pub fn evaluate_showing(cards: &[Card]) -> HandValue {
+    let groups = rank_groups(cards.iter().map(|c| c.rank));
+    HandValue { category: paired_category(&groups), ranks: groups.iter().map(|&(_, rank)| rank).collect() }
+}
+
+/// Every `k`-card combination of `cards`
+pub fn combinations(cards: &[Card], k: usize) -> Vec<Vec<Card>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    if cards.len() < k {
+        return vec![];
+    }
+
+    let mut with_first: Vec<Vec<Card>> = combinations(&cards[1..], k - 1).into_iter()
+        .map(|mut rest| { rest.insert(0, cards[0]); rest })
+        .collect();
+    with_first.extend(combinations(&cards[1..], k));
+    with_first
+}
+
+/// Best five-card hand out of five to seven cards
+///
+/// ```
+/// use llm_holdem::deck::Card;
+/// use llm_holdem::game::poker::evaluator::{best_hand, HandCategory};
+///
+/// let cards: Vec<Card> = ["Ah", "Kh", "Qh", "Jh", "Th", "2c", "2d"]
+///     .iter()
+///     .map(|code| Card::from_code(code).unwrap())
+///     .collect();
+/// assert_eq!(best_hand(&cards).category, HandCategory::StraightFlush);
+/// ```
+pub fn best_hand(cards: &[Card]) -> HandValue {
+    combinations(cards, 5).iter()
+        .map(|five| evaluate_five(five))
+        .max()
+        .expect("Expect at least five cards")
+}
+
+/// Best Omaha hand: exactly two of the `hole` cards with exactly three of the `board`
+pub fn best_omaha_hand(hole: &[Card], board: &[Card]) -> HandValue {
+    let boards = combinations(board, 3);
+    combinations(hole, 2).iter()
+        .flat_map(|two| boards.iter().map(move |three| evaluate_five(&[&two[..], &three[..]].concat())))
+        .max()
+        .expect("Expect two hole cards and three board cards")
+}
+
+/// Strength of a five-card low hand, the smaller value being the better low. Ace-to-five lows
+/// play aces low and ignore straights and flushes: values compare by pairing, then by `ranks`
+/// in order of importance, aces counting as 1. Deuce-to-seven lows rank hands as high hands
+/// do, aces always high, so the best is 7-5-4-3-2 of mixed suits.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LowValue {
+    pub category: HandCategory,
+    pub ranks: Vec<u8>,
+}
+
+impl LowValue {
+    /// Five different ranks, the highest an eight at most
+    pub fn is_eight_or_better(&self) -> bool {
+        self.category == HandCategory::HighCard && self.ranks[0] <= 8
+    }
+}
+
+pub(crate) fn low_rank(rank: Rank) -> u8 {
+    match rank {
+        Rank::Ace => 1,
+        rank => rank as u8,
+    }
+}
+
+fn low_value(cards: &[Card]) -> LowValue {
+    let groups = rank_groups(cards.iter().map(|c| low_rank(c.rank)));
+    LowValue { category: paired_category(&groups), ranks: groups.iter().map(|&(_, rank)| rank).collect() }
+}
+
+pub fn evaluate_low(cards: &[Card]) -> LowValue {
+    assert_eq!(cards.len(), 5, "A poker hand has five cards");
+    low_value(cards)
+}
+
+/// Five cards ranked for deuce-to-seven lowball: the high hand value read backwards, the ace
+/// playing only high so that A-2-3-4-5 is no straight
+pub fn evaluate_deuce_to_seven(cards: &[Card]) -> LowValue {
+    let value = evaluate(cards, Rank::Ace);
+    LowValue { category: value.category, ranks: value.ranks.iter().map(|&rank| rank as u8).collect() }
+}
+
+/// Ace-to-five strength of the up-cards of a Razz hand, one to four of them
+pub fn evaluate_showing_low(cards: &[Card]) -> LowValue {
+    low_value(cards)
+}
+
+/// Best ace-to-five low out of five to seven cards, qualifying or not
+pub fn best_low(cards: &[Card]) -> LowValue {
+    combinations(cards, 5).iter()
+        .map(|five| evaluate_low(five))
+        .min()
+        .expect("Expect at least five cards")
+}
+
+/// Best Omaha eight-or-better low: two of the `hole` cards with three of the `board`, if
+/// any such five cards qualify
+pub fn best_omaha_low(hole: &[Card], board: &[Card]) -> Option<LowValue> {
+    let boards = combinations(board, 3);
+    combinations(hole, 2).iter()
+        .flat_map(|two| boards.iter().map(move |three| evaluate_low(&[&two[..], &three[..]].concat())))
+        .filter(|value| value.is_eight_or_better())
+        .min()
+}
+
+fn unseen_cards(mut deck: Deck, known: &[&[Card]]) -> Vec<Card> {
+    (0..deck.len()).map(|_| deck.get_next_card())
+        .filter(|card| !known.iter().any(|cards| cards.contains(card)))
+        .collect()
+}
+
+/// Whether no other two hole cards make a better hand on this board, as it stands
+pub fn is_nuts(hole: &[Card], board: &[Card]) -> bool {
+    let mine = best_hand(&[hole, board].concat());
+    combinations(&unseen_cards(Deck::create(), &[hole, board]), 2).iter()
+        .all(|other| best_hand(&[&other[..], board].concat()) <= mine)
+}
+
+/// Share of the pot `hole` wins against `opponents` random hands of `variant`, by Monte Carlo
+/// over the unseen cards, the cards still to come dealt at random. Up-cards opponents show
+/// are not taken into account, and draw games count hands as they stand, as if every player
+/// stood pat. Split pots count fractionally, hi-lo pots as two halves when a low qualifies.
+pub fn equity(variant: GameVariant, hole: &[Card], board: &[Card], opponents: usize, iterations: u32, seed: u64) -> f64 {
+    let mut unseen = unseen_cards(variant.deck(), &[hole, board]);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let missing_board = variant.board_cards().saturating_sub(board.len());
+    let missing_hole = variant.hole_cards().saturating_sub(hole.len());
+    let mut won = 0.0;
+
+    for _ in 0..iterations {
+        unseen.shuffle(&mut rng);
+        let mut next = unseen.iter().copied();
+
+        let full_board: Vec<Card> = board.iter().copied().chain(next.by_ref().take(missing_board)).collect();
+        let full_hole: Vec<Card> = hole.iter().copied().chain(next.by_ref().take(missing_hole)).collect();
+        let mine = variant.high_hand(&full_hole, &full_board);
+        let my_low = variant.low_hand(&full_hole, &full_board);
+
+        let mut best_opponent: Option<HandValue> = None;
+        let mut tied = 0;
+        let mut best_opponent_low: Option<LowValue> = None;
+        let mut tied_low = 0;
+        for _ in 0..opponents {
+            let opponent_hole: Vec<Card> = next.by_ref().take(variant.hole_cards()).collect();
+            if let (Some(theirs), Some(mine)) = (variant.high_hand(&opponent_hole, &full_board), mine.as_ref()) {
+                match variant.compare_high(&theirs, mine) {
+                    Ordering::Equal => tied += 1,
+                    Ordering::Greater => best_opponent = Some(theirs),
+                    Ordering::Less => {}
+                }
+            }
+
+            if let Some(their_low) = variant.low_hand(&opponent_hole, &full_board) {
+                match my_low.as_ref().map(|low| their_low.cmp(low)) {
+                    Some(Ordering::Equal) => tied_low += 1,
+                    Some(Ordering::Greater) => {}
+                    Some(Ordering::Less) | None => best_opponent_low = Some(their_low),
+                }
+            }
+        }
+
+        // Lowball games have no high half, the low takes the whole pot
+        let (high_share, low_share) = match (mine.is_some(), my_low.is_some() || best_opponent_low.is_some()) {
+            (true, true) => (0.5, 0.5),
+            (true, false) => (1.0, 0.0),
+            (false, _) => (0.0, 1.0),
+        };
+        if mine.is_some() && best_opponent.is_none() {
+            won += high_share / (tied + 1) as f64;
+        }
+        if my_low.is_some() && best_opponent_low.is_none() {
+            won += low_share / (tied_low + 1) as f64;
+        }
+    }
+
+    if iterations == 0 { return 0.0; }
+    won / iterations as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(codes: &str) -> Vec<Card> {
+        codes.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect()
+    }
+
+    fn category(codes: &str) -> HandCategory {
+        evaluate_five(&cards(codes)).category
+    }
+
+    #[test]
+    fn test_categories() {
+        assert_eq!(category("As Ks Qs Js Ts"), HandCategory::StraightFlush);
+        assert_eq!(category("9h 9d 9c 9s 2d"), HandCategory::FourOfAKind);
+        assert_eq!(category("3h 3d 3c Ks Kd"), HandCategory::FullHouse);
+        assert_eq!(category("2h 7h 9h Jh Kh"), HandCategory::Flush);
+        assert_eq!(category("5h 6d 7c 8s 9d"), HandCategory::Straight);
+        assert_eq!(category("Ah 2d 3c 4s 5d"), HandCategory::Straight);
+        assert_eq!(category("Qh Qd Qc 4s 2d"), HandCategory::ThreeOfAKind);
+        assert_eq!(category("Jh Jd 4c 4s Ad"), HandCategory::TwoPair);
+        assert_eq!(category("Th Td 8c 4s 2d"), HandCategory::OnePair);
+        assert_eq!(category("Ah Jd 8c 4s 2d"), HandCategory::HighCard);
+        assert_eq!(category("Qh Kd Ac 2s 3d"), HandCategory::HighCard);
+    }
+
+    #[test]
+    fn test_ties_are_broken_by_ranks() {
+        let wheel = evaluate_five(&cards("Ah 2d 3c 4s 5d"));
+        let six_high = evaluate_five(&cards("2h 3d 4c 5s 6d"));
+        assert!(six_high > wheel);
+
+        let aces_up = evaluate_five(&cards("Ah Ad 4c 4s 2d"));
+        let kings_up = evaluate_five(&cards("Kh Kd Qc Qs Ad"));
+        assert!(aces_up > kings_up);
+
+        let better_kicker = evaluate_five(&cards("Th Td Ac 4s 2d"));
+        let worse_kicker = evaluate_five(&cards("Ts Tc Kc 4d 2h"));
+        assert!(better_kicker > worse_kicker);
+
+        assert_eq!(evaluate_five(&cards("Th Td Ac 4s 2d")), evaluate_five(&cards("Ts Tc Ad 4h 2c")));
+    }
+
+    #[test]
+    fn test_best_hand_of_seven() {
+        let value = best_hand(&cards("As Ks 2d 2c Qs Js Ts"));
+        assert_eq!(value.category, HandCategory::StraightFlush);
+        assert_eq!(value.ranks, vec![Rank::Ace]);
+
+        assert_eq!(combinations(&cards("As Ks 2d 2c Qs Js Ts"), 5).len(), 21);
+    }
+
+    #[test]
+    fn test_is_nuts() {
+        assert!(is_nuts(&cards("As 5s"), &cards("Ks 9s 2s 3d")));
+        assert!(is_nuts(&cards("Ks Kd"), &cards("Kh 9s 2s 3d")));
+        assert!(!is_nuts(&cards("Kc Kd"), &cards("Kh 9s 2s 3s")));
+        assert!(is_nuts(&cards("Ah Kd"), &cards("Qs Jc Th 2d 3c")));
+        assert!(!is_nuts(&cards("Ah 2d"), &cards("Qs Jc Th 2c 3c")));
+    }
+
+    #[test]
+    fn test_equity() {
+        let aces = cards("As Ah");
+        let preflop = equity(GameVariant::Holdem, &aces, &[], 1, 2000, 1);
+        assert!(preflop > 0.8 && preflop < 0.9, "Aces should win about 85% heads-up, got {}", preflop);
+
+        let nuts = equity(GameVariant::Holdem, &cards("As Ks"), &cards("Qs Js Ts 2d 3c"), 3, 200, 1);
+        assert_eq!(nuts, 1.0);
+
+        assert_eq!(equity(GameVariant::Holdem, &aces, &[], 1, 500, 9), equity(GameVariant::Holdem, &aces, &[], 1, 500, 9));
+    }
+
+    #[test]
+    fn test_omaha_plays_exactly_two_hole_cards() {
+        // Four hearts in hand make no flush without three hearts on the board
+        let value = best_omaha_hand(&cards("Ah Kh Qh Jh"), &cards("Th 9c 8d 2s 3c"));
+        assert_eq!(value.category, HandCategory::Straight);
+        assert_eq!(value.ranks, vec![Rank::Queen]);
+
+        // A board straight flush does not play by itself
+        let value = best_omaha_hand(&cards("2c 2d 7h 8s"), &cards("As Ks Qs Js Ts"));
+        assert_eq!(value.category, HandCategory::OnePair);
+        assert_eq!(value.ranks, vec![Rank::Two, Rank::Ace, Rank::King, Rank::Queen]);
+    }
+
+    #[test]
+    fn test_eight_or_better_low() {
+        let wheel = evaluate_low(&cards("Ah 2d 3c 4s 5d"));
+        let six_four = evaluate_low(&cards("6h 4d 3c 2s Ad"));
+        assert!(wheel < six_four, "the wheel is the best low");
+        assert!(wheel.is_eight_or_better());
+        assert!(!evaluate_low(&cards("9h 4d 3c 2s Ad")).is_eight_or_better());
+        assert!(!evaluate_low(&cards("4h 4d 3c 2s Ad")).is_eight_or_better());
+
+        assert_eq!(best_omaha_low(&cards("Ah 2d Kc Ks"), &cards("3h 6c 8d Qs Jd")).unwrap().ranks, vec![8, 6, 3, 2, 1]);
+        assert_eq!(best_omaha_low(&cards("Ah Kd Kc Ks"), &cards("3h 6c 8d 2s 4d")), None, "one low hole card is not enough");
+        assert_eq!(best_omaha_low(&cards("Ah 2d 3c 4s"), &cards("9h Tc 8d Qs Jd")), None, "the board needs three low cards");
+    }
+
+    #[test]
+    fn test_deuce_to_seven_low() {
+        let number_one = evaluate_deuce_to_seven(&cards("7h 5d 4c 3s 2d"));
+        assert!(number_one < evaluate_deuce_to_seven(&cards("7h 6d 4c 3s 2d")));
+        assert!(number_one < evaluate_deuce_to_seven(&cards("8h 5d 4c 3s 2d")));
+
+        // Aces are high and straights and flushes count against the hand
+        assert_eq!(evaluate_deuce_to_seven(&cards("Ah 5d 4c 3s 2d")).category, HandCategory::HighCard);
+        assert!(evaluate_deuce_to_seven(&cards("Kh Qd Jc 9s 8d")) < evaluate_deuce_to_seven(&cards("Ah 5d 4c 3s 2d")));
+        assert_eq!(evaluate_deuce_to_seven(&cards("6h 5d 4c 3s 2d")).category, HandCategory::Straight);
+        assert_eq!(evaluate_deuce_to_seven(&cards("7h 5h 4h 3h 2h")).category, HandCategory::Flush);
+        assert!(evaluate_deuce_to_seven(&cards("Kh Kd 4c 3s 2d")) > evaluate_deuce_to_seven(&cards("Ah Qd Jc 9s 8d")));
+    }
+
+    #[test]
+    fn test_hi_lo_equity_counts_halves() {
+        // The nut low takes at least half of the pot, unless another ace-deuce shares it
+        let share = equity(GameVariant::OmahaHiLo, &cards("Ah 2d Kc Qc"), &cards("3h 4c 7d Ts 9s"), 1, 500, 1);
+        assert!(share > 0.4 && share < 1.0, "got {}", share);
+        assert!(equity(GameVariant::Omaha, &cards("Ah 2d Kc Qc"), &cards("3h 4c 7d Ts 9s"), 1, 500, 1) < share);
+    }
+
+    #[test]
+    fn test_short_deck_rankings() {
+        let flush = evaluate_short_deck(&cards("6h 8h 9h Jh Kh"));
+        let full_house = evaluate_short_deck(&cards("As Ad Ac Ks Kd"));
+        assert_eq!(compare_short_deck(&flush, &full_house), Ordering::Greater);
+        assert!(evaluate_five(&cards("6h 8h 9h Jh Kh")) < evaluate_five(&cards("As Ad Ac Ks Kd")));
+
+        let low_straight = evaluate_short_deck(&cards("Ah 6d 7c 8s 9d"));
+        assert_eq!(low_straight.category, HandCategory::Straight);
+        assert_eq!(low_straight.ranks, vec![Rank::Nine]);
+        assert_eq!(category("Ah 6d 7c 8s 9d"), HandCategory::HighCard);
+        assert_eq!(compare_short_deck(&low_straight, &evaluate_short_deck(&cards("6h 7d 8c 9s Td"))), Ordering::Less);
+
+        assert_eq!(best_short_deck_hand(&cards("Ah Ad Ac Kh Kd 7h 9h")).category, HandCategory::FullHouse);
+        assert_eq!(best_short_deck_hand(&cards("Ah Ad Ac Kh Kd 7h 9h Th")).category, HandCategory::Flush);
+    }
+}
+
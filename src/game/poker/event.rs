@@ -0,0 +1,304 @@
+use std::io;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::deck::Card;
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::blind::Blind;
+use crate::game::poker::hand::HandStage;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeatedPlayer {
+    pub seat: u32,
+    pub player_id: usize,
+    pub name: String,
+    pub stack: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "event"))]
+pub enum HandEventKind {
+    /// Players are listed in dealing order, starting from the dealer, with their stacks before the blinds
+    HandStarted {
+        seed: u64,
+        dealer_seat: u32,
+        blind_price: u32,
+        betting: BettingStructure,
+        variant: GameVariant,
+        ante: u32,
+        players: Vec<SeatedPlayer>,
+    },
+    AntePosted { seat: u32, amount: u32 },
+    BlindPosted { seat: u32, blind: Blind, amount: u32 },
+    /// The forced opening bet of stud games, posted after the first street is dealt
+    BringInPosted { seat: u32, amount: u32 },
+    /// Cards dealt face down, seen by their player only
+    HoleCardsDealt { seat: u32, cards: Vec<Card> },
+    /// Cards dealt face up to a player in stud games, seen by everyone
+    UpCardsDealt { seat: u32, cards: Vec<Card> },
+    ActionTaken { seat: u32, stage: HandStage, action: PokerAction },
+    /// How many cards a player exchanged at a draw, seen by everyone: none stands pat
+    DrawTaken { seat: u32, stage: HandStage, count: u32 },
+    /// The cards a player threw away at a draw and their replacements, seen by that player
+    /// only; a player standing pat has none
+    CardsDrawn { seat: u32, discarded: Vec<Card>, drawn: Vec<Card> },
+    /// A new street starts, `cards` being turned face up on the board: none in stud games,
+    /// where the players' cards of the street follow
+    BoardDealt { stage: HandStage, cards: Vec<Card> },
+    Showdown { seat: u32, cards: Vec<Card> },
+    PotAwarded { seat: u32, amount: u32 },
+}
+
+/// One entry of the canonical record of a hand, which replays, stats and exports are built from.
+///
+/// An event is a flat JSON object, `event` holding the variant name and stages and blinds
+/// theirs (`INIT`, `SMALL`):
+/// `{"timestamp_ms":1000,"event":"ActionTaken","seat":2,"stage":"FLOP","action":{"type":"check"}}`.
+/// `to_json` writes it with serde, or without the `serde` feature with a fallback of the same shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandEvent {
+    pub timestamp_ms: u64,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub kind: HandEventKind,
+}
+
+/// Receives every event of a hand as the engine emits it.
+pub trait HandEventListener {
+    fn on_event(&mut self, event: &HandEvent);
+}
+
+impl<F: FnMut(&HandEvent)> HandEventListener for F {
+    fn on_event(&mut self, event: &HandEvent) {
+        self(event)
+    }
+}
+
+impl HandEvent {
+    pub fn now(kind: HandEventKind) -> Self {
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+        HandEvent { timestamp_ms, kind }
+    }
+
+    /// A single-line JSON object, `event` holding the variant name
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Expect events to serialise")
+    }
+
+    /// A single-line JSON object, `event` holding the variant name
+    #[cfg(not(feature = "serde"))]
+    pub fn to_json(&self) -> String {
+        fallback::to_json(self)
+    }
+}
+
+/// The encoder of `to_json` for builds without the `serde` feature, checked against serde by the tests
+#[cfg(any(not(feature = "serde"), test))]
+mod fallback {
+    use super::*;
+    use crate::game::poker::json;
+
+    fn cards_json(cards: &[Card]) -> String {
+        let codes: Vec<String> = cards.iter().map(|c| format!("\"{}\"", c.code())).collect();
+        format!("[{}]", codes.join(","))
+    }
+
+    /// Variant names, as the `serde` feature writes them rather than as they are displayed
+    fn stage_json(stage: &HandStage) -> &'static str {
+        match stage {
+            HandStage::INIT => "INIT",
+            HandStage::FLOP => "FLOP",
+            HandStage::TURN => "TURN",
+            HandStage::SIXTH => "SIXTH",
+            HandStage::RIVER => "RIVER",
+        }
+    }
+
+    fn blind_json(blind: &Blind) -> &'static str {
+        match blind {
+            Blind::NONE => "NONE",
+            Blind::SMALL => "SMALL",
+            Blind::BIG => "BIG",
+        }
+    }
+
+    fn action_json(action: &PokerAction) -> String {
+        match action {
+            PokerAction::Fold => String::from("{\"type\":\"fold\"}"),
+            PokerAction::Check => String::from("{\"type\":\"check\"}"),
+            PokerAction::Call(amount) => format!("{{\"type\":\"call\",\"amount\":{}}}", amount),
+            PokerAction::Bet(amount) => format!("{{\"type\":\"bet\",\"amount\":{}}}", amount),
+            PokerAction::Raise(amount) => format!("{{\"type\":\"raise\",\"amount\":{}}}", amount),
+        }
+    }
+
+    pub fn to_json(event: &HandEvent) -> String {
+        let body = match &event.kind {
+            HandEventKind::HandStarted { seed, dealer_seat, blind_price, betting, variant, ante, players } => {
+                let players: Vec<String> = players.iter().map(|p| format!(
+                    "{{\"seat\":{},\"player_id\":{},\"name\":\"{}\",\"stack\":{}}}",
+                    p.seat, p.player_id, json::escape(&p.name), p.stack
+                )).collect();
+                let betting = match betting {
+                    BettingStructure::FixedLimit { raise_cap } => format!("{{\"type\":\"fixed-limit\",\"raise_cap\":{}}}", raise_cap),
+                    other => format!("{{\"type\":\"{}\"}}", other.code()),
+                };
+                format!(
+                    "\"event\":\"HandStarted\",\"seed\":{},\"dealer_seat\":{},\"blind_price\":{},\"betting\":{},\"variant\":\"{}\",\"ante\":{},\"players\":[{}]",
+                    seed, dealer_seat, blind_price, betting, variant.code(), ante, players.join(",")
+                )
+            }
+            HandEventKind::AntePosted { seat, amount } =>
+                format!("\"event\":\"AntePosted\",\"seat\":{},\"amount\":{}", seat, amount),
+            HandEventKind::BlindPosted { seat, blind, amount } =>
+                format!("\"event\":\"BlindPosted\",\"seat\":{},\"blind\":\"{}\",\"amount\":{}", seat, blind_json(blind), amount),
+            HandEventKind::BringInPosted { seat, amount } =>
+                format!("\"event\":\"BringInPosted\",\"seat\":{},\"amount\":{}", seat, amount),
+            HandEventKind::HoleCardsDealt { seat, cards } =>
+                format!("\"event\":\"HoleCardsDealt\",\"seat\":{},\"cards\":{}", seat, cards_json(cards)),
+            HandEventKind::UpCardsDealt { seat, cards } =>
+                format!("\"event\":\"UpCardsDealt\",\"seat\":{},\"cards\":{}", seat, cards_json(cards)),
+            HandEventKind::ActionTaken { seat, stage, action } =>
+                format!("\"event\":\"ActionTaken\",\"seat\":{},\"stage\":\"{}\",\"action\":{}", seat, stage_json(stage), action_json(action)),
+            HandEventKind::DrawTaken { seat, stage, count } =>
+                format!("\"event\":\"DrawTaken\",\"seat\":{},\"stage\":\"{}\",\"count\":{}", seat, stage_json(stage), count),
+            HandEventKind::CardsDrawn { seat, discarded, drawn } =>
+                format!("\"event\":\"CardsDrawn\",\"seat\":{},\"discarded\":{},\"drawn\":{}", seat, cards_json(discarded), cards_json(drawn)),
+            HandEventKind::BoardDealt { stage, cards } =>
+                format!("\"event\":\"BoardDealt\",\"stage\":\"{}\",\"cards\":{}", stage_json(stage), cards_json(cards)),
+            HandEventKind::Showdown { seat, cards } =>
+                format!("\"event\":\"Showdown\",\"seat\":{},\"cards\":{}", seat, cards_json(cards)),
+            HandEventKind::PotAwarded { seat, amount } =>
+                format!("\"event\":\"PotAwarded\",\"seat\":{},\"amount\":{}", seat, amount),
+        };
+
+        format!("{{\"timestamp_ms\":{},{}}}", event.timestamp_ms, body)
+    }
+}
+
+/// Writes one JSON object per line
+pub fn write_json_lines<W: Write>(events: &[HandEvent], writer: &mut W) -> io::Result<()> {
+    for event in events {
+        writeln!(writer, "{}", event.to_json())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{Rank, Suit};
+
+    fn event(kind: HandEventKind) -> HandEvent {
+        HandEvent { timestamp_ms: 1000, kind }
+    }
+
+    #[test]
+    fn test_now_sets_timestamp() {
+        let event = HandEvent::now(HandEventKind::PotAwarded { seat: 1, amount: 10 });
+        assert!(event.timestamp_ms > 0);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let started = event(HandEventKind::HandStarted {
+            seed: 7,
+            dealer_seat: 1,
+            blind_price: 5,
+            betting: BettingStructure::FixedLimit { raise_cap: 4 },
+            variant: GameVariant::OmahaHiLo,
+            ante: 0,
+            players: vec![SeatedPlayer { seat: 1, player_id: 3, name: String::from("Cautious \"GPT\""), stack: 100 }],
+        });
+        assert_eq!(
+            started.to_json(),
+            "{\"timestamp_ms\":1000,\"event\":\"HandStarted\",\"seed\":7,\"dealer_seat\":1,\"blind_price\":5,\"betting\":{\"type\":\"fixed-limit\",\"raise_cap\":4},\"variant\":\"omaha-hi-lo\",\"ante\":0,\"players\":[{\"seat\":1,\"player_id\":3,\"name\":\"Cautious \\\"GPT\\\"\",\"stack\":100}]}"
+        );
+
+        let dealt = event(HandEventKind::HoleCardsDealt {
+            seat: 2,
+            cards: vec![Card { rank: Rank::Ace, suit: Suit::Spades }, Card { rank: Rank::Ten, suit: Suit::Hearts }],
+        });
+        assert_eq!(dealt.to_json(), "{\"timestamp_ms\":1000,\"event\":\"HoleCardsDealt\",\"seat\":2,\"cards\":[\"As\",\"Th\"]}");
+
+        let action = event(HandEventKind::ActionTaken { seat: 2, stage: HandStage::FLOP, action: PokerAction::Raise(40) });
+        assert_eq!(action.to_json(), "{\"timestamp_ms\":1000,\"event\":\"ActionTaken\",\"seat\":2,\"stage\":\"FLOP\",\"action\":{\"type\":\"raise\",\"amount\":40}}");
+
+        let ante = event(HandEventKind::AntePosted { seat: 3, amount: 5 });
+        assert_eq!(ante.to_json(), "{\"timestamp_ms\":1000,\"event\":\"AntePosted\",\"seat\":3,\"amount\":5}");
+    }
+
+    #[test]
+    fn test_write_json_lines() {
+        let events = vec![
+            event(HandEventKind::BlindPosted { seat: 1, blind: Blind::SMALL, amount: 5 }),
+            event(HandEventKind::PotAwarded { seat: 1, amount: 15 }),
+        ];
+        let mut output = vec![];
+        write_json_lines(&events, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"blind\":\"SMALL\""));
+        assert!(lines[1].contains("\"event\":\"PotAwarded\""));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_shape() {
+        let action = event(HandEventKind::ActionTaken { seat: 2, stage: HandStage::FLOP, action: PokerAction::Raise(40) });
+        assert_eq!(
+            serde_json::to_string(&action).unwrap(),
+            "{\"timestamp_ms\":1000,\"event\":\"ActionTaken\",\"seat\":2,\"stage\":\"FLOP\",\"action\":{\"type\":\"raise\",\"amount\":40}}"
+        );
+
+        let check: HandEvent = serde_json::from_str(
+            "{\"timestamp_ms\":5,\"event\":\"ActionTaken\",\"seat\":1,\"stage\":\"INIT\",\"action\":{\"type\":\"check\"}}"
+        ).unwrap();
+        assert_eq!(check, HandEvent { timestamp_ms: 5, kind: HandEventKind::ActionTaken { seat: 1, stage: HandStage::INIT, action: PokerAction::Check } });
+
+        let dealt = event(HandEventKind::BoardDealt { stage: HandStage::TURN, cards: vec![Card { rank: Rank::Two, suit: Suit::Clubs }] });
+        let json = serde_json::to_string(&dealt).unwrap();
+        assert!(json.contains("\"cards\":[\"2c\"]"));
+        assert_eq!(serde_json::from_str::<HandEvent>(&json).unwrap(), dealt);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_fallback_writes_what_serde_writes() {
+        let cards = vec![Card { rank: Rank::Ace, suit: Suit::Spades }, Card { rank: Rank::Two, suit: Suit::Clubs }];
+        let kinds = vec![
+            HandEventKind::HandStarted {
+                seed: u64::MAX,
+                dealer_seat: 1,
+                blind_price: 5,
+                betting: BettingStructure::FixedLimit { raise_cap: 4 },
+                variant: GameVariant::TripleDraw,
+                ante: 1,
+                players: vec![SeatedPlayer { seat: 1, player_id: 3, name: String::from("Cautious \"GPT\""), stack: 100 }],
+            },
+            HandEventKind::AntePosted { seat: 1, amount: 1 },
+            HandEventKind::BlindPosted { seat: 1, blind: Blind::SMALL, amount: 5 },
+            HandEventKind::BringInPosted { seat: 2, amount: 5 },
+            HandEventKind::HoleCardsDealt { seat: 1, cards: cards.clone() },
+            HandEventKind::UpCardsDealt { seat: 1, cards: cards.clone() },
+            HandEventKind::ActionTaken { seat: 1, stage: HandStage::INIT, action: PokerAction::Call(5) },
+            HandEventKind::DrawTaken { seat: 1, stage: HandStage::SIXTH, count: 2 },
+            HandEventKind::CardsDrawn { seat: 1, discarded: cards.clone(), drawn: cards.clone() },
+            HandEventKind::BoardDealt { stage: HandStage::RIVER, cards: cards.clone() },
+            HandEventKind::Showdown { seat: 1, cards },
+            HandEventKind::PotAwarded { seat: 1, amount: 20 },
+        ];
+
+        for kind in kinds {
+            let written = event(kind);
+            assert_eq!(fallback::to_json(&written), written.to_json());
+            assert_eq!(serde_json::from_str::<HandEvent>(&written.to_json()).unwrap(), written);
+        }
+    }
+}
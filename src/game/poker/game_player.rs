@@ -4,12 +4,15 @@ use crate::player::{Player};
 use crate::game::poker::error::PokerGameError;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PokerGamePlayer {
     pub seat: u32,
     pub player: Player,
     pub blind: Blind,
     pub cash_amount: u32,
     pub hand: Vec<Card>,
+    /// Cards of `hand` dealt face up, in stud games
+    pub up_cards: Vec<Card>,
     pub buy_ins: u32,
 }
 
@@ -21,19 +24,21 @@ impl PokerGamePlayer {
             blind: Blind::NONE,
             cash_amount: 0,
             hand: vec![],
+            up_cards: vec![],
             buy_ins: 0,
         }
     }
 
     pub fn fund(&mut self, amount: u32) -> Result<(), PokerGameError>  {
-        if amount <= 0 { return Err(PokerGameError::CannotFundNonGreaterThanZeroValues(amount)); }
+        if amount == 0 { return Err(PokerGameError::CannotFundNonGreaterThanZeroValues(amount)); }
         self.cash_amount += amount;
         self.buy_ins += 1;
         Ok(())
     }
 
     pub fn show_hand(&self) {
-        println!("🧠 {}: {} {}", self.player.name, self.hand[0], self.hand[1]);
+        let cards: Vec<String> = self.hand.iter().map(|card| card.to_string()).collect();
+        println!("🧠 {}: {}", self.player.name, cards.join(" "));
     }
 
     pub fn receive_card(&mut self, card: Card) -> Result<(), PokerGameError> {
@@ -43,7 +48,7 @@ impl PokerGamePlayer {
         }
 
         // check if already exists
-        let exists = self.hand.iter().any(|c| *c == card);
+        let exists = self.hand.contains(&card);
 
         if exists {
             return Err(PokerGameError::CardAlreadyInHand(card))
@@ -54,6 +59,27 @@ impl PokerGamePlayer {
         Ok(())
 
     }
+
+    /// Throws `card` away at a draw, keeping the order of the other cards
+    pub fn discard(&mut self, card: Card) -> Result<(), PokerGameError> {
+        let index = self.hand.iter().position(|c| *c == card);
+        if index.is_none() {
+            return Err(PokerGameError::CardNotInHand(card));
+        }
+
+        self.hand.remove(index.expect("Expect the card to be in the hand"));
+        Ok(())
+    }
+}
+
+/// Players at `seats`, each a (seat, name) with the seat as player id, funded with `stack`
+#[cfg(test)]
+pub(crate) fn funded_players(seats: &[(u32, &str)], stack: u32) -> Vec<PokerGamePlayer> {
+    seats.iter().map(|&(seat, name)| {
+        let mut player = PokerGamePlayer::create(seat, Player::create(seat as usize, name, "l.png"));
+        player.fund(stack).unwrap();
+        player
+    }).collect()
 }
 
 #[cfg(test)]
@@ -89,20 +115,13 @@ mod tests {
         let player = Player::create(1, "Bob", "example.png");
         let mut pgp = PokerGamePlayer::create(3, player);
 
-        pgp.fund(500)
-            .and_then(|_| {
-                assert_eq!(pgp.cash_amount, 500);
-                assert_eq!(pgp.buy_ins, 1);
-                Ok(())
-            }).expect("TODO: panic message");
+        pgp.fund(500).expect("Expect to fund 500");
+        assert_eq!(pgp.cash_amount, 500);
+        assert_eq!(pgp.buy_ins, 1);
 
-
-        pgp.fund(250)
-            .and_then(|_| {
-                assert_eq!(pgp.cash_amount, 750);
-                assert_eq!(pgp.buy_ins, 2);
-                Ok(())
-            }).expect("TODO: panic message");
+        pgp.fund(250).expect("Expect to fund 250");
+        assert_eq!(pgp.cash_amount, 750);
+        assert_eq!(pgp.buy_ins, 2);
     }
     #[test]
     fn test_fund_zero_value() {
@@ -140,6 +159,16 @@ mod tests {
         assert!(pgp.hand.contains(&card2));
     }
 
+    #[test]
+    fn test_receive_four_cards_for_omaha() {
+        let mut pgp = PokerGamePlayer::create(1, Player::create(2, "Bob", "example.png"));
+        for rank in [Rank::Ace, Rank::King, Rank::Queen, Rank::Jack] {
+            assert!(pgp.receive_card(Card { rank, suit: Suit::Clubs }).is_ok());
+        }
+
+        assert_eq!(pgp.hand.len(), 4);
+    }
+
     #[test]
     fn test_receive_duplicate_card_returns_error() {
         let player = Player::create(3, "Charlie", "example.png");
@@ -151,4 +180,15 @@ mod tests {
         let result = pgp.receive_card(card);
         assert!(matches!(result, Err(PokerGameError::CardAlreadyInHand(_))));
     }
+
+    #[test]
+    fn test_discard_removes_only_held_cards() {
+        let player = Player::create(3, "Charlie", "example.png");
+        let mut pgp = PokerGamePlayer::create(2, player);
+        pgp.receive_card(sample_card()).unwrap();
+
+        assert!(matches!(pgp.discard(second_card()), Err(PokerGameError::CardNotInHand(_))));
+        assert!(pgp.discard(sample_card()).is_ok());
+        assert!(pgp.hand.is_empty());
+    }
 }
@@ -1,79 +1,679 @@
-use std::ops::Index;
+use std::cmp::{min, Ordering};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
 use crate::deck::{Card, Deck};
+use crate::game::poker::action::{LegalActions, PokerAction};
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::{GameVariant, Street};
+use crate::game::poker::blind::Blind;
 use crate::game::poker::error::PokerGameError;
+use crate::game::poker::evaluator::{HandValue, LowValue};
+use crate::game::poker::event::{HandEvent, HandEventKind, HandEventListener, SeatedPlayer};
 use crate::game::poker::game_player::PokerGamePlayer;
-use crate::player::Player;
 
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HandStage {
     INIT,
     FLOP,
-    RIVER,
-    TURN
+    TURN,
+    /// Sixth street, only dealt in stud games
+    SIXTH,
+    RIVER
 }
 
-struct Fold {
-    player: PokerGamePlayer,
-    at: HandStage
+impl fmt::Display for HandStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            HandStage::INIT => "PRE-FLOP",
+            HandStage::FLOP => "FLOP",
+            HandStage::TURN => "TURN",
+            HandStage::SIXTH => "SIXTH STREET",
+            HandStage::RIVER => "RIVER",
+        };
+
+        write!(f, "{}", res)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fold {
+    pub player: PokerGamePlayer,
+    pub at: HandStage
 }
 
-struct PokerHand {
-    pot: u32,
-    flop: [Card; 3],
-    river: Card,
-    turn: Card,
-    dealer_seat: u32,
-    small_blind_seat: u32,
-    big_blind_seat: u32,
-    blind_price: u32,
-    speaker: PokerGamePlayer,
-    folds: Vec<Fold>,
-    active: Vec<PokerGamePlayer>
+/// One hand of poker, from blinds or antes to settlement, dealt street by street as its
+/// variant says. Every change is appended to `events`.
+///
+/// With the `serde` feature a hand serialises field by field, cards as codes; subscribed
+/// listeners are not part of it.
+///
+/// ```
+/// use llm_holdem::game::poker::action::PokerAction;
+/// use llm_holdem::game::poker::betting::BettingStructure;
+/// use llm_holdem::game::poker::variant::GameVariant;
+/// use llm_holdem::game::poker::game_player::PokerGamePlayer;
+/// use llm_holdem::game::poker::hand::{HandStage, PokerHand, StartHandParams};
+/// use llm_holdem::player::Player;
+///
+/// let players = [(1, "Alice"), (2, "Bob")].map(|(seat, name)| {
+///     let mut player = PokerGamePlayer::create(seat, Player::create(seat as usize, name, ""));
+///     player.fund(100).unwrap();
+///     player
+/// });
+/// let mut hand = PokerHand::start(StartHandParams {
+///     blind_price: 1,
+///     players: players.to_vec(),
+///     dealer_seat: 1,
+///     seed: 42,
+///     betting: BettingStructure::NoLimit,
+///     variant: GameVariant::Holdem,
+///     ante: 0,
+/// }).unwrap();
+///
+/// // Heads-up the dealer posts the small blind and speaks first
+/// assert_eq!(hand.to_call(1), 1);
+/// hand.act(1, PokerAction::Call(1)).unwrap();
+/// hand.act(2, PokerAction::Check).unwrap();
+/// assert!(hand.is_round_complete());
+///
+/// hand.deal_next_stage().unwrap();
+/// assert_eq!(hand.stage, HandStage::FLOP);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PokerHand {
+    pub pot: u32,
+    /// Community cards turned so far
+    pub board: Vec<Card>,
+    /// Cards left to deal
+    pub deck: Deck,
+    /// Cards thrown away at the draws, shuffled into a new deck when it runs out
+    pub discards: Vec<Card>,
+    pub dealer_seat: u32,
+    /// Seats of the blinds, 0 when antes or a bring-in replace them
+    pub small_blind_seat: u32,
+    pub big_blind_seat: u32,
+    pub blind_price: u32,
+    pub speaker: PokerGamePlayer,
+    pub folds: Vec<Fold>,
+    pub active: Vec<PokerGamePlayer>,
+    pub seed: u64,
+    pub stage: HandStage,
+    pub events: Vec<HandEvent>,
+    /// Chips each seat put in during the current street, blinds and bring-in included
+    pub street_bets: BTreeMap<u32, u32>,
+    /// Chips each seat put in during the whole hand
+    pub contributions: BTreeMap<u32, u32>,
+    /// Largest street bet so far
+    pub current_bet: u32,
+    /// Increment of the last full bet or raise this street, the least the next raise must add
+    pub min_raise: u32,
+    /// Seats that acted since the last full bet or raise of the street
+    pub acted: Vec<u32>,
+    /// Seats that exchanged cards at the draw of the current street
+    pub drawn: Vec<u32>,
+    pub betting: BettingStructure,
+    /// Bets and raises of the current street, the big blind counting as the first pre-flop and
+    /// a bring-in as none
+    pub street_raises: u32,
+    pub variant: GameVariant,
+    /// Chips every player antes instead of the blinds, none when zero
+    pub ante: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    listeners: Vec<Box<dyn HandEventListener>>,
 }
 
-struct StartHandParams {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StartHandParams {
     pub blind_price: u32,
     pub players: Vec<PokerGamePlayer>,
     pub dealer_seat: u32,
+    pub seed: u64,
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    /// When set, every player antes this much and no blinds are posted
+    pub ante: u32,
 }
 
 impl PokerHand {
-    fn start(params: StartHandParams) -> Result<PokerHand, PokerGameError>  {
-        let mut deck = Deck::create();
+    pub fn start(params: StartHandParams) -> Result<PokerHand, PokerGameError>  {
+        let mut deck = params.variant.deck();
+        deck.shuffle_with_seed(params.seed);
+        PokerHand::start_with_deck(params, deck)
+    }
+
+    /// Starts a hand dealt from `deck` as is, ignoring the seed for the card order.
+    pub fn start_with_deck(params: StartHandParams, deck: Deck) -> Result<PokerHand, PokerGameError>  {
         let players = params.players;
         let dealer_seat = params.dealer_seat;
+        let variant = params.variant;
 
-        let sorted_players_result = PokerHand::sort_players(&players, dealer_seat);
-        if sorted_players_result.is_err() {
-            return Err(sorted_players_result.unwrap_err());
+        if players.len() < 2 {
+            return Err(PokerGameError::NotEnoughPlayers(players.len() as u32));
+        }
+        if players.len() > variant.max_players() {
+            return Err(PokerGameError::TooManyPlayers(players.len() as u32, variant.max_players() as u32));
         }
 
-        let mut sorted_players = sorted_players_result.expect("Expect to be ok");
-        for _ in 0..2 {
+        let mut sorted_players = PokerHand::sort_players(&players, dealer_seat)?;
+        let mut events = vec![HandEvent::now(HandEventKind::HandStarted {
+            seed: params.seed,
+            dealer_seat,
+            blind_price: params.blind_price,
+            betting: params.betting,
+            variant,
+            ante: params.ante,
+            players: sorted_players.iter().map(|p| SeatedPlayer {
+                seat: p.seat,
+                player_id: p.player.id,
+                name: p.player.name.clone(),
+                stack: p.cash_amount,
+            }).collect(),
+        })];
+
+        // Heads-up the dealer posts the small blind. Stud games never post blinds.
+        let (small_blind_index, big_blind_index) = if sorted_players.len() == 2 { (0, 1) } else { (1, 2) };
+        let blinds = params.ante == 0 && !variant.is_stud();
+
+        let mut pot = 0;
+        let mut street_bets = BTreeMap::new();
+        let mut contributions = BTreeMap::new();
+        if params.ante > 0 {
+            // Antes are dead money: they go in the pot without counting as a bet of the street
             for player in sorted_players.iter_mut() {
-                let next_card = deck.get_next_card();
-                player.receive_card(next_card).expect("Expect to receive the card");
+                let price = min(params.ante, player.cash_amount);
+                player.cash_amount -= price;
+                pot += price;
+                contributions.insert(player.seat, price);
+                events.push(HandEvent::now(HandEventKind::AntePosted { seat: player.seat, amount: price }));
+            }
+        }
+        if blinds {
+            for (index, blind) in [(small_blind_index, Blind::SMALL), (big_blind_index, Blind::BIG)] {
+                let player = &mut sorted_players[index];
+                let price = min(params.blind_price * blind as u32, player.cash_amount);
+                player.cash_amount -= price;
+                player.blind = blind;
+                pot += price;
+                street_bets.insert(player.seat, price);
+                contributions.insert(player.seat, price);
+                events.push(HandEvent::now(HandEventKind::BlindPosted { seat: player.seat, blind, amount: price }));
+            }
+        }
+
+        let current_bet = street_bets.values().copied().max().unwrap_or(0);
+        let mut hand = PokerHand {
+            pot,
+            board: vec![],
+            deck,
+            discards: vec![],
+            dealer_seat,
+            small_blind_seat: if blinds { sorted_players[small_blind_index].seat } else { 0 },
+            big_blind_seat: if blinds { sorted_players[big_blind_index].seat } else { 0 },
+            blind_price: params.blind_price,
+            speaker: sorted_players[0].clone(),
+            folds: vec![],
+            active: sorted_players,
+            seed: params.seed,
+            stage: HandStage::INIT,
+            events,
+            current_bet,
+            contributions,
+            street_bets,
+            min_raise: params.blind_price * Blind::BIG as u32,
+            acted: vec![],
+            drawn: vec![],
+            betting: params.betting,
+            street_raises: if current_bet > 0 { 1 } else { 0 },
+            variant,
+            ante: params.ante,
+            listeners: vec![],
+        };
+        hand.deal_to_players(variant.streets()[0]);
+
+        // Without blinds the first player after the dealer speaks first, as on later streets
+        let first_to_act = if variant.is_stud() {
+            hand.post_bring_in() + 1
+        } else if blinds {
+            big_blind_index + 1
+        } else {
+            1
+        };
+        hand.speaker_from(first_to_act);
+
+        Ok(hand)
+    }
+
+    /// Deals the face-down then the face-up cards of `street`, one round at a time, to every
+    /// player still in the hand
+    fn deal_to_players(&mut self, street: Street) {
+        let burns = self.variant.burns_cards();
+        let mut dealt: Vec<(Vec<Card>, Vec<Card>)> = vec![(vec![], vec![]); self.active.len()];
+        for round in 0..street.down + street.up {
+            for (player, (down, up)) in self.active.iter_mut().zip(dealt.iter_mut()) {
+                let card = self.deck.get_next_card();
+                player.receive_card(card).expect("Expect to receive the card");
+                if round < street.down {
+                    down.push(card);
+                } else {
+                    player.up_cards.push(card);
+                    up.push(card);
+                }
+            }
+
+            if burns {
+                self.deck.get_next_card(); // Burn the next card
+            }
+        }
+
+        let seats: Vec<u32> = self.active.iter().map(|p| p.seat).collect();
+        for (seat, (down, up)) in seats.into_iter().zip(dealt) {
+            if !down.is_empty() {
+                self.emit(HandEventKind::HoleCardsDealt { seat, cards: down });
+            }
+            if !up.is_empty() {
+                self.emit(HandEventKind::UpCardsDealt { seat, cards: up });
+            }
+        }
+    }
+
+    /// The lowest up-card in stud, the highest in Razz, brings it in for the small blind: a
+    /// forced bet that does not count as a raise. Returns the index of that player.
+    fn post_bring_in(&mut self) -> usize {
+        let variant = self.variant;
+        let index = (0..self.active.len())
+            .min_by(|&a, &b| variant.compare_bring_in(&self.active[a].up_cards[0], &self.active[b].up_cards[0]))
+            .expect("Expect players in the hand");
+
+        let player = &mut self.active[index];
+        let seat = player.seat;
+        let price = min(self.blind_price, player.cash_amount);
+        player.cash_amount -= price;
+        self.pot += price;
+        self.street_bets.insert(seat, price);
+        *self.contributions.entry(seat).or_insert(0) += price;
+        self.current_bet = price;
+        // Unless someone completes the bet, calling the bring-in ends the street
+        self.acted.push(seat);
+        self.emit(HandEventKind::BringInPosted { seat, amount: price });
+
+        index
+    }
+
+    /// Forwards every event emitted so far, then every future one, to `listener`.
+    pub fn subscribe(&mut self, mut listener: Box<dyn HandEventListener>) {
+        for event in &self.events {
+            listener.on_event(event);
+        }
+        self.listeners.push(listener);
+    }
+
+    fn emit(&mut self, kind: HandEventKind) {
+        let event = HandEvent::now(kind);
+        for listener in self.listeners.iter_mut() {
+            listener.on_event(&event);
+        }
+        self.events.push(event);
+    }
+
+    /// Applies `action` for the player at `seat`, moving its chips into the pot, and passes
+    /// the turn to the next active player. Legality of the action is not checked here, only
+    /// that the draw of the street is over.
+    pub fn act(&mut self, seat: u32, action: PokerAction) -> Result<(), PokerGameError> {
+        let index = self.active.iter().position(|p| p.seat == seat);
+        if index.is_none() {
+            return Err(PokerGameError::PlayerSeatNotFound(seat));
+        }
+        if self.next_to_draw().is_some() {
+            return Err(PokerGameError::DrawPending(seat));
+        }
+        let index = index.expect("Expect to have index");
+
+        let next_index = if action == PokerAction::Fold {
+            let player = self.active.remove(index);
+            self.folds.push(Fold { player, at: self.stage });
+            index
+        } else {
+            let amount = action.amount();
+            let player = &mut self.active[index];
+            if amount > player.cash_amount {
+                return Err(PokerGameError::NotEnoughChips(seat, amount));
             }
+            player.cash_amount -= amount;
+            self.pot += amount;
 
-            deck.get_next_card(); // Burn the next card
+            let street_bet = self.street_bets.entry(seat).or_insert(0);
+            *street_bet += amount;
+            let street_bet = *street_bet;
+            *self.contributions.entry(seat).or_insert(0) += amount;
+
+            if street_bet > self.current_bet {
+                // Completing a bring-in is a full bet whatever it adds to it
+                let completing = self.street_raises == 0 && self.current_bet > 0;
+                let big_blind = self.blind_price * Blind::BIG as u32;
+                let full = if completing { street_bet >= big_blind } else { street_bet - self.current_bet >= self.min_raise };
+                // A short all-in raise reopens the betting to no one who already acted
+                if full {
+                    self.min_raise = self.min_raise.max(street_bet - self.current_bet);
+                    self.street_raises += 1;
+                    self.acted.clear();
+                }
+                self.current_bet = street_bet;
+            }
+            self.acted.push(seat);
+            index + 1
         };
 
-        let small_blind_player = &sorted_players[1];
-        let big_blind_player = &sorted_players[2];
+        self.speaker_from(next_index);
+        self.emit(HandEventKind::ActionTaken { seat, stage: self.stage, action });
 
+        Ok(())
+    }
+
+    /// Deals the next street: its board cards face up, then the cards of the players still in
+    /// the hand. The first active player after the dealer speaks first, or in stud games the
+    /// best hand showing.
+    pub fn deal_next_stage(&mut self) -> Result<Vec<Card>, PokerGameError> {
+        let street = self.variant.streets().into_iter().find(|s| s.stage > self.stage);
+        if street.is_none() {
+            return Err(PokerGameError::NoMoreStages);
+        }
+        let street = street.expect("Expect a next street");
 
+        if street.board > 0 && self.variant.burns_cards() {
+            self.deck.get_next_card(); // Burn before the board cards
+        }
+        let cards: Vec<Card> = (0..street.board).map(|_| self.deck.get_next_card()).collect();
+        self.board.extend(cards.iter().copied());
 
+        self.stage = street.stage;
+        self.street_bets.clear();
+        self.current_bet = 0;
+        self.min_raise = self.blind_price * Blind::BIG as u32;
+        self.street_raises = 0;
+        self.acted.clear();
+        self.drawn.clear();
+        self.emit(HandEventKind::BoardDealt { stage: street.stage, cards: cards.clone() });
+        self.deal_to_players(street);
 
+        if self.variant.is_stud() {
+            let best = self.best_showing();
+            self.speaker_from(best);
+        } else if let Some(first) = self.active.first() {
+            let first_to_act = if first.seat == self.dealer_seat { 1 } else { 0 };
+            self.speaker_from(first_to_act);
+        }
 
-        // return PokerHand {
-        //
-        // }
+        Ok(cards)
     }
 
+    /// Seat of the next player to exchange cards on a draw street, every player still in the
+    /// hand drawing in turn from the first after the dealer, all-in ones included
+    pub fn next_to_draw(&self) -> Option<u32> {
+        if !self.variant.draws_at(self.stage) {
+            return None;
+        }
+        let count = self.active.len();
+        let after_dealer = match self.active.first() {
+            Some(first) if first.seat == self.dealer_seat => 1,
+            _ => 0,
+        };
+
+        (0..count)
+            .map(|offset| self.active[(after_dealer + offset) % count].seat)
+            .find(|seat| !self.drawn.contains(seat))
+    }
 
-    fn sort_players(players: &Vec<PokerGamePlayer>, dealer_seat: u32) -> Result<Vec<PokerGamePlayer>, PokerGameError> {
-        let mut sorted_players = players.clone();
+    /// Throws `discards` away from the hand of `seat`, the next player to draw, and deals as
+    /// many replacements. When the deck runs out, the cards discarded by the earlier players
+    /// are shuffled into a new one. Discarding nothing stands pat. Returns the cards drawn.
+    pub fn draw(&mut self, seat: u32, discards: &[Card]) -> Result<Vec<Card>, PokerGameError> {
+        if self.next_to_draw() != Some(seat) {
+            return Err(PokerGameError::NotDrawing(seat));
+        }
+        let index = self.active.iter().position(|p| p.seat == seat).expect("Expect the next to draw to be in the hand");
+        for (position, card) in discards.iter().enumerate() {
+            if !self.active[index].hand.contains(card) || discards[..position].contains(card) {
+                return Err(PokerGameError::CardNotInHand(*card));
+            }
+        }
 
-        sorted_players.sort_by(|a, b| a.seat.cmp(&b.seat));
+        let mut drawn = vec![];
+        for _ in discards {
+            if self.deck.is_empty() {
+                self.deck = Deck::from_cards(std::mem::take(&mut self.discards));
+                self.deck.shuffle_with_seed(self.seed);
+            }
+            drawn.push(self.deck.get_next_card());
+        }
+        let player = &mut self.active[index];
+        for card in discards {
+            player.discard(*card).expect("Expect to discard a held card");
+        }
+        for card in &drawn {
+            player.receive_card(*card).expect("Expect to receive the card");
+        }
+        self.discards.extend(discards.iter().copied());
+        self.drawn.push(seat);
+
+        self.emit(HandEventKind::DrawTaken { seat, stage: self.stage, count: discards.len() as u32 });
+        if !discards.is_empty() {
+            self.emit(HandEventKind::CardsDrawn { seat, discarded: discards.to_vec(), drawn: drawn.clone() });
+        }
+
+        Ok(drawn)
+    }
+
+    /// Index of the player showing the best up-cards, ties going to the first after the dealer
+    fn best_showing(&self) -> usize {
+        let count = self.active.len();
+        let after_dealer = match self.active.first() {
+            Some(first) if first.seat == self.dealer_seat => 1,
+            _ => 0,
+        };
+
+        (0..count)
+            .map(|offset| (after_dealer + offset) % count)
+            .reduce(|best, i| match self.variant.compare_showing(&self.active[i].up_cards, &self.active[best].up_cards) {
+                Ordering::Greater => i,
+                _ => best,
+            })
+            .unwrap_or(0)
+    }
+
+    /// Gives the turn to the first player from `index` on, in dealing order, who still has chips
+    fn speaker_from(&mut self, index: usize) {
+        let count = self.active.len();
+        if count == 0 {
+            return;
+        }
+
+        let next = (0..count)
+            .map(|offset| (index + offset) % count)
+            .find(|&i| self.active[i].cash_amount > 0)
+            .unwrap_or(index % count);
+        self.speaker = self.active[next].clone();
+    }
+
+    /// Chips `seat` still has to put in to match the street bet, capped by its stack
+    pub fn to_call(&self, seat: u32) -> u32 {
+        let stack = self.active.iter().find(|p| p.seat == seat).map(|p| p.cash_amount).unwrap_or(0);
+        let street_bet = self.street_bets.get(&seat).copied().unwrap_or(0);
+        min(self.current_bet.saturating_sub(street_bet), stack)
+    }
+
+    /// Betting options of `seat` under the hand's betting structure. A stack too short for the
+    /// smallest bet or raise may still go all-in.
+    pub fn legal_actions(&self, seat: u32) -> Result<LegalActions, PokerGameError> {
+        let player = self.active.iter().find(|p| p.seat == seat);
+        if player.is_none() {
+            return Err(PokerGameError::PlayerSeatNotFound(seat));
+        }
+        let stack = player.expect("Expect to find the seat").cash_amount;
+        let to_call = self.to_call(seat);
+        let big_blind = self.blind_price * Blind::BIG as u32;
+        // Facing only a bring-in, the smallest raise completes it to a full bet
+        let completing = self.current_bet > 0 && self.street_raises == 0;
+        let raise_by = if completing { big_blind.saturating_sub(self.current_bet) } else { self.min_raise };
+
+        // Chips put in by the smallest and largest bet, or raise when facing one
+        let (smallest, largest) = match self.betting {
+            BettingStructure::NoLimit => (to_call + if self.current_bet == 0 { big_blind } else { raise_by }, stack),
+            // The largest raise first calls, then adds the pot as it stands after that call
+            BettingStructure::PotLimit => {
+                let smallest = to_call + if self.current_bet == 0 { big_blind } else { raise_by };
+                (smallest, smallest.max(2 * to_call + self.pot))
+            }
+            BettingStructure::FixedLimit { raise_cap } => {
+                let bet = BettingStructure::fixed_bet(self.stage, big_blind);
+                let size = to_call + if completing { bet.saturating_sub(self.current_bet) } else { bet };
+                if self.street_raises >= raise_cap { (0, 0) } else { (size, size) }
+            }
+        };
+        let range = if largest > 0 && stack > to_call { Some((min(smallest, stack), min(largest, stack))) } else { None };
+        let bet = if self.current_bet == 0 { range } else { None };
+        // Having acted since the last full raise, a player may only call a short all-in one
+        let raise = if self.current_bet > 0 && !self.acted.contains(&seat) { range } else { None };
+
+        Ok(LegalActions {
+            can_check: to_call == 0,
+            call: if to_call > 0 { Some(to_call) } else { None },
+            bet,
+            raise,
+        })
+    }
+
+    /// Whether the betting of the current street is over: one player is left, or everyone who
+    /// still has chips has acted since the last raise and matched it.
+    pub fn is_round_complete(&self) -> bool {
+        if self.active.len() < 2 {
+            return true;
+        }
+
+        let able: Vec<&PokerGamePlayer> = self.active.iter().filter(|p| p.cash_amount > 0).collect();
+        let matched = |p: &&PokerGamePlayer| self.to_call(p.seat) == 0;
+        if able.len() < 2 {
+            return able.iter().all(matched);
+        }
+
+        able.iter().all(|p| self.acted.contains(&p.seat) && matched(p))
+    }
+
+    /// Ends the hand: the last player standing takes the pot, otherwise the remaining streets
+    /// are dealt, players yet to draw standing pat, hands are shown and every side pot goes to
+    /// its best eligible hands.
+    pub fn settle(&mut self) -> Result<(), PokerGameError> {
+        if self.active.len() == 1 {
+            let seat = self.active[0].seat;
+            return self.award_pot(seat, self.pot);
+        }
+
+        while self.stage != HandStage::RIVER {
+            self.deal_next_stage()?;
+        }
+        self.show_down();
+
+        let board = self.board.clone();
+        let variant = self.variant;
+        let mut values: Vec<(u32, Option<HandValue>, Option<LowValue>)> = self.active.iter()
+            .map(|p| (p.seat, variant.high_hand(&p.hand, &board), variant.low_hand(&p.hand, &board)))
+            .collect();
+        // Odd chips go to the first winners after the dealer
+        if self.active[0].seat == self.dealer_seat {
+            values.rotate_left(1);
+        }
+
+        let mut levels: Vec<u32> = values.iter().map(|(seat, _, _)| self.contributions.get(seat).copied().unwrap_or(0)).collect();
+        levels.sort();
+        levels.dedup();
+
+        let mut previous = 0;
+        for (index, &level) in levels.iter().enumerate() {
+            let last = index == levels.len() - 1;
+            let amount: u32 = self.contributions.values()
+                .map(|&c| if last { c.saturating_sub(previous) } else { min(c, level).saturating_sub(previous) })
+                .sum();
+
+            let eligible: Vec<&(u32, Option<HandValue>, Option<LowValue>)> = values.iter()
+                .filter(|(seat, _, _)| self.contributions.get(seat).copied().unwrap_or(0) >= level)
+                .collect();
+            let winners: Vec<u32> = match eligible.iter().filter_map(|(_, value, _)| value.as_ref()).max_by(|a, b| variant.compare_high(a, b)).cloned() {
+                Some(best) => eligible.iter()
+                    .filter(|(_, value, _)| value.as_ref().is_some_and(|value| variant.compare_high(value, &best) == Ordering::Equal))
+                    .map(|(seat, _, _)| *seat)
+                    .collect(),
+                None => vec![],
+            };
+            let low_winners: Vec<u32> = match eligible.iter().filter_map(|(_, _, low)| low.as_ref()).min().cloned() {
+                Some(best_low) => eligible.iter()
+                    .filter(|(_, _, low)| low.as_ref() == Some(&best_low))
+                    .map(|(seat, _, _)| *seat)
+                    .collect(),
+                None => vec![],
+            };
+
+            // In hi-lo games the best low takes half of the pot, the high half keeping the odd
+            // chip; in lowball games it takes the whole pot
+            match (winners.is_empty(), low_winners.is_empty()) {
+                (false, false) => {
+                    self.split_pot(amount - amount / 2, &winners)?;
+                    self.split_pot(amount / 2, &low_winners)?;
+                }
+                (false, true) => self.split_pot(amount, &winners)?,
+                (true, _) => self.split_pot(amount, &low_winners)?,
+            }
+            previous = level;
+        }
+
+        Ok(())
+    }
+
+    /// Shares `amount` between `winners`, odd chips going to the first ones
+    fn split_pot(&mut self, amount: u32, winners: &[u32]) -> Result<(), PokerGameError> {
+        let share = amount / winners.len() as u32;
+        let odd_chips = amount % winners.len() as u32;
+        for (position, &seat) in winners.iter().enumerate() {
+            let extra = if (position as u32) < odd_chips { 1 } else { 0 };
+            if share + extra > 0 {
+                self.award_pot(seat, share + extra)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every player still in the hand shows their cards
+    pub fn show_down(&mut self) {
+        let shown: Vec<(u32, Vec<Card>)> = self.active.iter().map(|p| (p.seat, p.hand.clone())).collect();
+        for (seat, cards) in shown {
+            self.emit(HandEventKind::Showdown { seat, cards });
+        }
+    }
+
+    pub fn award_pot(&mut self, seat: u32, amount: u32) -> Result<(), PokerGameError> {
+        if amount > self.pot {
+            return Err(PokerGameError::AwardExceedsPot(amount, self.pot));
+        }
+
+        let player = self.active.iter_mut().find(|p| p.seat == seat);
+        if player.is_none() {
+            return Err(PokerGameError::PlayerSeatNotFound(seat));
+        }
+
+        player.expect("Expect to find the player").cash_amount += amount;
+        self.pot -= amount;
+        self.emit(HandEventKind::PotAwarded { seat, amount });
+
+        Ok(())
+    }
+
+
+    fn sort_players(players: &[PokerGamePlayer], dealer_seat: u32) -> Result<Vec<PokerGamePlayer>, PokerGameError> {
+        let mut sorted_players = players.to_vec();
+
+        sorted_players.sort_by_key(|p| p.seat);
 
         let player_index = sorted_players.iter().position(|p| p.seat == dealer_seat);
 
@@ -81,7 +681,7 @@ impl PokerHand {
             return Err(PokerGameError::PlayerSeatNotFound(dealer_seat))
         }
 
-        sorted_players.rotate_left(player_index.expect("Expect to have index") as usize);
+        sorted_players.rotate_left(player_index.expect("Expect to have index"));
 
         Ok(sorted_players)
     }
@@ -90,6 +690,13 @@ impl PokerHand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::poker::game_player::funded_players;
+    use crate::player::Player;
+    use crate::deck::Rank;
+
+    fn three_players() -> Vec<PokerGamePlayer> {
+        funded_players(&[(1, "P1"), (2, "P2"), (3, "P3")], 1000)
+    }
 
     #[test]
     fn test_sort_players() {
@@ -150,4 +757,473 @@ mod tests {
         assert_eq!(updated_sorted_players[0].seat, 5);
     }
 
+    #[test]
+    fn test_start_deals_two_cards_and_posts_blinds() {
+        let hand = PokerHand::start(StartHandParams {
+            blind_price: 10,
+            players: three_players(),
+            dealer_seat: 1,
+            seed: 7,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+        }).unwrap();
+
+        assert!(hand.active.iter().all(|p| p.hand.len() == 2));
+        assert_eq!(hand.small_blind_seat, 2);
+        assert_eq!(hand.big_blind_seat, 3);
+        assert_eq!(hand.speaker.seat, 1);
+        assert_eq!(hand.pot, 30);
+        assert_eq!(hand.active[1].cash_amount, 990);
+        assert_eq!(hand.active[2].cash_amount, 980);
+    }
+
+    #[test]
+    fn test_start_heads_up_dealer_is_small_blind() {
+        let hand = PokerHand::start(StartHandParams {
+            blind_price: 10,
+            players: three_players().into_iter().take(2).collect(),
+            dealer_seat: 2,
+            seed: 7,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+        }).unwrap();
+
+        assert_eq!(hand.small_blind_seat, 2);
+        assert_eq!(hand.big_blind_seat, 1);
+        assert_eq!(hand.speaker.seat, 2);
+    }
+
+    #[test]
+    fn test_start_same_seed_deals_same_cards() {
+        let start = || PokerHand::start(StartHandParams {
+            blind_price: 10,
+            players: three_players(),
+            dealer_seat: 1,
+            seed: 99,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+        }).unwrap();
+        let first = start();
+        let second = start();
+
+        assert_eq!(first.deck, second.deck);
+        for (a, b) in first.active.iter().zip(second.active.iter()) {
+            assert_eq!(a.hand, b.hand);
+        }
+    }
+
+    #[test]
+    fn test_start_requires_two_players() {
+        let result = PokerHand::start(StartHandParams {
+            blind_price: 10,
+            players: three_players().into_iter().take(1).collect(),
+            dealer_seat: 1,
+            seed: 1,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+        });
+
+        assert!(matches!(result, Err(PokerGameError::NotEnoughPlayers(1))));
+    }
+
+    fn started_hand() -> PokerHand {
+        PokerHand::start(StartHandParams {
+            blind_price: 10,
+            players: three_players(),
+            dealer_seat: 1,
+            seed: 5,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_start_emits_setup_events() {
+        let hand = started_hand();
+        let kinds: Vec<&HandEventKind> = hand.events.iter().map(|e| &e.kind).collect();
+
+        assert_eq!(kinds.len(), 6);
+        assert!(matches!(kinds[0], HandEventKind::HandStarted { seed: 5, dealer_seat: 1, players, .. } if players.len() == 3 && players[0].stack == 1000));
+        assert_eq!(*kinds[1], HandEventKind::BlindPosted { seat: 2, blind: Blind::SMALL, amount: 10 });
+        assert_eq!(*kinds[2], HandEventKind::BlindPosted { seat: 3, blind: Blind::BIG, amount: 20 });
+        assert_eq!(*kinds[3], HandEventKind::HoleCardsDealt { seat: 1, cards: hand.active[0].hand.clone() });
+    }
+
+    #[test]
+    fn test_act_moves_chips_and_speaker() {
+        let mut hand = started_hand();
+
+        hand.act(1, PokerAction::Raise(60)).unwrap();
+        assert_eq!(hand.pot, 90);
+        assert_eq!(hand.active[0].cash_amount, 940);
+        assert_eq!(hand.speaker.seat, 2);
+
+        hand.act(2, PokerAction::Fold).unwrap();
+        assert_eq!(hand.active.len(), 2);
+        assert_eq!(hand.folds[0].player.seat, 2);
+        assert_eq!(hand.folds[0].at, HandStage::INIT);
+        assert_eq!(hand.speaker.seat, 3);
+
+        assert!(matches!(hand.act(2, PokerAction::Check), Err(PokerGameError::PlayerSeatNotFound(2))));
+        assert!(matches!(hand.act(3, PokerAction::Call(5000)), Err(PokerGameError::NotEnoughChips(3, 5000))));
+
+        let last = hand.events.last().unwrap();
+        assert_eq!(last.kind, HandEventKind::ActionTaken { seat: 2, stage: HandStage::INIT, action: PokerAction::Fold });
+    }
+
+    #[test]
+    fn test_deal_next_stage_walks_the_board() {
+        let mut hand = started_hand();
+
+        assert_eq!(hand.deal_next_stage().unwrap(), hand.board);
+        assert_eq!(hand.stage, HandStage::FLOP);
+        assert_eq!(hand.speaker.seat, 2);
+        assert_eq!(hand.deal_next_stage().unwrap(), vec![hand.board[3]]);
+        assert_eq!(hand.deal_next_stage().unwrap(), vec![hand.board[4]]);
+        assert!(matches!(hand.deal_next_stage(), Err(PokerGameError::NoMoreStages)));
+
+        assert_eq!(hand.events.last().unwrap().kind, HandEventKind::BoardDealt { stage: HandStage::RIVER, cards: vec![hand.board[4]] });
+    }
+
+    #[test]
+    fn test_show_down_and_award_pot() {
+        let mut hand = started_hand();
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.show_down();
+
+        assert!(matches!(hand.award_pot(2, 1000), Err(PokerGameError::AwardExceedsPot(1000, 30))));
+        assert!(matches!(hand.award_pot(1, 10), Err(PokerGameError::PlayerSeatNotFound(1))));
+
+        hand.award_pot(3, 30).unwrap();
+        assert_eq!(hand.pot, 0);
+        assert_eq!(hand.active[1].cash_amount, 1010);
+
+        let kinds: Vec<&HandEventKind> = hand.events.iter().rev().take(3).map(|e| &e.kind).collect();
+        assert_eq!(*kinds[0], HandEventKind::PotAwarded { seat: 3, amount: 30 });
+        assert!(matches!(kinds[1], HandEventKind::Showdown { seat: 3, .. }));
+        assert!(matches!(kinds[2], HandEventKind::Showdown { seat: 2, .. }));
+    }
+
+    #[test]
+    fn test_subscribe_receives_past_and_future_events() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut hand = started_hand();
+
+        hand.subscribe(Box::new(move |event: &HandEvent| sender.send(event.clone()).unwrap()));
+        hand.act(1, PokerAction::Fold).unwrap();
+
+        let received: Vec<HandEvent> = receiver.try_iter().collect();
+        assert_eq!(received, hand.events);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut hand = PokerHand::start(StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 7, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap();
+        hand.act(1, PokerAction::Fold).unwrap();
+
+        let json = serde_json::to_string(&hand).unwrap();
+        let restored: PokerHand = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.deck, hand.deck);
+        assert_eq!(restored.pot, 30);
+        assert_eq!(restored.folds[0].player.seat, 1);
+        assert_eq!(restored.active[0].hand, hand.active[0].hand);
+        assert_eq!(restored.events, hand.events);
+
+        let error = PokerGameError::NotEnoughChips(2, 500);
+        assert_eq!(serde_json::to_string(&error).unwrap(), "{\"NotEnoughChips\":[2,500]}");
+        assert_eq!(serde_json::to_string(&PokerGameError::HandNotStarted).unwrap(), "\"HandNotStarted\"");
+    }
+
+    #[test]
+    fn test_legal_actions_and_round_completion() {
+        let mut hand = PokerHand::start(StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 7, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap();
+
+        let legal = hand.legal_actions(1).unwrap();
+        assert_eq!(legal, LegalActions { can_check: false, call: Some(20), bet: None, raise: Some((40, 1000)) });
+
+        hand.act(1, PokerAction::Call(20)).unwrap();
+        hand.act(2, PokerAction::Call(10)).unwrap();
+        assert!(!hand.is_round_complete(), "The big blind still has the option");
+
+        let option = hand.legal_actions(3).unwrap();
+        assert!(option.can_check);
+        assert_eq!(option.raise, Some((20, 980)));
+        hand.act(3, PokerAction::Check).unwrap();
+        assert!(hand.is_round_complete());
+
+        hand.deal_next_stage().unwrap();
+        assert_eq!(hand.speaker.seat, 2);
+        assert_eq!(hand.legal_actions(2).unwrap().bet, Some((20, 980)));
+        hand.act(2, PokerAction::Bet(50)).unwrap();
+        hand.act(3, PokerAction::Raise(150)).unwrap();
+        assert_eq!(hand.min_raise, 100);
+        assert_eq!(hand.to_call(1), 150);
+        assert_eq!(hand.legal_actions(1).unwrap().raise, Some((250, 980)));
+    }
+
+    fn hand_with(betting: BettingStructure) -> PokerHand {
+        PokerHand::start(StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 7, betting, variant: GameVariant::Holdem, ante: 0 }).unwrap()
+    }
+
+    #[test]
+    fn test_pot_limit_raises_up_to_the_pot_after_calling() {
+        let mut hand = hand_with(BettingStructure::PotLimit);
+
+        // Calling 20 makes the pot 50, so the largest raise puts in 70
+        assert_eq!(hand.legal_actions(1).unwrap().raise, Some((40, 70)));
+        hand.act(1, PokerAction::Raise(70)).unwrap();
+        assert_eq!(hand.legal_actions(2).unwrap().raise, Some((110, 220)));
+        hand.act(2, PokerAction::Call(60)).unwrap();
+        hand.act(3, PokerAction::Call(50)).unwrap();
+
+        hand.deal_next_stage().unwrap();
+        assert_eq!(hand.legal_actions(2).unwrap().bet, Some((20, 210)));
+    }
+
+    #[test]
+    fn test_short_all_in_raise_does_not_reopen_betting() {
+        let mut players = three_players();
+        players[2].cash_amount = 170;
+        let mut hand = PokerHand::start(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 7, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap();
+        hand.act(1, PokerAction::Call(20)).unwrap();
+        hand.act(2, PokerAction::Call(10)).unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.deal_next_stage().unwrap();
+
+        // All in for 150 over a bet of 100 adds less than a full raise
+        hand.act(2, PokerAction::Bet(100)).unwrap();
+        hand.act(3, PokerAction::Raise(150)).unwrap();
+        assert_eq!(hand.min_raise, 100);
+
+        // Yet to act, the next player may still raise, by a full raise at least
+        assert_eq!(hand.legal_actions(1).unwrap().raise, Some((250, 980)));
+        hand.act(1, PokerAction::Call(150)).unwrap();
+
+        // The bettor only calls or folds
+        let legal = hand.legal_actions(2).unwrap();
+        assert_eq!(legal.call, Some(50));
+        assert_eq!(legal.raise, None);
+        assert!(!hand.is_round_complete());
+        hand.act(2, PokerAction::Call(50)).unwrap();
+        assert!(hand.is_round_complete());
+    }
+
+    #[test]
+    fn test_fixed_limit_bet_sizes_and_raise_cap() {
+        let mut hand = hand_with(BettingStructure::FixedLimit { raise_cap: 4 });
+
+        assert_eq!(hand.legal_actions(1).unwrap().raise, Some((40, 40)));
+        hand.act(1, PokerAction::Raise(40)).unwrap();
+        assert_eq!(hand.legal_actions(2).unwrap().raise, Some((50, 50)));
+        hand.act(2, PokerAction::Raise(50)).unwrap();
+        hand.act(3, PokerAction::Raise(60)).unwrap();
+
+        let capped = hand.legal_actions(1).unwrap();
+        assert_eq!((capped.call, capped.raise), (Some(40), None));
+        hand.act(1, PokerAction::Call(40)).unwrap();
+        hand.act(2, PokerAction::Call(20)).unwrap();
+
+        hand.deal_next_stage().unwrap();
+        assert_eq!(hand.legal_actions(2).unwrap().bet, Some((20, 20)), "a small bet on the flop");
+        for seat in [2, 3, 1] {
+            hand.act(seat, PokerAction::Check).unwrap();
+        }
+        hand.deal_next_stage().unwrap();
+        assert_eq!(hand.legal_actions(2).unwrap().bet, Some((40, 40)), "a big bet on the turn");
+    }
+
+    #[test]
+    fn test_settle_pays_side_pots() {
+        let mut players = three_players();
+        for (player, stack) in players.iter_mut().zip([100, 300, 300]) {
+            player.cash_amount = stack;
+        }
+        let order = "As Ks Qs 8c Ah Kh Qh 8d 8h 2c 7d 9c 8s 4s Jd 3h";
+        let deck = Deck::from_cards(order.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect());
+        let mut hand = PokerHand::start_with_deck(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 0, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }, deck).unwrap();
+
+        hand.act(1, PokerAction::Raise(100)).unwrap();
+        hand.act(2, PokerAction::Raise(290)).unwrap();
+        hand.act(3, PokerAction::Call(280)).unwrap();
+        assert!(hand.is_round_complete());
+
+        hand.settle().unwrap();
+
+        assert_eq!(hand.stage, HandStage::RIVER);
+        assert_eq!(hand.pot, 0);
+        let stacks: Vec<u32> = hand.active.iter().map(|p| p.cash_amount).collect();
+        assert_eq!(stacks, vec![300, 400, 0]);
+    }
+
+    #[test]
+    fn test_omaha_deals_four_cards() {
+        let hand = PokerHand::start(StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 7, betting: BettingStructure::PotLimit, variant: GameVariant::Omaha, ante: 0 }).unwrap();
+
+        assert!(hand.active.iter().all(|p| p.hand.len() == 4));
+        let mut dealt: Vec<Card> = hand.active.iter().flat_map(|p| p.hand.clone()).collect();
+        dealt.sort();
+        dealt.dedup();
+        assert_eq!(dealt.len(), 12);
+        // Twelve hole cards and four burns out of the deck, the board still in it
+        assert_eq!(hand.deck.len(), 52 - 12 - 4);
+    }
+
+    #[test]
+    fn test_hi_lo_settle_splits_the_pot() {
+        let order = "Kh Ac 9c 4s Kc 2d 9d 4d Qs Js Tc 6s Qd Jd Th 6d 7s 3h 5c 8d 7d Ks 2s Kd";
+        let deck = Deck::from_cards(order.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect());
+        let params = StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 0, betting: BettingStructure::PotLimit, variant: GameVariant::OmahaHiLo, ante: 0 };
+        let mut hand = PokerHand::start_with_deck(params, deck).unwrap();
+
+        hand.act(1, PokerAction::Call(20)).unwrap();
+        hand.act(2, PokerAction::Call(10)).unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.settle().unwrap();
+
+        // Four kings take the high half, A-2-3-5-8 the low half
+        let stacks: Vec<u32> = hand.active.iter().map(|p| p.cash_amount).collect();
+        assert_eq!(stacks, vec![1010, 1010, 980]);
+    }
+
+    #[test]
+    fn test_short_deck_with_antes_instead_of_blinds() {
+        let params = StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 5, betting: BettingStructure::NoLimit, variant: GameVariant::ShortDeck, ante: 5 };
+        let mut hand = PokerHand::start(params).unwrap();
+
+        let antes: Vec<&HandEventKind> = hand.events[1..4].iter().map(|e| &e.kind).collect();
+        assert_eq!(antes, vec![
+            &HandEventKind::AntePosted { seat: 1, amount: 5 },
+            &HandEventKind::AntePosted { seat: 2, amount: 5 },
+            &HandEventKind::AntePosted { seat: 3, amount: 5 },
+        ]);
+        assert_eq!(hand.pot, 15);
+        assert!(hand.active.iter().all(|p| p.blind == Blind::NONE && p.cash_amount == 995));
+        assert!(hand.active.iter().flat_map(|p| p.hand.iter()).all(|c| c.rank >= Rank::Six));
+
+        // Nobody owes anything pre-flop and the player after the dealer opens
+        assert_eq!(hand.speaker.seat, 2);
+        let legal = hand.legal_actions(2).unwrap();
+        assert!(legal.can_check);
+        assert_eq!(legal.bet, Some((20, 995)));
+
+        for seat in [2, 3, 1] {
+            hand.act(seat, PokerAction::Check).unwrap();
+        }
+        assert!(hand.is_round_complete());
+        hand.settle().unwrap();
+        assert_eq!(hand.pot, 0);
+        assert_eq!(hand.active.iter().map(|p| p.cash_amount).sum::<u32>(), 3000);
+        assert!(hand.board.len() == 5 && hand.board.iter().all(|c| c.rank >= Rank::Six));
+    }
+
+    fn stud_hand(variant: GameVariant, order: &str) -> PokerHand {
+        let deck = Deck::from_cards(order.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect());
+        let params = StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 0, betting: BettingStructure::FixedLimit { raise_cap: 4 }, variant, ante: 0 };
+        PokerHand::start_with_deck(params, deck).unwrap()
+    }
+
+    #[test]
+    fn test_stud_bring_in_completion_and_first_to_act() {
+        let mut hand = stud_hand(GameVariant::Stud, "As Ks Qs Ah Kh Qh 9c 2d 2c 9d 3c Ac");
+
+        // The deuce of clubs brings it in, without blinds, and the next player speaks first
+        assert!(hand.active.iter().all(|p| p.hand.len() == 3 && p.up_cards.len() == 1 && p.blind == Blind::NONE));
+        assert_eq!(hand.events[2].kind, HandEventKind::UpCardsDealt { seat: 1, cards: vec![Card::from_code("9c").unwrap()] });
+        assert_eq!(hand.events.last().unwrap().kind, HandEventKind::BringInPosted { seat: 3, amount: 10 });
+        assert_eq!(hand.speaker.seat, 1);
+        let legal = hand.legal_actions(1).unwrap();
+        assert_eq!((legal.call, legal.raise), (Some(10), Some((20, 20))));
+
+        hand.act(1, PokerAction::Raise(20)).unwrap();
+        assert_eq!(hand.legal_actions(2).unwrap().raise, Some((40, 40)));
+        hand.act(2, PokerAction::Call(20)).unwrap();
+        hand.act(3, PokerAction::Call(10)).unwrap();
+        assert!(hand.is_round_complete());
+
+        // Fourth street: the pair of nines showing acts first
+        assert_eq!(hand.deal_next_stage().unwrap(), vec![]);
+        assert_eq!(hand.stage, HandStage::FLOP);
+        assert!(hand.board.is_empty());
+        assert_eq!(hand.speaker.seat, 1);
+        assert_eq!(hand.active[0].up_cards, vec![Card::from_code("9c").unwrap(), Card::from_code("9d").unwrap()]);
+    }
+
+    #[test]
+    fn test_razz_settle_pays_the_best_low() {
+        let order = "Ac Kc Qc 2c Kd Qd 3c Ks 5d 4c Kh 5h 5c 9d 7d 8c 9h 7h 8d Th 6c";
+        let mut hand = stud_hand(GameVariant::Razz, order);
+
+        // The king is the worst razz card and brings it in
+        assert_eq!(hand.events.last().unwrap().kind, HandEventKind::BringInPosted { seat: 2, amount: 10 });
+        assert_eq!(hand.speaker.seat, 3);
+        hand.act(3, PokerAction::Call(10)).unwrap();
+        hand.act(1, PokerAction::Call(10)).unwrap();
+        assert!(hand.is_round_complete());
+
+        // 4-3 showing is the best low, and the wheel wins the whole pot
+        hand.deal_next_stage().unwrap();
+        assert_eq!(hand.speaker.seat, 1);
+        hand.settle().unwrap();
+        assert!(hand.active.iter().all(|p| p.hand.len() == 7 && p.up_cards.len() == 4));
+        let stacks: Vec<u32> = hand.active.iter().map(|p| p.cash_amount).collect();
+        assert_eq!(stacks, vec![1020, 990, 990]);
+    }
+
+    #[test]
+    fn test_stud_deals_at_most_seven_players() {
+        let players = funded_players(&[(1, "P1"), (2, "P2"), (3, "P3"), (4, "P4"), (5, "P5"), (6, "P6"), (7, "P7"), (8, "P8")], 1000);
+        let result = PokerHand::start(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 1, betting: BettingStructure::FixedLimit { raise_cap: 4 }, variant: GameVariant::Stud, ante: 0 });
+
+        assert!(matches!(result, Err(PokerGameError::TooManyPlayers(8, 7))));
+    }
+
+    #[test]
+    fn test_draw_replaces_discards_and_reshuffles() {
+        let order = "2c 2d 8s 3c 3d 8h 4c 4h 9s 5d 5h Ts 7d Kc Js 6s";
+        let deck = Deck::from_cards(order.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect());
+        let params = StartHandParams { blind_price: 10, players: three_players(), dealer_seat: 1, seed: 0, betting: BettingStructure::FixedLimit { raise_cap: 4 }, variant: GameVariant::TripleDraw, ante: 0 };
+        let mut hand = PokerHand::start_with_deck(params, deck).unwrap();
+
+        assert!(hand.active.iter().all(|p| p.hand.len() == 5));
+        assert_eq!(hand.next_to_draw(), None);
+        hand.act(1, PokerAction::Call(20)).unwrap();
+        hand.act(2, PokerAction::Call(10)).unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+
+        // The first draw: nobody bets before everyone drew, in turn from the small blind
+        hand.deal_next_stage().unwrap();
+        assert_eq!(hand.next_to_draw(), Some(2));
+        assert!(matches!(hand.act(2, PokerAction::Check), Err(PokerGameError::DrawPending(2))));
+        assert!(matches!(hand.draw(3, &[]), Err(PokerGameError::NotDrawing(3))));
+        let ace = Card::from_code("Ac").unwrap();
+        assert!(matches!(hand.draw(2, &[ace]), Err(PokerGameError::CardNotInHand(card)) if card == ace));
+
+        let king = Card::from_code("Kc").unwrap();
+        assert_eq!(hand.draw(2, &[king]).unwrap(), vec![Card::from_code("6s").unwrap()]);
+        assert!(hand.deck.is_empty());
+
+        // The deck ran out, so the king discarded before comes back
+        let jack = Card::from_code("Js").unwrap();
+        assert_eq!(hand.draw(3, &[jack]).unwrap(), vec![king]);
+        assert_eq!(hand.discards, vec![jack]);
+        assert_eq!(hand.events[hand.events.len() - 2].kind, HandEventKind::DrawTaken { seat: 3, stage: HandStage::FLOP, count: 1 });
+        assert_eq!(hand.events.last().unwrap().kind, HandEventKind::CardsDrawn { seat: 3, discarded: vec![jack], drawn: vec![king] });
+
+        hand.draw(1, &[]).unwrap();
+        assert_eq!(hand.next_to_draw(), None);
+        assert_eq!(hand.speaker.seat, 2);
+
+        // Six-high is a straight in deuce-to-seven, so seven-five takes the pot
+        hand.settle().unwrap();
+        let stacks: Vec<u32> = hand.active.iter().map(|p| p.cash_amount).collect();
+        assert_eq!(stacks, vec![1040, 980, 980]);
+    }
 }
+
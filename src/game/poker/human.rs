@@ -0,0 +1,181 @@
+use std::cell::RefCell;
+use std::io;
+use std::io::{BufRead, Stdin, StdinLock, Stdout, Write};
+use std::rc::Rc;
+use crate::deck::Card;
+use crate::game::poker::action::{parse_discards, PokerAction};
+use crate::game::poker::agent::Agent;
+use crate::game::poker::table::TableState;
+use crate::game::poker::view::PlayerView;
+
+/// A person at the keyboard. Shows the table, then asks for an action until it is legal.
+/// When `table` is fed by the hand's events the person sees the action history as well;
+/// otherwise they get the same prompt an LLM would.
+pub struct HumanAgent<R: BufRead, W: Write> {
+    pub name: String,
+    pub table: Option<Rc<RefCell<TableState>>>,
+    input: R,
+    output: W,
+}
+
+impl HumanAgent<StdinLock<'static>, Stdout> {
+    pub fn stdin(name: &str, table: Option<Rc<RefCell<TableState>>>) -> Self {
+        HumanAgent::new(name, table, Stdin::lock(&io::stdin()), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> HumanAgent<R, W> {
+    pub fn new(name: &str, table: Option<Rc<RefCell<TableState>>>, input: R, output: W) -> Self {
+        HumanAgent { name: name.to_string(), table, input, output }
+    }
+
+    fn show(&mut self, view: &PlayerView) -> io::Result<()> {
+        match &self.table {
+            Some(table) => write!(self.output, "{}", table.borrow().render(Some(view.seat), false)),
+            None => write!(self.output, "{}", view.prompt()),
+        }
+    }
+
+    /// The first legal answer, or `None` once the input runs out
+    fn ask(&mut self, view: &PlayerView) -> io::Result<Option<PokerAction>> {
+        self.show(view)?;
+
+        loop {
+            write!(self.output, "{}, your action ({}): ", self.name, view.legal_actions)?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            let action = match PokerAction::parse(&line) {
+                Some(action) => action,
+                None => {
+                    writeln!(self.output, "Cannot read `{}`, type e.g. `call` or `raise 40`", line.trim())?;
+                    continue;
+                }
+            };
+            match view.legal_actions.check(action) {
+                Ok(action) => return Ok(Some(action)),
+                Err(error) => writeln!(self.output, "`{}` is not allowed: {}", line.trim(), error)?,
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> HumanAgent<R, W> {
+    /// The first discard of cards held, or `None` once the input runs out
+    fn ask_discards(&mut self, view: &PlayerView) -> io::Result<Option<Vec<Card>>> {
+        self.show(view)?;
+        let example = view.hole_cards.first().map(|c| c.code()).unwrap_or_default();
+
+        loop {
+            write!(self.output, "{}, cards to discard (`discard {}` or `stand pat`): ", self.name, example)?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            match parse_discards(&line) {
+                Some(cards) if cards.iter().all(|card| view.hole_cards.contains(card)) => return Ok(Some(cards)),
+                Some(_) => writeln!(self.output, "`{}` names a card you do not hold", line.trim())?,
+                None => writeln!(self.output, "Cannot read `{}`, type e.g. `discard {}` or `stand pat`", line.trim(), example)?,
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Agent for HumanAgent<R, W> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Checks or folds when the input is closed or broken
+    fn decide(&mut self, view: &PlayerView) -> PokerAction {
+        match self.ask(view) {
+            Ok(Some(action)) => action,
+            _ if view.legal_actions.can_check => PokerAction::Check,
+            _ => PokerAction::Fold,
+        }
+    }
+
+    /// Stands pat when the input is closed or broken
+    fn discard(&mut self, view: &PlayerView) -> Vec<Card> {
+        self.ask_discards(view).ok().flatten().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::event::HandEvent;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+    fn hand() -> PokerHand {
+        let players = funded_players(&[(1, "P1"), (2, "P2")], 100);
+
+        PokerHand::start(StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 11, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap()
+    }
+
+    fn view(hand: &PokerHand) -> PlayerView {
+        PlayerView::create(hand, hand.speaker.seat, hand.stage, None).unwrap()
+    }
+
+    #[test]
+    fn test_asks_again_until_the_action_is_legal() {
+        let hand = hand();
+        let mut output = vec![];
+        let mut agent = HumanAgent::new("P1", None, "shove\ncheck\nraise 500\nraise 30\n".as_bytes(), &mut output);
+
+        assert_eq!(agent.decide(&view(&hand)), PokerAction::Raise(30));
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Your cards:"));
+        assert!(output.contains("Cannot read `shove`"));
+        assert!(output.contains("`check` is not allowed: you cannot check now, legal actions are: fold, call 5, raise 15-95"));
+        assert!(output.contains("`raise 500` is not allowed: you cannot raise 500 now"));
+        assert_eq!(output.matches("P1, your action (fold, call 5, raise 15-95): ").count(), 4);
+    }
+
+    #[test]
+    fn test_bare_call_pays_what_is_owed() {
+        let hand = hand();
+        let mut agent = HumanAgent::new("P1", None, "call\n".as_bytes(), vec![]);
+
+        assert_eq!(agent.decide(&view(&hand)), PokerAction::Call(5));
+    }
+
+    #[test]
+    fn test_closed_input_folds_or_checks() {
+        let mut hand = hand();
+        let mut agent = HumanAgent::new("P1", None, "".as_bytes(), vec![]);
+        assert_eq!(agent.decide(&view(&hand)), PokerAction::Fold);
+
+        hand.act(1, PokerAction::Call(5)).unwrap();
+        assert_eq!(agent.decide(&view(&hand)), PokerAction::Check);
+    }
+
+    #[test]
+    fn test_renders_shared_table_with_history() {
+        let mut hand = hand();
+        let table = Rc::new(RefCell::new(TableState::new()));
+        let feed = table.clone();
+        hand.subscribe(Box::new(move |event: &HandEvent| feed.borrow_mut().apply(event)));
+        hand.act(1, PokerAction::Call(5)).unwrap();
+
+        let mut output = vec![];
+        let mut agent = HumanAgent::new("P2", Some(table), "check\n".as_bytes(), &mut output);
+        assert_eq!(agent.decide(&view(&hand)), PokerAction::Check);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("P1 calls 5"));
+        assert!(output.contains(&format!("🧠 P2: {} {}", hand.active[1].hand[0], hand.active[1].hand[1])));
+        assert!(!output.contains(&hand.active[0].hand[0].to_string()));
+    }
+}
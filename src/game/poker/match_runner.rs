@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+use crate::game::poker::blind::Blind;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::hand::StartHandParams;
+use crate::game::poker::json;
+use crate::game::poker::PokerGame;
+
+/// z-score of the two-sided 95% confidence interval
+const Z_95: f64 = 1.959964;
+
+pub struct MatchRunner {
+    pub hands: u32,
+    pub auto_rebuy: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandResult {
+    pub hand_number: u32,
+    pub seed: u64,
+    pub winnings: BTreeMap<usize, i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentStats {
+    pub player_id: usize,
+    pub name: String,
+    pub hands: u32,
+    pub net_chips: i64,
+    pub bb_per_100: f64,
+    pub std_dev_per_100: f64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+/// Paired comparison of two agents over the hands they both played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignificanceTest {
+    pub player_a: usize,
+    pub player_b: usize,
+    pub hands: u32,
+    pub difference_bb_per_100: f64,
+    pub z_score: f64,
+    pub p_value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchReport {
+    pub big_blind: u32,
+    pub names: BTreeMap<usize, String>,
+    pub results: Vec<HandResult>,
+}
+
+impl MatchRunner {
+    /// Plays up to `hands` more hands at `game`, moving the button one seat per hand.
+    ///
+    /// `play_hand` receives the dealt-in players with their current stacks and returns the net
+    /// chips won per player id. The match ends early as soon as a player busts, unless
+    /// `auto_rebuy` is set and their buy-in limit lets them buy back in. An error from
+    /// `play_hand` stops the match and is returned as it is.
+    pub fn run<F, E>(&self, game: &mut PokerGame, mut play_hand: F) -> Result<MatchReport, E>
+    where
+        F: FnMut(StartHandParams) -> Result<BTreeMap<usize, i64>, E>,
+        E: From<PokerGameError>,
+    {
+        let mut results = Vec::with_capacity(self.hands as usize);
+
+        for _ in 0..self.hands {
+            if self.auto_rebuy {
+                self.rebuy_busted(game)?;
+            }
+            if game.players.iter().any(|p| p.cash_amount == 0) {
+                break;
+            }
+
+            let hand_number = game.hand_number;
+            let params = match game.next_hand() {
+                Some(params) => params,
+                None => break,
+            };
+            let seed = params.seed;
+            let winnings = play_hand(params)?;
+
+            game.apply_winnings(&winnings);
+
+            results.push(HandResult { hand_number, seed, winnings });
+        }
+
+        Ok(MatchReport::new(game, results))
+    }
+
+    fn rebuy_busted(&self, game: &mut PokerGame) -> Result<(), PokerGameError> {
+        let buy_in = game.default_buy_in_value;
+        let limit = game.buy_in_limit;
+
+        for player in game.players.iter_mut() {
+            if player.cash_amount == 0 && player.buy_ins < limit {
+                player.fund(buy_in)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MatchReport {
+    /// Report on `results` played at `game`, naming its players
+    pub fn new(game: &PokerGame, results: Vec<HandResult>) -> MatchReport {
+        MatchReport {
+            big_blind: game.blind_price * Blind::BIG as u32,
+            names: game.players.iter().map(|p| (p.player.id, p.player.name.clone())).collect(),
+            results,
+        }
+    }
+
+    /// Per-hand results of one player in big blinds, for the hands they were dealt into.
+    fn results_in_big_blinds(&self, player_id: usize) -> Vec<f64> {
+        self.results.iter()
+            .filter_map(|r| r.winnings.get(&player_id))
+            .map(|&amount| amount as f64 / self.big_blind as f64)
+            .collect()
+    }
+
+    pub fn agent_stats(&self) -> Vec<AgentStats> {
+        self.names.iter().map(|(&player_id, name)| {
+            let results = self.results_in_big_blinds(player_id);
+            let n = results.len() as f64;
+            let mean = mean(&results);
+            let std_dev = std_dev(&results);
+            let margin = if results.is_empty() { 0.0 } else { Z_95 * std_dev / n.sqrt() };
+
+            AgentStats {
+                player_id,
+                name: name.clone(),
+                hands: results.len() as u32,
+                net_chips: self.results.iter().filter_map(|r| r.winnings.get(&player_id)).sum(),
+                bb_per_100: 100.0 * mean,
+                std_dev_per_100: 10.0 * std_dev,
+                ci_low: 100.0 * (mean - margin),
+                ci_high: 100.0 * (mean + margin),
+            }
+        }).collect()
+    }
+
+    /// Paired z-test on the per-hand difference between two agents, using the normal
+    /// approximation, so it is only meaningful over a few dozen hands or more.
+    pub fn compare(&self, player_a: usize, player_b: usize) -> Option<SignificanceTest> {
+        let differences: Vec<f64> = self.results.iter()
+            .filter_map(|r| Some((*r.winnings.get(&player_a)? - *r.winnings.get(&player_b)?) as f64))
+            .map(|d| d / self.big_blind as f64)
+            .collect();
+
+        if differences.len() < 2 {
+            return None;
+        }
+
+        let mean = mean(&differences);
+        let standard_error = std_dev(&differences) / (differences.len() as f64).sqrt();
+        let z_score = if standard_error == 0.0 { 0.0 } else { mean / standard_error };
+
+        Some(SignificanceTest {
+            player_a,
+            player_b,
+            hands: differences.len() as u32,
+            difference_bb_per_100: 100.0 * mean,
+            z_score,
+            p_value: 2.0 * (1.0 - normal_cdf(z_score.abs())),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let agents: Vec<String> = self.agent_stats().iter().map(|a| format!(
+            "{{\"player_id\":{},\"name\":\"{}\",\"hands\":{},\"net_chips\":{},\"bb_per_100\":{:.4},\"std_dev_per_100\":{:.4},\"ci_95\":[{:.4},{:.4}]}}",
+            a.player_id, json::escape(&a.name), a.hands, a.net_chips, a.bb_per_100, a.std_dev_per_100, a.ci_low, a.ci_high
+        )).collect();
+
+        let ids: Vec<usize> = self.names.keys().copied().collect();
+        let mut comparisons = vec![];
+        for (index, &a) in ids.iter().enumerate() {
+            for &b in &ids[index + 1..] {
+                if let Some(test) = self.compare(a, b) {
+                    comparisons.push(format!(
+                        "{{\"player_a\":{},\"player_b\":{},\"hands\":{},\"difference_bb_per_100\":{:.4},\"z_score\":{:.4},\"p_value\":{:.6}}}",
+                        test.player_a, test.player_b, test.hands, test.difference_bb_per_100, test.z_score, test.p_value
+                    ));
+                }
+            }
+        }
+
+        format!(
+            "{{\"hands_played\":{},\"big_blind\":{},\"agents\":[{}],\"comparisons\":[{}]}}",
+            self.results.len(), self.big_blind, agents.join(","), comparisons.join(",")
+        )
+    }
+}
+
+impl fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} hands, big blind {}", self.results.len(), self.big_blind)?;
+        writeln!(f, "{:<20} {:>7} {:>10} {:>10} {:>10} {:>21}", "Player", "Hands", "Net", "bb/100", "SD/100", "95% CI")?;
+
+        for agent in self.agent_stats() {
+            writeln!(
+                f, "{:<20} {:>7} {:>10} {:>10.2} {:>10.2} {:>21}",
+                agent.name, agent.hands, agent.net_chips, agent.bb_per_100, agent.std_dev_per_100,
+                format!("[{:.2}, {:.2}]", agent.ci_low, agent.ci_high)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() { return 0.0; }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 { return 0.0; }
+    let mean = mean(values);
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+/// Standard normal CDF via the Abramowitz and Stegun 7.1.26 approximation of erf
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+
+    if z >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::CreatePokerGame;
+    use crate::player::Player;
+
+    fn heads_up_game(buy_in_limit: u32) -> PokerGame {
+        let mut game = PokerGame::new(CreatePokerGame {
+            seats: 2,
+            default_buy_in_value: 100,
+            buy_in_limit,
+            blind_price: 5,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false,
+        });
+        game.add_player(Player::create(1, "Alice", "a.png"), 1).unwrap();
+        game.add_player(Player::create(2, "Bob", "b.png"), 2).unwrap();
+        game
+    }
+
+    #[test]
+    fn test_run_plays_requested_hands_and_moves_button() {
+        let mut game = heads_up_game(1);
+        let runner = MatchRunner { hands: 4, auto_rebuy: false };
+        let mut dealers = vec![];
+
+        let report = runner.run::<_, PokerGameError>(&mut game, |params| {
+            dealers.push(params.dealer_seat);
+            Ok(BTreeMap::from([(1, 10), (2, -10)]))
+        }).unwrap();
+
+        assert_eq!(report.results.len(), 4);
+        assert_eq!(dealers, vec![1, 2, 1, 2]);
+        assert_eq!(game.players[0].cash_amount, 140);
+        assert_eq!(game.players[1].cash_amount, 60);
+    }
+
+    #[test]
+    fn test_run_stops_when_a_player_busts() {
+        let mut game = heads_up_game(1);
+        let runner = MatchRunner { hands: 10, auto_rebuy: true };
+
+        let report = runner.run::<_, PokerGameError>(&mut game, |_| Ok(BTreeMap::from([(1, 100), (2, -100)]))).unwrap();
+
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(game.players[1].cash_amount, 0);
+    }
+
+    #[test]
+    fn test_multiway_run_stops_at_the_first_bust() {
+        let mut game = heads_up_game(1);
+        game.seats = 3;
+        game.add_player(Player::create(3, "Carol", "c.png"), 3).unwrap();
+        let runner = MatchRunner { hands: 10, auto_rebuy: false };
+
+        let report = runner.run::<_, PokerGameError>(&mut game, |_| Ok(BTreeMap::from([(1, 100), (2, -100), (3, 0)]))).unwrap();
+
+        // Alice and Carol could play on, but Bob's bust ends the match
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(game.players.iter().map(|p| p.cash_amount).collect::<Vec<_>>(), vec![200, 0, 100]);
+    }
+
+    #[test]
+    fn test_run_auto_rebuys_within_limit() {
+        let mut game = heads_up_game(3);
+        let runner = MatchRunner { hands: 10, auto_rebuy: true };
+
+        let report = runner.run::<_, PokerGameError>(&mut game, |_| Ok(BTreeMap::from([(1, 100), (2, -100)]))).unwrap();
+
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(game.players[1].buy_ins, 3);
+    }
+
+    #[test]
+    fn test_hand_seeds_are_deterministic_and_distinct() {
+        let runner = MatchRunner { hands: 3, auto_rebuy: false };
+        let seeds = |game: &mut PokerGame| runner.run::<_, PokerGameError>(game, |_| Ok(BTreeMap::new())).unwrap()
+            .results.iter().map(|r| r.seed).collect::<Vec<_>>();
+        let seeded_game = || {
+            let mut game = heads_up_game(1);
+            game.seed = 9;
+            game
+        };
+
+        let first = seeds(&mut seeded_game());
+        assert_eq!(first, seeds(&mut seeded_game()));
+        assert_ne!(first[0], first[1]);
+    }
+
+    #[test]
+    fn test_agent_stats_in_big_blinds_per_100() {
+        let report = MatchReport {
+            big_blind: 10,
+            names: BTreeMap::from([(1, String::from("Alice")), (2, String::from("Bob"))]),
+            results: [20, -10, 20, -10].iter().enumerate().map(|(i, &amount)| HandResult {
+                hand_number: i as u32,
+                seed: 0,
+                winnings: BTreeMap::from([(1, amount), (2, -amount)]),
+            }).collect(),
+        };
+
+        let stats = report.agent_stats();
+        assert_eq!(stats[0].net_chips, 20);
+        assert!((stats[0].bb_per_100 - 50.0).abs() < 1e-9);
+        assert!((stats[1].bb_per_100 + 50.0).abs() < 1e-9);
+        assert!((stats[0].std_dev_per_100 - 10.0 * 3.0f64.sqrt()).abs() < 1e-9);
+        assert!(stats[0].ci_low < 50.0 && stats[0].ci_high > 50.0);
+    }
+
+    #[test]
+    fn test_compare_detects_consistent_winner() {
+        let report = MatchReport {
+            big_blind: 10,
+            names: BTreeMap::from([(1, String::from("Alice")), (2, String::from("Bob"))]),
+            results: (0..200).map(|i| HandResult {
+                hand_number: i,
+                seed: 0,
+                winnings: if i % 4 == 0 {
+                    BTreeMap::from([(1, -10), (2, 10)])
+                } else {
+                    BTreeMap::from([(1, 10), (2, -10)])
+                },
+            }).collect(),
+        };
+
+        let test = report.compare(1, 2).unwrap();
+        assert_eq!(test.hands, 200);
+        assert!(test.difference_bb_per_100 > 0.0);
+        assert!(test.p_value < 0.001);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-6);
+        assert!((normal_cdf(Z_95) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(-Z_95) - 0.025).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_to_json_escapes_names() {
+        let report = MatchReport {
+            big_blind: 10,
+            names: BTreeMap::from([(1, String::from("Al\"ice"))]),
+            results: vec![],
+        };
+
+        let json = report.to_json();
+        assert!(json.contains("\"name\":\"Al\\\"ice\""));
+        assert!(json.starts_with("{\"hands_played\":0,\"big_blind\":10"));
+    }
+}
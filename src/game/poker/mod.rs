@@ -1,11 +1,46 @@
-mod game_player;
-mod blind;
-mod error;
-mod hand;
-
-use crate::game::Game;
+pub mod game_player;
+pub mod blind;
+pub mod error;
+pub mod hand;
+pub mod action;
+pub mod betting;
+pub mod variant;
+pub mod event;
+mod json;
+pub mod pokerstars;
+pub mod phh;
+pub mod agent;
+pub mod bots;
+pub mod replay;
+pub mod evaluator;
+pub mod counterfactual;
+pub mod duplicate;
+pub mod dealer;
+pub mod match_runner;
+pub mod tournament;
+pub mod rating;
+pub mod stats;
+pub mod view;
+pub mod table;
+pub mod human;
+#[cfg(feature = "tui")]
+pub mod spectator;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub mod api;
+mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod storage;
+
+use std::collections::BTreeMap;
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::GameVariant;
 use crate::game::poker::error::PokerGameError;
 use crate::game::poker::game_player::PokerGamePlayer;
+use crate::game::poker::hand::{HandStage, PokerHand, StartHandParams};
+use crate::game::poker::stats::{HandRecord, StatsTracker};
+use crate::game::poker::view::PlayerView;
 use crate::player::Player;
 
 pub struct PokerGame {
@@ -14,13 +49,34 @@ pub struct PokerGame {
     pub default_buy_in_value: u32,
     pub buy_in_limit: u32,
     pub blind_price: u32,
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    /// Chips every player antes instead of posting blinds, none when zero
+    pub ante: u32,
+    pub show_hud: bool,
+    pub stats: StatsTracker,
+    /// Seat of the button in the last hand dealt
+    pub dealer_seat: Option<u32>,
+    /// Hands dealt so far
+    pub hand_number: u32,
+    /// Every hand's deck seed derives from this and the hand number, and the seeds of the bots
+    /// playing the hand from the deck seed, so the two of them are the whole random state of
+    /// the table. Agents outside the crate's control, such as LLMs, may still answer differently.
+    pub seed: u64,
 }
 
+/// With the `serde` feature this is the body of `POST /tables` in the REST API.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreatePokerGame {
     pub seats: u32,
     pub default_buy_in_value: u32,
     pub buy_in_limit: u32,
     pub blind_price: u32,
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    pub ante: u32,
+    /// Include opponents' session statistics in every player view
+    pub show_hud: bool,
 }
 
 impl PokerGame {
@@ -30,27 +86,76 @@ impl PokerGame {
             seats: params.seats,
             default_buy_in_value: params.default_buy_in_value,
             buy_in_limit: params.buy_in_limit,
-            blind_price: params.blind_price
+            blind_price: params.blind_price,
+            betting: params.betting,
+            variant: params.variant,
+            ante: params.ante,
+            show_hud: params.show_hud,
+            stats: StatsTracker::new(),
+            dealer_seat: None,
+            hand_number: 0,
+            seed: 0,
+        }
+    }
+
+    /// Moves the button to the next player with chips and sets up the next hand, or returns
+    /// `None` while fewer than two players have chips.
+    pub fn next_hand(&mut self) -> Option<StartHandParams> {
+        let mut players: Vec<_> = self.players.iter().filter(|p| p.cash_amount > 0).cloned().collect();
+        if players.len() < 2 {
+            return None;
         }
+        players.sort_by_key(|p| p.seat);
+
+        let dealer_seat = players.iter()
+            .map(|p| p.seat)
+            .find(|&seat| self.dealer_seat.is_none_or(|last| seat > last))
+            .unwrap_or(players[0].seat);
+        let seed = self.seed ^ (self.hand_number as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+
+        self.dealer_seat = Some(dealer_seat);
+        self.hand_number += 1;
+
+        Some(StartHandParams { blind_price: self.blind_price, players, dealer_seat, seed, betting: self.betting, variant: self.variant, ante: self.ante })
+    }
+
+    /// Adds each player's net result of a hand to their stack, by player id
+    pub fn apply_winnings(&mut self, winnings: &BTreeMap<usize, i64>) {
+        for (&player_id, &amount) in winnings {
+            if let Some(player) = self.players.iter_mut().find(|p| p.player.id == player_id) {
+                player.cash_amount = (player.cash_amount as i64 + amount).max(0) as u32;
+            }
+        }
+    }
+
+    /// Adds a finished hand to the session statistics shown in the HUD.
+    pub fn record_hand(&mut self, hand: &HandRecord) {
+        self.stats.record_hand(hand);
+    }
+
+    pub fn player_view(&self, hand: &PokerHand, seat: u32, stage: HandStage) -> Result<PlayerView, PokerGameError> {
+        let hud = if self.show_hud { Some(&self.stats) } else { None };
+        PlayerView::create(hand, seat, stage, hud)
     }
 
     pub fn add_player(&mut self, player: Player, seat: u32) -> Result<(), PokerGameError> {
-        return self.validate_seat(seat).or_else(|e| return Err(e))
+        self.validate_seat(seat)
             .and_then(|_| self.validate_new_player(&player))
-            .or_else(|e| return Err(e))
-            .and_then(|_| self.handle_new_player(player, seat));
+            .and_then(|_| self.handle_new_player(player, seat))
     }
 
     pub fn handle_new_player(&mut self, player: Player, seat:u32) -> Result<(), PokerGameError> {
         let mut poker_player = PokerGamePlayer::create(seat, player);
 
-        return poker_player.fund(self.default_buy_in_value)
-            .and_then(
-                |_| {
-                    self.players.push(poker_player);
-                    Ok(())
-                }
-            ).map_err(|e| PokerGameError::SeatMustBeGreaterThanZero)
+        // Tables without a default buy-in seat players unfunded
+        if self.default_buy_in_value == 0 {
+            self.players.push(poker_player);
+            return Ok(())
+        }
+
+        poker_player.fund(self.default_buy_in_value)?;
+        self.players.push(poker_player);
+        Ok(())
     }
 
     fn validate_new_player(&self, player: &Player) -> Result<(), PokerGameError> {
@@ -70,7 +175,7 @@ impl PokerGame {
     }
 
     pub fn validate_seat(&self, seat: u32) -> Result<(), PokerGameError> {
-        if seat <= 0 {
+        if seat == 0 {
             return Err(PokerGameError::SeatMustBeGreaterThanZero);
         }
 
@@ -98,7 +203,6 @@ impl PokerGame {
 
 #[cfg(test)]
 mod tests {
-    use crate::game::poker::blind::Blind;
     use super::*;
 
 
@@ -112,7 +216,11 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:0,
-            blind_price: 50
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
         });
         let result = game.validate_seat(0);
         assert!(matches!(result, Err(PokerGameError::SeatMustBeGreaterThanZero)));
@@ -124,7 +232,11 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
         });
         let result = game.validate_seat(6);
         assert!(matches!(result, Err(PokerGameError::SeatGreaterThanAllowed(6, 5))));
@@ -136,8 +248,12 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let result = game.validate_seat(3);
         assert!(result.is_ok());
     }
@@ -145,12 +261,17 @@ mod tests {
     #[test]
     fn test_seat_already_in_use() {
         let player = dummy_player_with_seat(2);
-        let game = PokerGame::new(CreatePokerGame{
+        let mut game = PokerGame::new(CreatePokerGame{
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
+        game.players.push(player);
         let result = game.validate_seat(2);
         assert!(matches!(result, Err(PokerGameError::SeatAlreadyInUse)));
     }
@@ -158,12 +279,17 @@ mod tests {
     #[test]
     fn test_seat_valid_and_not_taken() {
         let player = dummy_player_with_seat(2);
-        let game = PokerGame::new(CreatePokerGame{
+        let mut game = PokerGame::new(CreatePokerGame{
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
+        game.players.push(player);
         let result = game.validate_seat(3);
         assert!(result.is_ok());
     }
@@ -178,8 +304,12 @@ mod tests {
             default_buy_in_value:200,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let player = player_with_id(1, "Alice");
         let result = game.add_player(player, 1);
         assert!(result.is_ok());
@@ -195,8 +325,12 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let p1 = player_with_id(1, "Alice");
         let p2 = player_with_id(2, "Bob");
 
@@ -211,8 +345,12 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let player = player_with_id(1, "Alice");
         let result = game.add_player(player, 0);
         assert!(matches!(result, Err(PokerGameError::SeatMustBeGreaterThanZero)));
@@ -224,8 +362,12 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let player = player_with_id(1, "Alice");
         let result = game.add_player(player, 6);
         assert!(matches!(result, Err(PokerGameError::SeatGreaterThanAllowed(6, 5))));
@@ -237,8 +379,12 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let p1 = player_with_id(1, "Alice");
         let p2 = player_with_id(1, "Bob"); // Same ID, different name
 
@@ -253,8 +399,12 @@ mod tests {
             default_buy_in_value:0,
             buy_in_limit:2,
             seats:5,
-            blind_price: 50
-        });;
+            blind_price: 50,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false
+        });
         let p1 = player_with_id(1, "Alice");
         let p2 = player_with_id(2, "Alice"); // Same name, different ID
 
@@ -263,5 +413,52 @@ mod tests {
         assert!(matches!(result, Err(PokerGameError::NameAlreadyUsed(name)) if name == "Alice"));
     }
 
+    fn heads_up_hand(show_hud: bool) -> (PokerGame, PokerHand) {
+        let mut game = PokerGame::new(CreatePokerGame{
+            default_buy_in_value:1000,
+            buy_in_limit:2,
+            seats:2,
+            blind_price: 5,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud
+        });
+        game.add_player(player_with_id(1, "Alice"), 1).unwrap();
+        game.add_player(player_with_id(2, "Bob"), 2).unwrap();
+
+        let hand = PokerHand::start(hand::StartHandParams {
+            blind_price: game.blind_price,
+            players: game.players.clone(),
+            dealer_seat: 1,
+            seed: 1,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+        }).unwrap();
+        game.record_hand(&HandRecord { seats: vec![(1, 1), (2, 2)], ..HandRecord::default() });
+
+        (game, hand)
+    }
+
+    #[test]
+    fn test_player_view_includes_hud_when_enabled() {
+        let (game, hand) = heads_up_hand(true);
+        let view = game.player_view(&hand, 1, HandStage::INIT).unwrap();
+
+        assert_eq!(view.opponent_stats.len(), 1);
+        assert_eq!(view.opponent_stats[0].name, "Bob");
+        assert_eq!(view.opponent_stats[0].stats.hands, 1);
+    }
+
+    #[test]
+    fn test_player_view_hides_hud_when_disabled() {
+        let (game, hand) = heads_up_hand(false);
+        let view = game.player_view(&hand, 1, HandStage::INIT).unwrap();
+
+        assert!(view.opponent_stats.is_empty());
+        assert!(!view.prompt().contains("Opponent statistics"));
+    }
+
 }
 
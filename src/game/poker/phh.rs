@@ -0,0 +1,952 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::Formatter;
+use crate::deck::{Card, Deck};
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::betting::{BettingStructure, DEFAULT_RAISE_CAP};
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::blind::Blind;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::event::{HandEvent, HandEventKind};
+use crate::game::poker::game_player::PokerGamePlayer;
+use crate::game::poker::hand::{PokerHand, StartHandParams};
+use crate::player::Player;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhhError {
+    Toml(String),
+    MissingField(&'static str),
+    UnsupportedVariant(String),
+    UnsupportedBlinds(Vec<u32>),
+    InvalidAction(String),
+    InvalidCard(String),
+    Disagreement(String),
+    Engine(PokerGameError),
+}
+
+impl fmt::Display for PhhError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            PhhError::Toml(message) => format!("not a TOML document: {}", message),
+            PhhError::MissingField(field) => format!("missing field `{}`", field),
+            PhhError::UnsupportedVariant(variant) => format!("unsupported variant {}", variant),
+            PhhError::UnsupportedBlinds(blinds) => format!("unsupported blinds {:?}", blinds),
+            PhhError::InvalidAction(action) => format!("invalid action `{}`", action),
+            PhhError::InvalidCard(card) => format!("invalid card `{}`", card),
+            PhhError::Disagreement(what) => format!("the replay disagrees with the file: {}", what),
+            PhhError::Engine(error) => format!("the engine rejected the hand: {}", error),
+        };
+        write!(f, "{}", res)
+    }
+}
+
+impl std::error::Error for PhhError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PhhError::Engine(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<PokerGameError> for PhhError {
+    fn from(error: PokerGameError) -> Self {
+        PhhError::Engine(error)
+    }
+}
+
+/// One entry of the PHH `actions` list. Players are numbered from 1, `p1` being the first
+/// player to the left of the button.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhhAction {
+    DealHole { player: usize, cards: Vec<Card> },
+    DealBoard { cards: Vec<Card> },
+    /// The forced bet of stud games, posted by the worst up-card
+    BringIn { player: usize },
+    Fold { player: usize },
+    CheckOrCall { player: usize },
+    /// `amount` is the player's total bet on the street after the action
+    BetOrRaiseTo { player: usize, amount: u32 },
+    ShowOrMuck { player: usize, cards: Vec<Card> },
+    /// Cards thrown away at a draw, none to stand pat; the replacements follow as `d dh`
+    StandPatOrDiscard { player: usize, cards: Vec<Card> },
+}
+
+fn parse_cards(value: &str) -> Result<Vec<Card>, PhhError> {
+    if !value.len().is_multiple_of(2) {
+        return Err(PhhError::InvalidCard(value.to_string()));
+    }
+
+    (0..value.len()).step_by(2)
+        .map(|i| value.get(i..i + 2)
+            .and_then(Card::from_code)
+            .ok_or_else(|| PhhError::InvalidCard(value.to_string())))
+        .collect()
+}
+
+fn cards_code(cards: &[Card]) -> String {
+    cards.iter().map(|c| c.code()).collect()
+}
+
+impl PhhAction {
+    pub fn parse(value: &str) -> Result<PhhAction, PhhError> {
+        let invalid = || PhhError::InvalidAction(value.to_string());
+        let without_comment = value.split('#').next().unwrap_or_default();
+        let parts: Vec<&str> = without_comment.split_whitespace().collect();
+        let player = |part: &str| part.strip_prefix('p')
+            .and_then(|index| index.parse::<usize>().ok())
+            .filter(|&index| index > 0)
+            .ok_or_else(invalid);
+
+        match parts[..] {
+            ["d", "dh", p, cards] => Ok(PhhAction::DealHole { player: player(p)?, cards: parse_cards(cards)? }),
+            ["d", "db", cards] => Ok(PhhAction::DealBoard { cards: parse_cards(cards)? }),
+            [p, "pb"] => Ok(PhhAction::BringIn { player: player(p)? }),
+            [p, "f"] => Ok(PhhAction::Fold { player: player(p)? }),
+            [p, "cc"] => Ok(PhhAction::CheckOrCall { player: player(p)? }),
+            [p, "cbr", amount] => Ok(PhhAction::BetOrRaiseTo {
+                player: player(p)?,
+                amount: amount.parse().map_err(|_| invalid())?,
+            }),
+            [p, "sm"] | [p, "sm", "-"] => Ok(PhhAction::ShowOrMuck { player: player(p)?, cards: vec![] }),
+            // Unknown cards, as a mucked hand is written
+            [p, "sm", cards] if cards.chars().all(|c| c == '?') => Ok(PhhAction::ShowOrMuck { player: player(p)?, cards: vec![] }),
+            [p, "sm", cards] => Ok(PhhAction::ShowOrMuck { player: player(p)?, cards: parse_cards(cards)? }),
+            [p, "sd"] => Ok(PhhAction::StandPatOrDiscard { player: player(p)?, cards: vec![] }),
+            [p, "sd", cards] => Ok(PhhAction::StandPatOrDiscard { player: player(p)?, cards: parse_cards(cards)? }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for PhhAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PhhAction::DealHole { player, cards } => write!(f, "d dh p{} {}", player, cards_code(cards)),
+            PhhAction::DealBoard { cards } => write!(f, "d db {}", cards_code(cards)),
+            PhhAction::BringIn { player } => write!(f, "p{} pb", player),
+            PhhAction::Fold { player } => write!(f, "p{} f", player),
+            PhhAction::CheckOrCall { player } => write!(f, "p{} cc", player),
+            PhhAction::BetOrRaiseTo { player, amount } => write!(f, "p{} cbr {}", player, amount),
+            PhhAction::ShowOrMuck { player, cards } if cards.is_empty() => write!(f, "p{} sm -", player),
+            PhhAction::ShowOrMuck { player, cards } => write!(f, "p{} sm {}", player, cards_code(cards)),
+            PhhAction::StandPatOrDiscard { player, cards } if cards.is_empty() => write!(f, "p{} sd", player),
+            PhhAction::StandPatOrDiscard { player, cards } => write!(f, "p{} sd {}", player, cards_code(cards)),
+        }
+    }
+}
+
+/// A hand in the Poker Hand History (PHH) format: no-limit Hold'em (`NT`), fixed-limit
+/// Hold'em (`FT`), no-limit short-deck Hold'em (`NS`), pot-limit Omaha (`PO`), fixed-limit
+/// Omaha hi-lo (`FO/8`), fixed-limit seven-card stud (`F7S`), fixed-limit Razz (`FR`) or
+/// fixed-limit 2-7 triple draw (`F2L3D`). Other pairings of betting structure and game, and
+/// five-card draw, have no code in the specification, so they are written following its
+/// naming: `N`, `P` or `F`, then `T`, `S`, `O`, `O/8`, `7S`, `R`, `5D` or `2L3D`.
+///
+/// Per-player lists are in PHH order: from the first player left of the button round to the
+/// button. Hole cards must be known (no `??`) for a hand to be replayed. Stud hands deal every
+/// street with `d dh`, up-cards included, and draw hands deal the replacements of each
+/// `sd` with `d dh`. Unseeded hands that reshuffle the discards cannot be replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhhHand {
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    /// All zero unless antes replace the blinds
+    pub antes: Vec<u32>,
+    pub blinds_or_straddles: Vec<u32>,
+    /// The small bet of fixed-limit hands, the big bet being twice as much
+    pub min_bet: u32,
+    pub starting_stacks: Vec<u32>,
+    pub actions: Vec<PhhAction>,
+    pub players: Vec<String>,
+    pub seats: Vec<u32>,
+    /// Empty when the file does not record them
+    pub finishing_stacks: Vec<u32>,
+    pub table: Option<String>,
+    pub hand: Option<u64>,
+    /// Deck seed of hands played by this engine, kept in the user-defined `_seed` field
+    pub seed: Option<u64>,
+}
+
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+fn toml_numbers(values: &[u32]) -> String {
+    format!("[{}]", values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "))
+}
+
+impl PhhHand {
+    pub fn from_events(events: &[HandEvent]) -> Result<PhhHand, PokerGameError> {
+        let started = events.iter().find_map(|e| match &e.kind {
+            HandEventKind::HandStarted { seed, blind_price, betting, variant, players, .. } => Some((*seed, *blind_price, *betting, *variant, players)),
+            _ => None,
+        });
+        if started.is_none() {
+            return Err(PokerGameError::HandNotStarted);
+        }
+        let (seed, blind_price, betting, variant, players) = started.expect("Expect the hand to be started");
+
+        // Events list players from the dealer, PHH from the seat after the button
+        let mut ordered = players.clone();
+        ordered.rotate_left(1);
+        let index: BTreeMap<u32, usize> = ordered.iter().enumerate().map(|(i, p)| (p.seat, i)).collect();
+        let number = |seat: &u32| index.get(seat).map(|i| i + 1).ok_or(PokerGameError::PlayerSeatNotFound(*seat));
+
+        let mut antes = vec![0; ordered.len()];
+        let mut blinds = vec![0; ordered.len()];
+        let mut stacks: Vec<u32> = ordered.iter().map(|p| p.stack).collect();
+        let mut street_bets: BTreeMap<u32, u32> = BTreeMap::new();
+        let mut actions = vec![];
+
+        for event in events {
+            match &event.kind {
+                HandEventKind::HandStarted { .. } => {}
+                HandEventKind::AntePosted { seat, amount } => {
+                    antes[number(seat)? - 1] = *amount;
+                    stacks[number(seat)? - 1] -= amount;
+                }
+                HandEventKind::BlindPosted { seat, amount, .. } => {
+                    blinds[number(seat)? - 1] = *amount;
+                    stacks[number(seat)? - 1] -= amount;
+                    *street_bets.entry(*seat).or_default() += amount;
+                }
+                HandEventKind::BringInPosted { seat, amount } => {
+                    stacks[number(seat)? - 1] -= amount;
+                    *street_bets.entry(*seat).or_default() += amount;
+                    actions.push(PhhAction::BringIn { player: number(seat)? });
+                }
+                HandEventKind::HoleCardsDealt { seat, cards } | HandEventKind::UpCardsDealt { seat, cards } => {
+                    let player = number(seat)?;
+                    // A stud street deals a player's face-down and face-up cards in one action
+                    match actions.last_mut() {
+                        Some(PhhAction::DealHole { player: last, cards: dealt }) if *last == player => dealt.extend(cards.iter().copied()),
+                        _ => actions.push(PhhAction::DealHole { player, cards: cards.clone() }),
+                    }
+                }
+                HandEventKind::ActionTaken { seat, action, .. } => {
+                    let player = number(seat)?;
+                    let bet = street_bets.entry(*seat).or_default();
+                    *bet += action.amount();
+                    stacks[player - 1] -= action.amount();
+                    actions.push(match action {
+                        PokerAction::Fold => PhhAction::Fold { player },
+                        PokerAction::Check | PokerAction::Call(_) => PhhAction::CheckOrCall { player },
+                        PokerAction::Bet(_) | PokerAction::Raise(_) => PhhAction::BetOrRaiseTo { player, amount: *bet },
+                    });
+                }
+                HandEventKind::BoardDealt { cards, .. } => {
+                    street_bets.clear();
+                    if !cards.is_empty() {
+                        actions.push(PhhAction::DealBoard { cards: cards.clone() });
+                    }
+                }
+                HandEventKind::DrawTaken { seat, count: 0, .. } => actions.push(PhhAction::StandPatOrDiscard { player: number(seat)?, cards: vec![] }),
+                HandEventKind::DrawTaken { .. } => {}
+                HandEventKind::CardsDrawn { seat, discarded, drawn } => {
+                    let player = number(seat)?;
+                    actions.push(PhhAction::StandPatOrDiscard { player, cards: discarded.clone() });
+                    actions.push(PhhAction::DealHole { player, cards: drawn.clone() });
+                }
+                HandEventKind::Showdown { seat, cards } =>
+                    actions.push(PhhAction::ShowOrMuck { player: number(seat)?, cards: cards.clone() }),
+                HandEventKind::PotAwarded { seat, amount } => stacks[number(seat)? - 1] += amount,
+            }
+        }
+
+        Ok(PhhHand {
+            betting,
+            variant,
+            antes,
+            blinds_or_straddles: blinds,
+            min_bet: blind_price * Blind::BIG as u32,
+            starting_stacks: ordered.iter().map(|p| p.stack).collect(),
+            actions,
+            players: ordered.iter().map(|p| p.name.clone()).collect(),
+            seats: ordered.iter().map(|p| p.seat).collect(),
+            finishing_stacks: stacks,
+            table: None,
+            hand: None,
+            seed: Some(seed),
+        })
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::new();
+        let betting = match self.betting {
+            BettingStructure::NoLimit => "N",
+            BettingStructure::PotLimit => "P",
+            BettingStructure::FixedLimit { .. } => "F",
+        };
+        let game = match self.variant {
+            GameVariant::Holdem => "T",
+            GameVariant::ShortDeck => "S",
+            GameVariant::Omaha => "O",
+            GameVariant::OmahaHiLo => "O/8",
+            GameVariant::Stud => "7S",
+            GameVariant::Razz => "R",
+            GameVariant::FiveCardDraw => "5D",
+            GameVariant::TripleDraw => "2L3D",
+        };
+        toml.push_str(&format!("variant = \"{}{}\"\n", betting, game));
+        toml.push_str(&format!("antes = {}\n", toml_numbers(&self.antes)));
+        toml.push_str(&format!("blinds_or_straddles = {}\n", toml_numbers(&self.blinds_or_straddles)));
+        match self.betting {
+            BettingStructure::FixedLimit { raise_cap } => {
+                toml.push_str(&format!("small_bet = {}\n", self.min_bet));
+                toml.push_str(&format!("big_bet = {}\n", 2 * self.min_bet));
+                toml.push_str(&format!("_raise_cap = {}\n", raise_cap));
+            }
+            _ => toml.push_str(&format!("min_bet = {}\n", self.min_bet)),
+        }
+        if self.variant.is_stud() {
+            toml.push_str(&format!("bring_in = {}\n", self.min_bet / Blind::BIG as u32));
+        }
+        toml.push_str(&format!("starting_stacks = {}\n", toml_numbers(&self.starting_stacks)));
+        toml.push_str("actions = [\n");
+        for action in &self.actions {
+            toml.push_str(&format!("    {},\n", toml_string(&action.to_string())));
+        }
+        toml.push_str("]\n");
+
+        let players: Vec<String> = self.players.iter().map(|p| toml_string(p)).collect();
+        toml.push_str(&format!("players = [{}]\n", players.join(", ")));
+        toml.push_str(&format!("seats = {}\n", toml_numbers(&self.seats)));
+        if !self.finishing_stacks.is_empty() {
+            toml.push_str(&format!("finishing_stacks = {}\n", toml_numbers(&self.finishing_stacks)));
+        }
+        if let Some(table) = &self.table {
+            toml.push_str(&format!("table = {}\n", toml_string(table)));
+        }
+        if let Some(hand) = self.hand {
+            toml.push_str(&format!("hand = {}\n", hand));
+        }
+        if let Some(seed) = self.seed {
+            // TOML integers are signed 64-bit, so the seed is stored bit for bit
+            toml.push_str(&format!("_seed = {}\n", seed as i64));
+        }
+
+        toml
+    }
+
+    pub fn parse(text: &str) -> Result<PhhHand, PhhError> {
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| PhhError::Toml(e.to_string()))?;
+
+        let variant = table.get("variant").and_then(|v| v.as_str()).ok_or(PhhError::MissingField("variant"))?;
+        let integer = |key: &'static str| table.get(key).and_then(|v| v.as_integer()).and_then(|v| u32::try_from(v).ok())
+            .ok_or(PhhError::MissingField(key));
+        let game = match variant.get(1..) {
+            Some("T") => GameVariant::Holdem,
+            Some("S") => GameVariant::ShortDeck,
+            Some("O") => GameVariant::Omaha,
+            Some("O/8") => GameVariant::OmahaHiLo,
+            Some("7S") => GameVariant::Stud,
+            Some("R") => GameVariant::Razz,
+            Some("5D") => GameVariant::FiveCardDraw,
+            Some("2L3D") => GameVariant::TripleDraw,
+            _ => return Err(PhhError::UnsupportedVariant(variant.to_string())),
+        };
+        let (betting, min_bet) = match variant.get(..1) {
+            Some("N") => (BettingStructure::NoLimit, integer("min_bet")?),
+            Some("P") => (BettingStructure::PotLimit, integer("min_bet")?),
+            Some("F") => {
+                let small_bet = integer("small_bet")?;
+                if integer("big_bet")? != 2 * small_bet {
+                    return Err(PhhError::UnsupportedVariant(format!("{} with a big bet other than two small bets", variant)));
+                }
+                let raise_cap = match table.get("_raise_cap") {
+                    Some(_) => integer("_raise_cap")?,
+                    None => DEFAULT_RAISE_CAP,
+                };
+                (BettingStructure::FixedLimit { raise_cap }, small_bet)
+            }
+            _ => return Err(PhhError::UnsupportedVariant(variant.to_string())),
+        };
+        if table.contains_key("bring_in") && integer("bring_in")? != min_bet / Blind::BIG as u32 {
+            return Err(PhhError::UnsupportedVariant(format!("{} with a bring-in other than half the small bet", variant)));
+        }
+
+        let numbers = |key: &'static str| -> Result<Option<Vec<u32>>, PhhError> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(value) => value.as_array()
+                    .and_then(|values| values.iter()
+                        .map(|v| v.as_integer().and_then(|i| u32::try_from(i).ok()))
+                        .collect::<Option<Vec<u32>>>())
+                    .map(Some)
+                    .ok_or(PhhError::MissingField(key)),
+            }
+        };
+
+        let starting_stacks = numbers("starting_stacks")?.ok_or(PhhError::MissingField("starting_stacks"))?;
+        let players_count = starting_stacks.len();
+
+        let antes = numbers("antes")?.unwrap_or_else(|| vec![0; players_count]);
+
+        let actions = table.get("actions")
+            .and_then(|v| v.as_array())
+            .ok_or(PhhError::MissingField("actions"))?
+            .iter()
+            .map(|v| v.as_str().ok_or(PhhError::MissingField("actions")).and_then(PhhAction::parse))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let players = match table.get("players").and_then(|v| v.as_array()) {
+            Some(values) => values.iter()
+                .map(|v| v.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(PhhError::MissingField("players"))?,
+            None => (1..=players_count).map(|i| format!("p{}", i)).collect(),
+        };
+
+        Ok(PhhHand {
+            betting,
+            variant: game,
+            antes,
+            blinds_or_straddles: numbers("blinds_or_straddles")?.ok_or(PhhError::MissingField("blinds_or_straddles"))?,
+            min_bet,
+            starting_stacks,
+            actions,
+            players,
+            seats: numbers("seats")?.unwrap_or_else(|| (1..=players_count as u32).collect()),
+            finishing_stacks: numbers("finishing_stacks")?.unwrap_or_default(),
+            table: table.get("table").and_then(|v| v.as_str()).map(String::from),
+            hand: table.get("hand").and_then(|v| v.as_integer()).map(|v| v as u64),
+            seed: table.get("_seed").and_then(|v| v.as_integer()).map(|v| v as u64),
+        })
+    }
+
+    /// Small blind of the hand, checking the blinds sit where this engine posts them
+    fn small_blind(&self) -> Result<u32, PhhError> {
+        let unsupported = || PhhError::UnsupportedBlinds(self.blinds_or_straddles.clone());
+        // Antes and the bring-in replace the blinds, which then only size the bets
+        if self.ante() > 0 || self.variant.is_stud() {
+            if self.blinds_or_straddles.iter().any(|&b| b > 0) {
+                return Err(unsupported());
+            }
+            return Ok(self.min_bet / Blind::BIG as u32);
+        }
+        let (small, big) = match self.blinds_or_straddles[..] {
+            [big, small] => (small, big),
+            [small, big, ref rest @ ..] if rest.iter().all(|&b| b == 0) => (small, big),
+            _ => return Err(unsupported()),
+        };
+
+        if small == 0 || big != small * Blind::BIG as u32 {
+            return Err(unsupported());
+        }
+        Ok(small)
+    }
+
+    /// The ante of the hand, players short of it having posted less
+    fn ante(&self) -> u32 {
+        self.antes.iter().copied().max().unwrap_or(0)
+    }
+
+    /// The deck this engine would need to deal exactly the hand's hole cards and board
+    fn deck(&self, dealing_order: &[usize]) -> Result<Deck, PhhError> {
+        let burns = self.variant.burns_cards();
+        let mut slots: Vec<Option<Card>> = vec![];
+        let mut street: BTreeMap<usize, Vec<Card>> = BTreeMap::new();
+        // The engine deals the cards of a street one round at a time, in dealing order
+        let deal_street = |street: &mut BTreeMap<usize, Vec<Card>>, slots: &mut Vec<Option<Card>>| {
+            let rounds = street.values().map(|cards| cards.len()).max().unwrap_or(0);
+            for round in 0..rounds {
+                for player in dealing_order.iter().filter(|player| street.contains_key(player)) {
+                    slots.push(street[player].get(round).copied());
+                }
+                if burns {
+                    slots.push(None);
+                }
+            }
+            street.clear();
+        };
+
+        for action in &self.actions {
+            match action {
+                PhhAction::DealHole { player, cards } => {
+                    // A player dealt twice in a row starts a street dealt without betting
+                    if street.contains_key(player) {
+                        deal_street(&mut street, &mut slots);
+                    }
+                    street.insert(*player, cards.clone());
+                }
+                PhhAction::DealBoard { cards } => {
+                    deal_street(&mut street, &mut slots);
+                    if burns {
+                        slots.push(None);
+                    }
+                    slots.extend(cards.iter().map(|&card| Some(card)));
+                }
+                _ => deal_street(&mut street, &mut slots),
+            }
+        }
+        deal_street(&mut street, &mut slots);
+
+        let known: Vec<Card> = slots.iter().flatten().copied().collect();
+        for (i, card) in known.iter().enumerate() {
+            if known[i + 1..].contains(card) {
+                return Err(PhhError::InvalidCard(card.code()));
+            }
+        }
+
+        let mut spare = self.variant.deck();
+        let mut cards = Vec::with_capacity(52);
+        for slot in slots {
+            match slot {
+                Some(card) => cards.push(card),
+                None => {
+                    let mut card = spare.get_next_card();
+                    while known.contains(&card) {
+                        card = spare.get_next_card();
+                    }
+                    cards.push(card);
+                }
+            }
+        }
+
+        Ok(Deck::from_cards(cards))
+    }
+
+    /// Plays the hand through the engine. Hands carrying a seed are dealt from the seeded deck,
+    /// so their recorded cards are checked against it; others are dealt the recorded cards.
+    ///
+    /// At the end a lone remaining player collects the pot, and a showdown of known hands is
+    /// settled by the engine. Only when a player mucked at showdown (`sm -` or `sm ????`), or
+    /// the file stops before the betting is over, is the pot split as the recorded finishing
+    /// stacks say, and left in the middle when they are missing.
+    pub fn replay(&self) -> Result<PokerHand, PhhError> {
+        let count = self.starting_stacks.len();
+        if self.seats.len() != count || self.players.len() != count {
+            return Err(PhhError::MissingField("seats"));
+        }
+        let small_blind = self.small_blind()?;
+
+        let players = (0..count).map(|i| {
+            let mut player = PokerGamePlayer::create(self.seats[i], Player::create(i + 1, &self.players[i], ""));
+            player.fund(self.starting_stacks[i]).map(|_| player)
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        let params = StartHandParams {
+            blind_price: small_blind,
+            players,
+            dealer_seat: self.seats[count - 1],
+            seed: self.seed.unwrap_or_default(),
+            betting: self.betting,
+            variant: self.variant,
+            ante: self.ante(),
+        };
+        let mut hand = match self.seed {
+            Some(_) => PokerHand::start(params)?,
+            None => {
+                let dealing_order: Vec<usize> = std::iter::once(count).chain(1..count).collect();
+                PokerHand::start_with_deck(params, self.deck(&dealing_order)?)?
+            }
+        };
+
+        let seat = |player: usize| self.seats.get(player - 1).copied()
+            .ok_or_else(|| PhhError::InvalidAction(format!("p{}", player)));
+        let mut street_bets: BTreeMap<u32, u32> = hand.events.iter().filter_map(|e| match e.kind {
+            HandEventKind::BlindPosted { seat, amount, .. } | HandEventKind::BringInPosted { seat, amount } => Some((seat, amount)),
+            _ => None,
+        }).collect();
+        let mut mucked = false;
+        // Players dealt cards of the current street, cleared once the street is bet
+        let mut dealt_players: BTreeSet<usize> = BTreeSet::new();
+        let mut dealing = true;
+
+        for (index, action) in self.actions.iter().enumerate() {
+            let highest_bet = street_bets.values().copied().max().unwrap_or(0);
+            let replacing = index > 0 && matches!(self.actions[index - 1], PhhAction::StandPatOrDiscard { .. });
+            if !matches!(action, PhhAction::DealHole { .. }) {
+                dealing = false;
+            }
+            match action {
+                PhhAction::DealHole { player, cards } => {
+                    // Stud games deal the players' cards of every street, draw games the
+                    // replacements of a discard, which the engine dealt as it drew
+                    if !replacing && (!dealing || dealt_players.contains(player)) {
+                        street_bets.clear();
+                        hand.deal_next_stage()?;
+                        dealt_players.clear();
+                        dealing = true;
+                    }
+                    dealt_players.insert(*player);
+                    let seat = seat(*player)?;
+                    let dealt = hand.active.iter().find(|p| p.seat == seat).map(|p| &p.hand);
+                    if !dealt.is_some_and(|dealt| dealt.ends_with(cards)) {
+                        return Err(PhhError::Disagreement(format!("hole cards of {}", action)));
+                    }
+                }
+                PhhAction::BringIn { player } => {
+                    let seat = seat(*player)?;
+                    if !hand.events.iter().any(|e| matches!(e.kind, HandEventKind::BringInPosted { seat: posted, .. } if posted == seat)) {
+                        return Err(PhhError::Disagreement(format!("bring-in {}", action)));
+                    }
+                }
+                PhhAction::DealBoard { cards } => {
+                    street_bets.clear();
+                    if hand.deal_next_stage()? != *cards {
+                        return Err(PhhError::Disagreement(format!("board {}", action)));
+                    }
+                }
+                PhhAction::Fold { player } => hand.act(seat(*player)?, PokerAction::Fold)?,
+                PhhAction::CheckOrCall { player } => {
+                    let seat = seat(*player)?;
+                    let bet = street_bets.entry(seat).or_default();
+                    let stack = hand.active.iter().find(|p| p.seat == seat).map(|p| p.cash_amount).unwrap_or(0);
+                    let to_call = highest_bet.saturating_sub(*bet).min(stack);
+                    *bet += to_call;
+                    hand.act(seat, if to_call == 0 { PokerAction::Check } else { PokerAction::Call(to_call) })?;
+                }
+                PhhAction::BetOrRaiseTo { player, amount } => {
+                    let seat = seat(*player)?;
+                    let bet = street_bets.entry(seat).or_default();
+                    let added = amount.checked_sub(*bet).ok_or_else(|| PhhError::InvalidAction(action.to_string()))?;
+                    *bet = *amount;
+                    hand.act(seat, if highest_bet == 0 { PokerAction::Bet(added) } else { PokerAction::Raise(added) })?;
+                }
+                PhhAction::StandPatOrDiscard { player, cards } => {
+                    if hand.next_to_draw().is_none() {
+                        street_bets.clear();
+                        hand.deal_next_stage()?;
+                    }
+                    hand.draw(seat(*player)?, cards)?;
+                }
+                PhhAction::ShowOrMuck { cards, .. } => mucked |= cards.is_empty(),
+            }
+        }
+
+        if hand.active.len() == 1 {
+            let winner = hand.active[0].seat;
+            hand.award_pot(winner, hand.pot)?;
+        } else if !mucked && hand.is_round_complete() {
+            hand.settle()?;
+        } else if !self.finishing_stacks.is_empty() {
+            hand.show_down();
+            let shares: Vec<(u32, u32)> = hand.active.iter().filter_map(|p| {
+                let index = self.seats.iter().position(|&s| s == p.seat)?;
+                Some((p.seat, self.finishing_stacks[index].saturating_sub(p.cash_amount)))
+            }).collect();
+            for (seat, amount) in shares.into_iter().filter(|&(_, amount)| amount > 0) {
+                hand.award_pot(seat, amount)?;
+            }
+        }
+
+        Ok(hand)
+    }
+
+    /// Replays the hand and checks the engine ends with the recorded finishing stacks.
+    pub fn verify(&self) -> Result<PokerHand, PhhError> {
+        let hand = self.replay()?;
+
+        if !self.finishing_stacks.is_empty() {
+            if hand.pot != 0 {
+                return Err(PhhError::Disagreement(format!("{} chips left in the pot", hand.pot)));
+            }
+            for (index, &seat) in self.seats.iter().enumerate() {
+                let stack = hand.active.iter().chain(hand.folds.iter().map(|f| &f.player))
+                    .find(|p| p.seat == seat)
+                    .map(|p| p.cash_amount);
+                if stack != Some(self.finishing_stacks[index]) {
+                    return Err(PhhError::Disagreement(format!(
+                        "p{} finishes with {:?}, expected {}", index + 1, stack, self.finishing_stacks[index]
+                    )));
+                }
+            }
+        }
+
+        Ok(hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::game_player::funded_players;
+
+    const THREE_HANDED: &str = r#"
+# Carol turns a straight against Bob
+variant = 'NT'
+antes = [0, 0, 0]
+blinds_or_straddles = [50, 100, 0]
+min_bet = 100
+starting_stacks = [10000, 10000, 10000]
+actions = [
+  'd dh p1 Ac2d',
+  'd dh p2 5h7s',
+  'd dh p3 7h6h',
+  'p3 cbr 300',
+  'p1 f',
+  'p2 cc',
+  'd db Jc3d5c',
+  'p2 cc',
+  'p3 cbr 400',
+  'p2 cc',
+  'd db 4h',
+  'p2 cbr 1200',
+  'p3 cc',
+  'd db Jh',
+  'p2 cc',
+  'p3 cc',
+  'p2 sm 5h7s',
+  'p3 sm 7h6h',
+]
+players = ['Alice', 'Bob', 'Carol']
+finishing_stacks = [9950, 8100, 11950]
+"#;
+
+    /// Alice, Bob and Carol at seats 2, 4 and 6, Bob on the button
+    fn start(seed: u64, betting: BettingStructure, variant: GameVariant, ante: u32) -> PokerHand {
+        let players = funded_players(&[(2, "Alice"), (4, "Bob"), (6, "Carol")], 1000);
+        PokerHand::start(StartHandParams { blind_price: 5, players, dealer_seat: 4, seed, betting, variant, ante }).unwrap()
+    }
+
+    fn played_hand() -> PokerHand {
+        let mut hand = start(u64::MAX - 3, BettingStructure::NoLimit, GameVariant::Holdem, 0);
+        hand.act(4, PokerAction::Raise(30)).unwrap();
+        hand.act(6, PokerAction::Call(25)).unwrap();
+        hand.act(2, PokerAction::Call(20)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.act(6, PokerAction::Check).unwrap();
+        hand.act(2, PokerAction::Bet(60)).unwrap();
+        hand.act(4, PokerAction::Fold).unwrap();
+        hand.act(6, PokerAction::Raise(180)).unwrap();
+        hand.act(2, PokerAction::Call(120)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.show_down();
+        hand.award_pot(2, 200).unwrap();
+        hand.award_pot(6, hand.pot).unwrap();
+        hand
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(PhhAction::parse("p3 cbr 300").unwrap(), PhhAction::BetOrRaiseTo { player: 3, amount: 300 });
+        assert_eq!(PhhAction::parse("p1 f # tank fold").unwrap(), PhhAction::Fold { player: 1 });
+        assert_eq!(PhhAction::parse("p2 sm -").unwrap(), PhhAction::ShowOrMuck { player: 2, cards: vec![] });
+        assert_eq!(PhhAction::parse("d db Jc3d5c").unwrap().to_string(), "d db Jc3d5c");
+        assert_eq!(PhhAction::parse("p2 pb").unwrap(), PhhAction::BringIn { player: 2 });
+        assert!(matches!(PhhAction::parse("d dh p1 ????"), Err(PhhError::InvalidCard(_))));
+        assert!(matches!(PhhAction::parse("p0 f"), Err(PhhError::InvalidAction(_))));
+        assert!(matches!(PhhAction::parse("p1 sqz 300"), Err(PhhError::InvalidAction(_))));
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let phh = PhhHand::parse(THREE_HANDED).unwrap();
+
+        assert_eq!(phh.blinds_or_straddles, vec![50, 100, 0]);
+        assert_eq!(phh.min_bet, 100);
+        assert_eq!(phh.actions.len(), 18);
+        assert_eq!(phh.players, vec!["Alice", "Bob", "Carol"]);
+        assert_eq!(phh.seats, vec![1, 2, 3]);
+        assert_eq!(phh.seed, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_variants_and_antes_with_blinds() {
+        let badugi = THREE_HANDED.replace("variant = 'NT'", "variant = 'FB'");
+        assert_eq!(PhhHand::parse(&badugi), Err(PhhError::UnsupportedVariant(String::from("FB"))));
+
+        let antes = THREE_HANDED.replace("antes = [0, 0, 0]", "antes = [10, 10, 10]");
+        assert!(matches!(PhhHand::parse(&antes).unwrap().replay(), Err(PhhError::UnsupportedBlinds(_))));
+
+        assert!(matches!(PhhHand::parse("variant = "), Err(PhhError::Toml(_))));
+    }
+
+    #[test]
+    fn test_replay_external_hand_agrees() {
+        let hand = PhhHand::parse(THREE_HANDED).unwrap().verify().unwrap();
+
+        assert_eq!(hand.folds[0].player.player.name, "Alice");
+        assert_eq!(hand.active.iter().find(|p| p.seat == 3).unwrap().cash_amount, 11950);
+        assert_eq!(hand.board[4].code(), "Jh");
+    }
+
+    #[test]
+    fn test_verify_detects_wrong_finishing_stacks() {
+        let wrong = THREE_HANDED.replace("finishing_stacks = [9950, 8100, 11950]", "finishing_stacks = [9950, 8100, 11900]");
+        assert!(matches!(PhhHand::parse(&wrong).unwrap().verify(), Err(PhhError::Disagreement(_))));
+    }
+
+    #[test]
+    fn test_verify_detects_wrong_showdown_winner() {
+        // Bob's 5h7s loses to Carol's straight, yet the file pays him the pot
+        let wrong = THREE_HANDED.replace("finishing_stacks = [9950, 8100, 11950]", "finishing_stacks = [9950, 11950, 8100]");
+        assert!(matches!(PhhHand::parse(&wrong).unwrap().verify(), Err(PhhError::Disagreement(_))));
+    }
+
+    #[test]
+    fn test_mucked_showdown_pays_the_recorded_stacks() {
+        let mucked = THREE_HANDED.replace("'p2 sm 5h7s'", "'p2 sm ????'");
+        let hand = PhhHand::parse(&mucked).unwrap().verify().unwrap();
+        assert_eq!(hand.active.iter().find(|p| p.seat == 3).unwrap().cash_amount, 11950);
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let hand = played_hand();
+        let mut phh = PhhHand::from_events(&hand.events).unwrap();
+        phh.table = Some(String::from("LLM 'Arena'"));
+        phh.hand = Some(7);
+
+        assert_eq!(phh.players, vec!["Carol", "Alice", "Bob"]);
+        assert_eq!(phh.blinds_or_straddles, vec![5, 10, 0]);
+        assert_eq!(phh.actions[3], PhhAction::BetOrRaiseTo { player: 3, amount: 30 });
+        assert!(phh.actions.contains(&PhhAction::BetOrRaiseTo { player: 1, amount: 180 }));
+
+        let parsed = PhhHand::parse(&phh.to_toml()).unwrap();
+        assert_eq!(parsed, phh);
+    }
+
+    #[test]
+    fn test_fixed_limit_export_writes_small_and_big_bets() {
+        let mut phh = PhhHand::from_events(&played_hand().events).unwrap();
+        phh.betting = BettingStructure::FixedLimit { raise_cap: 3 };
+
+        let toml = phh.to_toml();
+        assert!(toml.starts_with("variant = \"FT\""));
+        assert!(toml.contains("small_bet = 10\nbig_bet = 20\n_raise_cap = 3\n"));
+        assert!(!toml.contains("min_bet"));
+        assert_eq!(PhhHand::parse(&toml).unwrap(), phh);
+    }
+
+    #[test]
+    fn test_omaha_hand_exports_and_replays_without_a_seed() {
+        let mut hand = start(9, BettingStructure::PotLimit, GameVariant::Omaha, 0);
+        hand.act(4, PokerAction::Call(10)).unwrap();
+        hand.act(6, PokerAction::Call(5)).unwrap();
+        hand.act(2, PokerAction::Check).unwrap();
+        hand.settle().unwrap();
+
+        let mut phh = PhhHand::from_events(&hand.events).unwrap();
+        let toml = phh.to_toml();
+        assert!(toml.starts_with("variant = \"PO\""));
+        assert_eq!(PhhHand::parse(&toml).unwrap(), phh);
+
+        phh.seed = None;
+        let replayed = phh.verify().unwrap();
+        assert_eq!(replayed.active[0].hand, hand.active[0].hand);
+        assert_eq!(replayed.board, hand.board);
+    }
+
+    #[test]
+    fn test_stud_hand_exports_and_replays_without_a_seed() {
+        let mut hand = start(11, BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::Stud, 1);
+        while !hand.is_round_complete() {
+            let seat = hand.speaker.seat;
+            let action = match hand.legal_actions(seat).unwrap().call {
+                Some(amount) => PokerAction::Call(amount),
+                None => PokerAction::Check,
+            };
+            hand.act(seat, action).unwrap();
+        }
+        hand.deal_next_stage().unwrap();
+        let seat = hand.speaker.seat;
+        hand.act(seat, PokerAction::Bet(10)).unwrap();
+        for _ in 0..2 {
+            let seat = hand.speaker.seat;
+            hand.act(seat, PokerAction::Call(10)).unwrap();
+        }
+        hand.settle().unwrap();
+
+        let mut phh = PhhHand::from_events(&hand.events).unwrap();
+        let toml = phh.to_toml();
+        assert!(toml.starts_with("variant = \"F7S\"\nantes = [1, 1, 1]\nblinds_or_straddles = [0, 0, 0]\n"));
+        assert!(toml.contains("bring_in = 5\n"));
+        assert!(phh.actions.iter().any(|a| matches!(a, PhhAction::BringIn { .. })));
+        assert!(phh.actions.iter().all(|a| !matches!(a, PhhAction::DealBoard { .. })));
+        assert_eq!(PhhHand::parse(&toml).unwrap(), phh);
+        assert!(PhhHand::parse(&toml.replace("bring_in = 5", "bring_in = 3")).is_err());
+
+        phh.seed = None;
+        let replayed = phh.verify().unwrap();
+        assert_eq!(replayed.active[0].hand, hand.active[0].hand);
+        assert_eq!(replayed.active[2].up_cards, hand.active[2].up_cards);
+    }
+
+    #[test]
+    fn test_triple_draw_hand_exports_and_replays_the_draws() {
+        let mut hand = start(11, BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::TripleDraw, 0);
+        loop {
+            // Carol stands pat, the others throw their first card away
+            while let Some(seat) = hand.next_to_draw() {
+                let first = hand.active.iter().find(|p| p.seat == seat).unwrap().hand[0];
+                let discards = if seat == 6 { vec![] } else { vec![first] };
+                hand.draw(seat, &discards).unwrap();
+            }
+            while !hand.is_round_complete() {
+                let seat = hand.speaker.seat;
+                let action = match hand.legal_actions(seat).unwrap().call {
+                    Some(amount) => PokerAction::Call(amount),
+                    None => PokerAction::Check,
+                };
+                hand.act(seat, action).unwrap();
+            }
+            if hand.deal_next_stage().is_err() {
+                break;
+            }
+        }
+        hand.settle().unwrap();
+
+        let mut phh = PhhHand::from_events(&hand.events).unwrap();
+        let toml = phh.to_toml();
+        assert!(toml.starts_with("variant = \"F2L3D\"\n"));
+        assert!(toml.contains("\"p1 sd\","));
+        assert_eq!(phh.actions.iter().filter(|a| matches!(a, PhhAction::StandPatOrDiscard { .. })).count(), 9);
+        assert_eq!(PhhAction::parse("p2 sd 7h2c").unwrap().to_string(), "p2 sd 7h2c");
+        assert_eq!(PhhHand::parse(&toml).unwrap(), phh);
+
+        let hands = |hand: &PokerHand| hand.active.iter().map(|p| p.hand.clone()).collect::<Vec<_>>();
+        assert_eq!(hands(&phh.verify().unwrap()), hands(&hand));
+        phh.seed = None;
+        assert_eq!(hands(&phh.verify().unwrap()), hands(&hand));
+    }
+
+    #[test]
+    fn test_short_deck_hand_with_antes_exports_and_replays() {
+        let mut hand = start(3, BettingStructure::NoLimit, GameVariant::ShortDeck, 10);
+        hand.act(6, PokerAction::Bet(30)).unwrap();
+        hand.act(2, PokerAction::Call(30)).unwrap();
+        hand.act(4, PokerAction::Fold).unwrap();
+        hand.settle().unwrap();
+
+        let mut phh = PhhHand::from_events(&hand.events).unwrap();
+        let toml = phh.to_toml();
+        assert!(toml.starts_with("variant = \"NS\"\nantes = [10, 10, 10]\nblinds_or_straddles = [0, 0, 0]\n"));
+        assert_eq!(PhhHand::parse(&toml).unwrap(), phh);
+
+        phh.seed = None;
+        let replayed = phh.verify().unwrap();
+        assert_eq!(replayed.ante, 10);
+        assert_eq!(replayed.board, hand.board);
+    }
+
+    #[test]
+    fn test_exported_hand_replays_with_same_result() {
+        let hand = played_hand();
+        let phh = PhhHand::from_events(&hand.events).unwrap();
+
+        let replayed = phh.verify().unwrap();
+        assert_eq!(replayed.board, hand.board);
+    }
+
+    #[test]
+    fn test_verify_detects_cards_that_do_not_match_the_seed() {
+        let mut phh = PhhHand::from_events(&played_hand().events).unwrap();
+        let used: Vec<Card> = phh.actions.iter().flat_map(|a| match a {
+            PhhAction::DealHole { cards, .. } | PhhAction::DealBoard { cards } => cards.clone(),
+            _ => vec![],
+        }).collect();
+        let mut deck = Deck::create();
+        let unused = (0..52).map(|_| deck.get_next_card()).find(|c| !used.contains(c)).unwrap();
+
+        let flop = phh.actions.iter_mut().find(|a| matches!(a, PhhAction::DealBoard { .. })).unwrap();
+        if let PhhAction::DealBoard { cards } = flop {
+            cards[0] = unused;
+        }
+
+        assert!(matches!(phh.verify(), Err(PhhError::Disagreement(_))));
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::BTreeMap;
+use crate::deck::Card;
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::blind::Blind;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::event::{HandEvent, HandEventKind};
+use crate::game::poker::hand::HandStage;
+
+pub struct ExportParams {
+    pub hand_number: u64,
+    pub table_name: String,
+    pub max_seats: u32,
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards.iter().map(|c| c.code()).collect::<Vec<_>>().join(" ")
+}
+
+/// `YYYY/MM/DD HH:MM:SS` in UTC, using Howard Hinnant's days-to-civil conversion
+fn format_timestamp(timestamp_ms: u64) -> String {
+    let seconds = timestamp_ms / 1000;
+    let days = (seconds / 86_400) as i64;
+    let time = seconds % 86_400;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}/{:02}/{:02} {:02}:{:02}:{:02}", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+fn stage_name(variant: GameVariant, stage: HandStage) -> &'static str {
+    // Betting before the first draw is named after it, as pre-flop betting is after the flop
+    match (variant, stage) {
+        (GameVariant::FiveCardDraw, _) => return "Draw",
+        (GameVariant::TripleDraw, HandStage::INIT | HandStage::FLOP) => return "First Draw",
+        (GameVariant::TripleDraw, HandStage::TURN) => return "Second Draw",
+        (GameVariant::TripleDraw, _) => return "Third Draw",
+        _ => {}
+    }
+    match (variant.is_stud(), stage) {
+        (false, HandStage::INIT | HandStage::FLOP) => "Flop",
+        (false, HandStage::TURN) => "Turn",
+        (false, _) => "River",
+        (true, HandStage::INIT) => "3rd Street",
+        (true, HandStage::FLOP) => "4th Street",
+        (true, HandStage::TURN) => "5th Street",
+        (true, HandStage::SIXTH) => "6th Street",
+        (true, HandStage::RIVER) => "7th Street",
+    }
+}
+
+/// Renders a finished hand's events as a PokerStars hand history, which standard replayers
+/// and trackers can import. Every player's hole cards are listed as dealt, in stud games
+/// street by street after the cards they already held, and in draw games after the cards
+/// they kept.
+pub fn to_pokerstars(events: &[HandEvent], params: &ExportParams) -> Result<String, PokerGameError> {
+    let started = events.iter().find_map(|e| match &e.kind {
+        HandEventKind::HandStarted { dealer_seat, blind_price, betting, variant, players, .. } => Some((e.timestamp_ms, *dealer_seat, *blind_price, *betting, *variant, players)),
+        _ => None,
+    });
+    if started.is_none() {
+        return Err(PokerGameError::HandNotStarted);
+    }
+    let (timestamp_ms, dealer_seat, blind_price, betting, variant, players) = started.expect("Expect the hand to be started");
+
+    let names: BTreeMap<u32, &str> = players.iter().map(|p| (p.seat, p.name.as_str())).collect();
+    let name = |seat: &u32| names.get(seat).copied().unwrap_or("unknown");
+    let mut stacks: BTreeMap<u32, u32> = players.iter().map(|p| (p.seat, p.stack)).collect();
+    let mut street_bets: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut blinds: BTreeMap<u32, Blind> = BTreeMap::new();
+    let mut folded: BTreeMap<u32, HandStage> = BTreeMap::new();
+    let mut shown: BTreeMap<u32, Vec<Card>> = BTreeMap::new();
+    let mut won: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut board: Vec<Card> = vec![];
+    let mut total_pot = 0;
+
+    let mut history = String::new();
+    let mut out = |line: String| { history.push_str(&line); history.push('\n'); };
+
+    // Limit games are labelled with their small and big bets rather than the blinds
+    let big_blind = blind_price * Blind::BIG as u32;
+    let (limit, stakes) = match betting {
+        BettingStructure::NoLimit => ("No Limit", (blind_price * Blind::SMALL as u32, big_blind)),
+        BettingStructure::PotLimit => ("Pot Limit", (blind_price * Blind::SMALL as u32, big_blind)),
+        BettingStructure::FixedLimit { .. } => ("Limit", (big_blind, 2 * big_blind)),
+    };
+    let game = match variant {
+        GameVariant::Holdem => "Hold'em",
+        GameVariant::ShortDeck => "6+ Hold'em",
+        GameVariant::Omaha => "Omaha",
+        GameVariant::OmahaHiLo => "Omaha Hi/Lo",
+        GameVariant::Stud => "7 Card Stud",
+        GameVariant::Razz => "Razz",
+        GameVariant::FiveCardDraw => "5 Card Draw",
+        GameVariant::TripleDraw => "Triple Draw 2-7 Lowball",
+    };
+    out(format!(
+        "PokerStars Hand #{}: {} {} ({}/{}) - {} UTC",
+        params.hand_number, game, limit, stakes.0, stakes.1, format_timestamp(timestamp_ms)
+    ));
+    out(format!("Table '{}' {}-max Seat #{} is the button", params.table_name, params.max_seats, dealer_seat));
+    for player in players {
+        out(format!("Seat {}: {} ({} in chips)", player.seat, player.name, player.stack));
+    }
+
+    let mut dealt_cards: BTreeMap<u32, Vec<Card>> = BTreeMap::new();
+    // A stud street deals a player face-down then face-up cards, written on one line
+    let dealt_to = |event: Option<&HandEvent>| match event.map(|e| &e.kind) {
+        Some(HandEventKind::HoleCardsDealt { seat, cards } | HandEventKind::UpCardsDealt { seat, cards }) => Some((*seat, cards.clone())),
+        _ => None,
+    };
+    for (index, event) in events.iter().enumerate() {
+        match &event.kind {
+            HandEventKind::HandStarted { .. } => {}
+            HandEventKind::AntePosted { seat, amount } => {
+                out(format!("{}: posts the ante {}", name(seat), amount));
+                *stacks.entry(*seat).or_default() -= amount;
+                total_pot += amount;
+            }
+            HandEventKind::BlindPosted { seat, blind, amount } => {
+                let label = if *blind == Blind::SMALL { "small" } else { "big" };
+                out(format!("{}: posts {} blind {}", name(seat), label, amount));
+                *stacks.entry(*seat).or_default() -= amount;
+                *street_bets.entry(*seat).or_default() += amount;
+                blinds.insert(*seat, *blind);
+                total_pot += amount;
+            }
+            HandEventKind::BringInPosted { seat, amount } => {
+                out(format!("{}: brings in for {}", name(seat), amount));
+                *stacks.entry(*seat).or_default() -= amount;
+                *street_bets.entry(*seat).or_default() += amount;
+                total_pot += amount;
+            }
+            HandEventKind::HoleCardsDealt { seat, cards: dealt } | HandEventKind::UpCardsDealt { seat, cards: dealt } => {
+                if index > 0 && dealt_to(events.get(index - 1)).is_some_and(|(previous, _)| previous == *seat) {
+                    continue;
+                }
+                let mut dealt = dealt.clone();
+                if let Some((_, next)) = dealt_to(events.get(index + 1)).filter(|(next, _)| next == seat) {
+                    dealt.extend(next);
+                }
+                if dealt_cards.is_empty() {
+                    let header = if variant.is_stud() {
+                        "*** 3rd STREET ***"
+                    } else if variant.has_draws() {
+                        "*** DEALING HANDS ***"
+                    } else {
+                        "*** HOLE CARDS ***"
+                    };
+                    out(String::from(header));
+                }
+                let held = dealt_cards.entry(*seat).or_default();
+                if held.is_empty() {
+                    out(format!("Dealt to {} [{}]", name(seat), cards(&dealt)));
+                } else {
+                    out(format!("Dealt to {} [{}] [{}]", name(seat), cards(held), cards(&dealt)));
+                }
+                held.extend(dealt.iter().copied());
+            }
+            HandEventKind::ActionTaken { seat, stage, action } => {
+                let highest_bet = street_bets.values().copied().max().unwrap_or(0);
+                let amount = action.amount();
+                let stack = stacks.entry(*seat).or_default();
+                *stack = stack.saturating_sub(amount);
+                let all_in = if amount > 0 && *stack == 0 { " and is all-in" } else { "" };
+                let bet = street_bets.entry(*seat).or_default();
+                *bet += amount;
+                total_pot += amount;
+
+                let line = match action {
+                    PokerAction::Fold => {
+                        folded.insert(*seat, *stage);
+                        String::from("folds")
+                    }
+                    PokerAction::Check => String::from("checks"),
+                    PokerAction::Call(amount) => format!("calls {}", amount),
+                    PokerAction::Bet(amount) => format!("bets {}", amount),
+                    PokerAction::Raise(_) => format!("raises {} to {}", bet.saturating_sub(highest_bet), bet),
+                };
+                out(format!("{}: {}{}", name(seat), line, all_in));
+            }
+            HandEventKind::DrawTaken { seat, count: 0, .. } => out(format!("{}: stands pat", name(seat))),
+            HandEventKind::DrawTaken { seat, count, .. } => {
+                let plural = if *count == 1 { "card" } else { "cards" };
+                match events.get(index + 1).map(|e| &e.kind) {
+                    Some(HandEventKind::CardsDrawn { discarded, .. }) => out(format!("{}: discards {} {} [{}]", name(seat), count, plural, cards(discarded))),
+                    _ => out(format!("{}: discards {} {}", name(seat), count, plural)),
+                }
+            }
+            HandEventKind::CardsDrawn { seat, discarded, drawn } => {
+                let held = dealt_cards.entry(*seat).or_default();
+                held.retain(|c| !discarded.contains(c));
+                if held.is_empty() {
+                    out(format!("Dealt to {} [{}]", name(seat), cards(drawn)));
+                } else {
+                    out(format!("Dealt to {} [{}] [{}]", name(seat), cards(held), cards(drawn)));
+                }
+                held.extend(drawn.iter().copied());
+            }
+            HandEventKind::BoardDealt { stage, cards: dealt } => {
+                street_bets.clear();
+                let header = match (variant.is_stud(), stage) {
+                    _ if variant.has_draws() => format!("*** {} ***", stage_name(variant, *stage).to_uppercase()),
+                    (true, HandStage::RIVER) => String::from("*** RIVER ***"),
+                    (true, _) => format!("*** {} ***", stage_name(variant, *stage).replace("Street", "STREET")),
+                    (false, HandStage::FLOP) => format!("*** FLOP *** [{}]", cards(dealt)),
+                    (false, _) => format!("*** {} *** [{}] [{}]", stage, cards(&board), cards(dealt)),
+                };
+                board.extend(dealt);
+                out(header);
+            }
+            HandEventKind::Showdown { seat, cards: hole } => {
+                if shown.is_empty() {
+                    out(String::from("*** SHOW DOWN ***"));
+                }
+                out(format!("{}: shows [{}]", name(seat), cards(hole)));
+                shown.insert(*seat, hole.clone());
+            }
+            HandEventKind::PotAwarded { seat, amount } => {
+                out(format!("{} collected {} from pot", name(seat), amount));
+                *won.entry(*seat).or_default() += amount;
+            }
+        }
+    }
+
+    out(String::from("*** SUMMARY ***"));
+    out(format!("Total pot {} | Rake 0", total_pot));
+    if !board.is_empty() {
+        out(format!("Board [{}]", cards(&board)));
+    }
+
+    for player in players {
+        let mut labels = String::new();
+        if player.seat == dealer_seat {
+            labels.push_str(" (button)");
+        }
+        match blinds.get(&player.seat) {
+            Some(Blind::SMALL) => labels.push_str(" (small blind)"),
+            Some(Blind::BIG) => labels.push_str(" (big blind)"),
+            _ => {}
+        }
+
+        let outcome = match (folded.get(&player.seat), shown.get(&player.seat), won.get(&player.seat)) {
+            (Some(HandStage::INIT), _, _) if !variant.is_stud() => format!("folded before {}", stage_name(variant, HandStage::INIT)),
+            (Some(stage), _, _) => format!("folded on the {}", stage_name(variant, *stage)),
+            (None, Some(hole), Some(amount)) => format!("showed [{}] and won ({})", cards(hole), amount),
+            (None, Some(hole), None) => format!("showed [{}] and lost", cards(hole)),
+            (None, None, Some(amount)) => format!("collected ({})", amount),
+            (None, None, None) => String::from("mucked"),
+        };
+        out(format!("Seat {}: {}{} {}", player.seat, player.name, labels, outcome));
+    }
+
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+    fn params() -> ExportParams {
+        ExportParams { hand_number: 42, table_name: String::from("LLM Arena"), max_seats: 6 }
+    }
+
+    fn hand(betting: BettingStructure, variant: GameVariant, ante: u32) -> PokerHand {
+        let players = funded_players(&[(1, "Alice"), (2, "Bob"), (3, "Carol")], 1000);
+        PokerHand::start(StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 8, betting, variant, ante }).unwrap()
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970/01/01 00:00:00");
+        assert_eq!(format_timestamp(1_709_210_096_000), "2024/02/29 12:34:56");
+    }
+
+    #[test]
+    fn test_requires_hand_started() {
+        assert!(matches!(to_pokerstars(&[], &params()), Err(PokerGameError::HandNotStarted)));
+    }
+
+    #[test]
+    fn test_exports_full_hand() {
+        let mut hand = hand(BettingStructure::NoLimit, GameVariant::Holdem, 0);
+        hand.act(1, PokerAction::Raise(30)).unwrap();
+        hand.act(2, PokerAction::Fold).unwrap();
+        hand.act(3, PokerAction::Call(20)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.act(1, PokerAction::Bet(40)).unwrap();
+        hand.act(3, PokerAction::Raise(120)).unwrap();
+        hand.act(1, PokerAction::Call(80)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.show_down();
+        hand.award_pot(3, hand.pot).unwrap();
+
+        let history = to_pokerstars(&hand.events, &params()).unwrap();
+        let lines: Vec<&str> = history.lines().collect();
+
+        assert!(lines[0].starts_with("PokerStars Hand #42: Hold'em No Limit (5/10) - "));
+        assert_eq!(lines[1], "Table 'LLM Arena' 6-max Seat #1 is the button");
+        assert_eq!(lines[2], "Seat 1: Alice (1000 in chips)");
+        assert_eq!(lines[5], "Bob: posts small blind 5");
+        assert_eq!(lines[6], "Carol: posts big blind 10");
+        assert_eq!(lines[7], "*** HOLE CARDS ***");
+        assert_eq!(lines[8], format!("Dealt to Alice [{}]", cards(&hand.active[0].hand)));
+        assert!(lines.contains(&"Alice: raises 20 to 30"));
+        assert!(lines.contains(&"Bob: folds"));
+        assert!(lines.contains(&"Carol: calls 20"));
+        assert!(lines.contains(&format!("*** FLOP *** [{}]", cards(&hand.board[..3])).as_str()));
+        assert!(lines.contains(&"Carol: raises 80 to 120"));
+        assert!(lines.contains(&format!("*** TURN *** [{}] [{}]", cards(&hand.board[..3]), hand.board[3].code()).as_str()));
+        assert!(lines.contains(&"*** SHOW DOWN ***"));
+        assert!(lines.contains(&"Carol collected 305 from pot"));
+        assert!(lines.contains(&"Total pot 305 | Rake 0"));
+        assert!(lines.contains(&"Seat 2: Bob (small blind) folded before Flop"));
+        assert!(lines.contains(&format!("Seat 3: Carol (big blind) showed [{}] and won (305)", cards(&hand.active[1].hand)).as_str()));
+        assert!(lines.contains(&format!("Seat 1: Alice (button) showed [{}] and lost", cards(&hand.active[0].hand)).as_str()));
+    }
+
+    #[test]
+    fn test_exports_stud_streets() {
+        let mut hand = hand(BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::Stud, 1);
+        let bring_in = hand.events.iter().find_map(|e| match e.kind {
+            HandEventKind::BringInPosted { seat, .. } => Some(seat),
+            _ => None,
+        }).unwrap();
+        hand.deal_next_stage().unwrap();
+
+        let history = to_pokerstars(&hand.events, &params()).unwrap();
+        let lines: Vec<&str> = history.lines().collect();
+        let alice = &hand.active[0].hand;
+
+        assert!(lines[0].starts_with("PokerStars Hand #42: 7 Card Stud Limit (10/20) - "));
+        assert_eq!(lines[5], "Alice: posts the ante 1");
+        assert_eq!(lines[8], "*** 3rd STREET ***");
+        assert_eq!(lines[9], format!("Dealt to Alice [{}]", cards(&alice[..3])));
+        assert_eq!(lines[12], format!("{}: brings in for 5", ["Alice", "Bob", "Carol"][bring_in as usize - 1]));
+        assert_eq!(lines[13], "*** 4th STREET ***");
+        assert_eq!(lines[14], format!("Dealt to Alice [{}] [{}]", cards(&alice[..3]), alice[3].code()));
+    }
+
+    #[test]
+    fn test_exports_draws() {
+        let mut hand = hand(BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::TripleDraw, 0);
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.act(2, PokerAction::Call(5)).unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.deal_next_stage().unwrap();
+        let bob = hand.active[0].hand.clone();
+        let drawn = hand.draw(2, &bob[..2]).unwrap();
+        hand.draw(3, &[]).unwrap();
+
+        let history = to_pokerstars(&hand.events, &params()).unwrap();
+        let lines: Vec<&str> = history.lines().collect();
+
+        assert!(lines[0].starts_with("PokerStars Hand #42: Triple Draw 2-7 Lowball Limit (10/20) - "));
+        assert_eq!(lines[7], "*** DEALING HANDS ***");
+        assert!(lines.contains(&"*** FIRST DRAW ***"));
+        assert!(lines.contains(&format!("Bob: discards 2 cards [{}]", cards(&bob[..2])).as_str()));
+        assert!(lines.contains(&format!("Dealt to Bob [{}] [{}]", cards(&bob[2..]), cards(&drawn)).as_str()));
+        assert!(lines.contains(&"Carol: stands pat"));
+        assert!(lines.contains(&"Seat 1: Alice (button) folded before First Draw"));
+    }
+
+    #[test]
+    fn test_exports_hand_won_without_showdown() {
+        let mut hand = hand(BettingStructure::NoLimit, GameVariant::Holdem, 0);
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.act(2, PokerAction::Fold).unwrap();
+        hand.award_pot(3, hand.pot).unwrap();
+
+        let history = to_pokerstars(&hand.events, &params()).unwrap();
+        assert!(!history.contains("*** SHOW DOWN ***"));
+        assert!(!history.contains("Board ["));
+        assert!(history.contains("Seat 3: Carol (big blind) collected (15)"));
+        assert!(history.contains("Seat 1: Alice (button) folded before Flop"));
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::game::poker::match_runner::MatchReport;
+
+const INITIAL_ELO: f64 = 1500.0;
+const ELO_K: f64 = 32.0;
+const INITIAL_MU: f64 = 25.0;
+const INITIAL_SIGMA: f64 = INITIAL_MU / 3.0;
+const BETA: f64 = INITIAL_SIGMA / 2.0;
+/// Lower bound on the factor a variance can shrink by in one update
+const KAPPA: f64 = 0.0001;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub player_id: usize,
+    pub name: String,
+    pub elo: f64,
+    pub mu: f64,
+    pub sigma: f64,
+    pub matches: u32,
+}
+
+impl Rating {
+    pub fn create(player_id: usize, name: &str) -> Self {
+        Rating {
+            player_id,
+            name: name.to_string(),
+            elo: INITIAL_ELO,
+            mu: INITIAL_MU,
+            sigma: INITIAL_SIGMA,
+            matches: 0,
+        }
+    }
+
+    /// Conservative skill estimate used for ranking: the skill is above this with ~99% confidence
+    pub fn ordinal(&self) -> f64 {
+        self.mu - 3.0 * self.sigma
+    }
+}
+
+/// Leaderboard of agents keyed by `Player.id`.
+///
+/// Heads-up results update an Elo rating; every result, including multiway finishing orders,
+/// updates a Plackett-Luce skill estimate (Weng-Lin) whose `sigma` is the uncertainty.
+#[derive(Debug, Clone, Default)]
+pub struct RatingLadder {
+    pub ratings: BTreeMap<usize, Rating>,
+}
+
+impl RatingLadder {
+    pub fn new() -> Self {
+        RatingLadder { ratings: BTreeMap::new() }
+    }
+
+    pub fn register(&mut self, player_id: usize, name: &str) {
+        self.ratings.entry(player_id)
+            .and_modify(|r| r.name = name.to_string())
+            .or_insert_with(|| Rating::create(player_id, name));
+    }
+
+    fn rating(&mut self, player_id: usize) -> &mut Rating {
+        self.ratings.entry(player_id).or_insert_with(|| Rating::create(player_id, &player_id.to_string()))
+    }
+
+    /// Records a heads-up result, `score_a` being 1.0 for a win of `player_a`, 0.5 for a draw
+    /// and 0.0 for a loss.
+    pub fn record_heads_up(&mut self, player_a: usize, player_b: usize, score_a: f64) {
+        let elo_a = self.rating(player_a).elo;
+        let elo_b = self.rating(player_b).elo;
+        let expected_a = 1.0 / (1.0 + 10f64.powf((elo_b - elo_a) / 400.0));
+
+        self.rating(player_a).elo += ELO_K * (score_a - expected_a);
+        self.rating(player_b).elo += ELO_K * (expected_a - score_a);
+
+        let ranks = if score_a > 0.5 {
+            [(player_a, 1), (player_b, 2)]
+        } else if score_a < 0.5 {
+            [(player_a, 2), (player_b, 1)]
+        } else {
+            [(player_a, 1), (player_b, 1)]
+        };
+        self.record_finishing_order(&ranks);
+    }
+
+    /// Records a multiway result as `(player_id, rank)` pairs, rank 1 being the best.
+    /// Players sharing a rank are treated as tied.
+    pub fn record_finishing_order(&mut self, ranks: &[(usize, u32)]) {
+        if ranks.len() < 2 {
+            return;
+        }
+
+        let players: Vec<(u32, f64, f64)> = ranks.iter()
+            .map(|&(id, rank)| {
+                let rating = self.rating(id);
+                (rank, rating.mu, rating.sigma)
+            })
+            .collect();
+
+        let c = players.iter().map(|&(_, _, sigma)| sigma * sigma + BETA * BETA).sum::<f64>().sqrt();
+        let strength: Vec<f64> = players.iter().map(|&(_, mu, _)| (mu / c).exp()).collect();
+        // Sum of strengths of everyone who finished level with or behind each player
+        let sum_q: Vec<f64> = players.iter()
+            .map(|&(rank_q, _, _)| players.iter().zip(&strength)
+                .filter(|((rank, _, _), _)| *rank >= rank_q)
+                .map(|(_, s)| s)
+                .sum())
+            .collect();
+        let ties: Vec<f64> = players.iter()
+            .map(|&(rank_q, _, _)| players.iter().filter(|(rank, _, _)| *rank == rank_q).count() as f64)
+            .collect();
+
+        for (i, &(rank_i, mu, sigma)) in players.iter().enumerate() {
+            let mut omega = 0.0;
+            let mut delta = 0.0;
+
+            for (q, &(rank_q, _, _)) in players.iter().enumerate() {
+                if rank_q > rank_i {
+                    continue;
+                }
+                let quotient = strength[i] / sum_q[q];
+                omega += if q == i { 1.0 - quotient } else { -quotient } / ties[q];
+                delta += quotient * (1.0 - quotient) / ties[q];
+            }
+
+            let variance = sigma * sigma;
+            let gamma = sigma / c;
+            let rating = self.rating(ranks[i].0);
+            rating.mu = mu + omega * variance / c;
+            rating.sigma = (variance * (1.0 - gamma * delta * variance / (c * c)).max(KAPPA)).sqrt();
+            rating.matches += 1;
+        }
+    }
+
+    /// Records a finished match, ranking its players by net chips won.
+    pub fn record_match(&mut self, report: &MatchReport) {
+        for (&player_id, name) in &report.names {
+            self.register(player_id, name);
+        }
+
+        let mut results: Vec<(usize, i64)> = report.agent_stats().iter()
+            .map(|a| (a.player_id, a.net_chips))
+            .collect();
+
+        if let [(a, net_a), (b, net_b)] = results[..] {
+            let score_a = match net_a.cmp(&net_b) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Less => 0.0,
+                std::cmp::Ordering::Equal => 0.5,
+            };
+            self.record_heads_up(a, b, score_a);
+            return;
+        }
+
+        results.sort_by_key(|&(_, net)| std::cmp::Reverse(net));
+        let mut ranks = Vec::with_capacity(results.len());
+        for (index, &(player_id, net)) in results.iter().enumerate() {
+            let rank = match ranks.last() {
+                Some(&(_, previous_rank)) if results[index - 1].1 == net => previous_rank,
+                _ => index as u32 + 1,
+            };
+            ranks.push((player_id, rank));
+        }
+        self.record_finishing_order(&ranks);
+    }
+
+    pub fn leaderboard(&self) -> Vec<&Rating> {
+        let mut ratings: Vec<&Rating> = self.ratings.values().collect();
+        ratings.sort_by(|a, b| b.ordinal().total_cmp(&a.ordinal()));
+        ratings
+    }
+
+    /// Loads a ladder saved by `save`; a missing file yields an empty ladder.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(RatingLadder::new());
+        }
+
+        let mut ladder = RatingLadder::new();
+        for line in fs::read_to_string(path)?.lines().filter(|l| !l.trim().is_empty()) {
+            let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid rating line: {}", line));
+            let fields: Vec<&str> = line.splitn(6, '\t').collect();
+            if fields.len() != 6 {
+                return Err(invalid());
+            }
+
+            let rating = Rating {
+                player_id: fields[0].parse().map_err(|_| invalid())?,
+                elo: fields[1].parse().map_err(|_| invalid())?,
+                mu: fields[2].parse().map_err(|_| invalid())?,
+                sigma: fields[3].parse().map_err(|_| invalid())?,
+                matches: fields[4].parse().map_err(|_| invalid())?,
+                name: fields[5].to_string(),
+            };
+            ladder.ratings.insert(rating.player_id, rating);
+        }
+
+        Ok(ladder)
+    }
+
+    /// Saves one tab-separated line per player: id, elo, mu, sigma, matches, name.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let lines: String = self.ratings.values()
+            .map(|r| format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\n",
+                r.player_id, r.elo, r.mu, r.sigma, r.matches, r.name.replace(['\n', '\r'], " ")
+            ))
+            .collect();
+
+        fs::write(path, lines)
+    }
+}
+
+impl fmt::Display for RatingLadder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>4} {:<20} {:>8} {:>16} {:>8} {:>8}", "#", "Player", "Rating", "Skill", "Elo", "Matches")?;
+
+        for (index, rating) in self.leaderboard().iter().enumerate() {
+            writeln!(
+                f, "{:>4} {:<20} {:>8.2} {:>16} {:>8.0} {:>8}",
+                index + 1, rating.name, rating.ordinal(),
+                format!("{:.2} ± {:.2}", rating.mu, rating.sigma), rating.elo, rating.matches
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::match_runner::HandResult;
+
+    fn report(winnings: &[(usize, i64)]) -> MatchReport {
+        MatchReport {
+            big_blind: 10,
+            names: winnings.iter().map(|&(id, _)| (id, format!("P{}", id))).collect(),
+            results: vec![HandResult { hand_number: 0, seed: 0, winnings: winnings.iter().copied().collect() }],
+        }
+    }
+
+    #[test]
+    fn test_heads_up_win_moves_elo_symmetrically() {
+        let mut ladder = RatingLadder::new();
+        ladder.record_heads_up(1, 2, 1.0);
+
+        assert!((ladder.ratings[&1].elo - 1516.0).abs() < 1e-9);
+        assert!((ladder.ratings[&2].elo - 1484.0).abs() < 1e-9);
+        assert!(ladder.ratings[&1].mu > ladder.ratings[&2].mu);
+        assert!(ladder.ratings[&1].sigma < INITIAL_SIGMA);
+    }
+
+    #[test]
+    fn test_draw_between_equals_keeps_elo() {
+        let mut ladder = RatingLadder::new();
+        ladder.record_heads_up(1, 2, 0.5);
+
+        assert!((ladder.ratings[&1].elo - INITIAL_ELO).abs() < 1e-9);
+        assert!((ladder.ratings[&1].mu - ladder.ratings[&2].mu).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_finishing_order_ranks_players() {
+        let mut ladder = RatingLadder::new();
+        for _ in 0..5 {
+            ladder.record_finishing_order(&[(3, 1), (1, 2), (2, 3)]);
+        }
+
+        let order: Vec<usize> = ladder.leaderboard().iter().map(|r| r.player_id).collect();
+        assert_eq!(order, vec![3, 1, 2]);
+        assert!(ladder.ratings.values().all(|r| r.matches == 5 && r.sigma < INITIAL_SIGMA));
+    }
+
+    #[test]
+    fn test_record_match_uses_net_chips() {
+        let mut ladder = RatingLadder::new();
+        ladder.record_match(&report(&[(1, -50), (2, 50)]));
+        assert!(ladder.ratings[&2].elo > ladder.ratings[&1].elo);
+        assert_eq!(ladder.ratings[&2].name, "P2");
+
+        ladder.record_match(&report(&[(1, 30), (2, -10), (3, -20)]));
+        assert_eq!(ladder.ratings[&3].matches, 1);
+        assert!(ladder.ratings[&1].mu > ladder.ratings[&3].mu);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let mut ladder = RatingLadder::new();
+        ladder.register(1, "Cautious GPT");
+        ladder.register(2, "Aggressive\tBot");
+        ladder.record_heads_up(1, 2, 1.0);
+
+        let path = std::env::temp_dir().join(format!("llm_holdem_ratings_{}.tsv", std::process::id()));
+        ladder.save(&path).unwrap();
+        let loaded = RatingLadder::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.ratings, ladder.ratings);
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let ladder = RatingLadder::load(Path::new("/nonexistent/ratings.tsv")).unwrap();
+        assert!(ladder.ratings.is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::deck::Card;
+use crate::game::poker::action::{PlayerAction, PokerAction};
+use crate::game::poker::agent::Agent;
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::event::{HandEvent, HandEventKind};
+use crate::game::poker::game_player::PokerGamePlayer;
+use crate::game::poker::hand::{HandStage, PokerHand, StartHandParams};
+use crate::game::poker::view::PlayerView;
+use crate::player::Player;
+
+/// Rebuilds every state of a recorded hand from its seed and action log.
+///
+/// `position` counts the recorded actions applied so far; the state at a position is the
+/// decision point of the next recorded action, with the board of its street already dealt.
+pub struct HandReplay {
+    pub blind_price: u32,
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    pub ante: u32,
+    pub players: Vec<PokerGamePlayer>,
+    pub dealer_seat: u32,
+    pub seed: u64,
+    pub actions: Vec<PlayerAction>,
+    /// Seat, street and discards of every draw exchanging cards, replayed before the street is
+    /// bet; the other players stand pat
+    pub draws: Vec<(u32, HandStage, Vec<Card>)>,
+    /// Street the hand reached, which may be past the last action when players were all in
+    pub final_stage: HandStage,
+    pub showdown: bool,
+    pub awards: Vec<(u32, u32)>,
+    pub position: usize,
+}
+
+impl HandReplay {
+    pub fn from_events(events: &[HandEvent]) -> Result<HandReplay, PokerGameError> {
+        let mut replay = None;
+
+        for event in events {
+            match (&mut replay, &event.kind) {
+                (None, HandEventKind::HandStarted { seed, dealer_seat, blind_price, betting, variant, ante, players }) => {
+                    replay = Some(HandReplay {
+                        blind_price: *blind_price,
+                        betting: *betting,
+                        variant: *variant,
+                        ante: *ante,
+                        players: players.iter().map(|p| {
+                            let mut player = PokerGamePlayer::create(p.seat, Player::create(p.player_id, &p.name, ""));
+                            player.cash_amount = p.stack;
+                            player
+                        }).collect(),
+                        dealer_seat: *dealer_seat,
+                        seed: *seed,
+                        actions: vec![],
+                        draws: vec![],
+                        final_stage: HandStage::INIT,
+                        showdown: false,
+                        awards: vec![],
+                        position: 0,
+                    });
+                }
+                (Some(replay), HandEventKind::ActionTaken { seat, stage, action }) =>
+                    replay.actions.push(PlayerAction { seat: *seat, stage: *stage, action: *action }),
+                (Some(replay), HandEventKind::BoardDealt { stage, .. }) => replay.final_stage = *stage,
+                // Draws follow the street's deal, so the street reached is theirs
+                (Some(replay), HandEventKind::CardsDrawn { seat, discarded, .. }) =>
+                    replay.draws.push((*seat, replay.final_stage, discarded.clone())),
+                (Some(replay), HandEventKind::Showdown { .. }) => replay.showdown = true,
+                (Some(replay), HandEventKind::PotAwarded { seat, amount }) => replay.awards.push((*seat, *amount)),
+                _ => {}
+            }
+        }
+
+        replay.ok_or(PokerGameError::HandNotStarted)
+    }
+
+    /// Deals up to `stage`, each player drawing on the way as recorded
+    fn deal_until(&self, hand: &mut PokerHand, stage: HandStage) -> Result<(), PokerGameError> {
+        self.draw_recorded(hand)?;
+        while hand.stage < stage {
+            hand.deal_next_stage()?;
+            self.draw_recorded(hand)?;
+        }
+        Ok(())
+    }
+
+    fn draw_recorded(&self, hand: &mut PokerHand) -> Result<(), PokerGameError> {
+        while let Some(seat) = hand.next_to_draw() {
+            let discards = self.draws.iter()
+                .find(|(drawn, stage, _)| *drawn == seat && *stage == hand.stage)
+                .map(|(_, _, discards)| discards.clone())
+                .unwrap_or_default();
+            hand.draw(seat, &discards)?;
+        }
+        Ok(())
+    }
+
+    /// The hand as it stood at `position`
+    pub fn state(&self) -> Result<PokerHand, PokerGameError> {
+        let mut hand = PokerHand::start(StartHandParams {
+            blind_price: self.blind_price,
+            players: self.players.clone(),
+            dealer_seat: self.dealer_seat,
+            seed: self.seed,
+            betting: self.betting,
+            variant: self.variant,
+            ante: self.ante,
+        })?;
+
+        for recorded in &self.actions[..self.position] {
+            self.deal_until(&mut hand, recorded.stage)?;
+            hand.act(recorded.seat, recorded.action)?;
+        }
+
+        match self.decision() {
+            Some(next) => self.deal_until(&mut hand, next.stage)?,
+            None => {
+                self.deal_until(&mut hand, self.final_stage)?;
+                if self.showdown {
+                    hand.show_down();
+                }
+                for &(seat, amount) in &self.awards {
+                    hand.award_pot(seat, amount)?;
+                }
+            }
+        }
+
+        Ok(hand)
+    }
+
+    /// The recorded action about to be taken, `None` once the hand is over
+    pub fn decision(&self) -> Option<&PlayerAction> {
+        self.actions.get(self.position)
+    }
+
+    pub fn stage(&self) -> HandStage {
+        self.decision().map(|a| a.stage).unwrap_or(self.final_stage)
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.actions.len());
+    }
+
+    pub fn step_forward(&mut self) -> bool {
+        if self.position >= self.actions.len() {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        true
+    }
+
+    /// Moves to the first decision of the next street, or to the end of the hand
+    pub fn next_street(&mut self) {
+        let stage = self.stage();
+        self.position = self.actions.iter().enumerate()
+            .skip(self.position)
+            .find(|(_, a)| a.stage > stage)
+            .map(|(index, _)| index)
+            .unwrap_or(self.actions.len());
+    }
+
+    /// Moves to the first decision of the current street, or of the previous one when
+    /// already there
+    pub fn previous_street(&mut self) {
+        let first_of_current = self.actions.iter().position(|a| a.stage >= self.stage()).unwrap_or(self.actions.len());
+        let target_stage = if self.position > first_of_current || first_of_current == 0 {
+            self.stage()
+        } else {
+            match self.actions[..first_of_current].last() {
+                Some(previous) => previous.stage,
+                None => return,
+            }
+        };
+
+        self.position = self.actions.iter().position(|a| a.stage >= target_stage).unwrap_or(0);
+    }
+
+    /// What the player facing the current decision saw
+    pub fn view(&self) -> Result<PlayerView, PokerGameError> {
+        let decision = self.decision().ok_or(PokerGameError::NoPendingDecision)?;
+        let hand = self.state()?;
+        PlayerView::create(&hand, decision.seat, hand.stage, None)
+    }
+
+    /// Asks `agent` what it would have done at the current decision instead
+    pub fn requery(&self, agent: &mut dyn Agent) -> Result<PokerAction, PokerGameError> {
+        Ok(agent.decide(&self.view()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::game_player::funded_players;
+
+    struct AlwaysFold;
+
+    impl Agent for AlwaysFold {
+        fn name(&self) -> &str { "AlwaysFold" }
+        fn decide(&mut self, _view: &PlayerView) -> PokerAction { PokerAction::Fold }
+    }
+
+    fn start(betting: BettingStructure, variant: GameVariant) -> PokerHand {
+        let players = funded_players(&[(1, "P1"), (2, "P2"), (3, "P3")], 1000);
+        PokerHand::start(StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 21, betting, variant, ante: 0 }).unwrap()
+    }
+
+    fn recorded_hand() -> PokerHand {
+        let mut hand = start(BettingStructure::NoLimit, GameVariant::Holdem);
+        hand.act(1, PokerAction::Raise(30)).unwrap();
+        hand.act(2, PokerAction::Call(25)).unwrap();
+        hand.act(3, PokerAction::Call(20)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.act(2, PokerAction::Check).unwrap();
+        hand.act(3, PokerAction::Bet(50)).unwrap();
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.act(2, PokerAction::Call(50)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.show_down();
+        hand.award_pot(3, hand.pot).unwrap();
+        hand
+    }
+
+    #[test]
+    fn test_from_events_reads_seed_and_actions() {
+        let replay = HandReplay::from_events(&recorded_hand().events).unwrap();
+
+        assert_eq!(replay.seed, 21);
+        assert_eq!(replay.actions.len(), 7);
+        assert_eq!(replay.final_stage, HandStage::RIVER);
+        assert!(replay.showdown);
+        assert_eq!(replay.awards, vec![(3, 190)]);
+        assert!(matches!(HandReplay::from_events(&[]), Err(PokerGameError::HandNotStarted)));
+    }
+
+    #[test]
+    fn test_end_state_matches_recorded_hand() {
+        let recorded = recorded_hand();
+        let mut replay = HandReplay::from_events(&recorded.events).unwrap();
+        replay.seek(usize::MAX);
+
+        let state = replay.state().unwrap();
+        let kinds = |events: &[HandEvent]| events.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(kinds(&state.events), kinds(&recorded.events));
+        assert_eq!(state.active[1].cash_amount, recorded.active[1].cash_amount);
+    }
+
+    #[test]
+    fn test_step_forward_and_back() {
+        let mut replay = HandReplay::from_events(&recorded_hand().events).unwrap();
+
+        assert!(!replay.step_back());
+        assert!(replay.step_forward());
+        assert_eq!(replay.state().unwrap().pot, 45);
+        assert_eq!(replay.decision().unwrap().seat, 2);
+
+        assert!(replay.step_back());
+        assert_eq!(replay.state().unwrap().pot, 15);
+
+        replay.seek(7);
+        assert!(!replay.step_forward());
+    }
+
+    #[test]
+    fn test_street_navigation() {
+        let mut replay = HandReplay::from_events(&recorded_hand().events).unwrap();
+
+        replay.next_street();
+        assert_eq!(replay.position, 3);
+        assert_eq!(replay.stage(), HandStage::FLOP);
+        assert_eq!(replay.state().unwrap().stage, HandStage::FLOP);
+
+        replay.step_forward();
+        replay.previous_street();
+        assert_eq!(replay.position, 3);
+        replay.previous_street();
+        assert_eq!(replay.position, 0);
+
+        replay.seek(3);
+        replay.next_street();
+        assert_eq!(replay.position, 7);
+        assert_eq!(replay.stage(), HandStage::RIVER);
+    }
+
+    #[test]
+    fn test_requery_another_agent() {
+        let recorded = recorded_hand();
+        let mut replay = HandReplay::from_events(&recorded.events).unwrap();
+        replay.seek(4);
+
+        let view = replay.view().unwrap();
+        assert_eq!(view.seat, 3);
+        assert_eq!(view.board, recorded.board[..3].to_vec());
+        assert_eq!(replay.decision().unwrap().action, PokerAction::Bet(50));
+        assert_eq!(replay.requery(&mut AlwaysFold).unwrap(), PokerAction::Fold);
+
+        replay.seek(usize::MAX);
+        assert!(matches!(replay.requery(&mut AlwaysFold), Err(PokerGameError::NoPendingDecision)));
+    }
+
+    #[test]
+    fn test_state_replays_the_draws() {
+        let mut hand = start(BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::TripleDraw);
+        hand.act(1, PokerAction::Call(10)).unwrap();
+        hand.act(2, PokerAction::Call(5)).unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.deal_next_stage().unwrap();
+        for (seat, count) in [(2, 1), (3, 0), (1, 2)] {
+            let discards = hand.active.iter().find(|p| p.seat == seat).unwrap().hand[..count].to_vec();
+            hand.draw(seat, &discards).unwrap();
+        }
+        hand.act(2, PokerAction::Bet(10)).unwrap();
+        hand.act(3, PokerAction::Fold).unwrap();
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.settle().unwrap();
+
+        let mut replay = HandReplay::from_events(&hand.events).unwrap();
+        // Standing pat exchanges no cards, so only two draws are recorded
+        assert_eq!(replay.draws.len(), 2);
+
+        // The decision after the draw sees the replacement cards
+        replay.seek(3);
+        let state = replay.state().unwrap();
+        assert_eq!(state.next_to_draw(), None);
+        assert_eq!(state.active[1].hand, hand.active[0].hand);
+
+        replay.seek(usize::MAX);
+        let kinds = |events: &[HandEvent]| events.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(kinds(&replay.state().unwrap().events), kinds(&hand.events));
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Message, WebSocket};
+use crate::deck::Card;
+use crate::game::poker::action::{parse_discards, PokerAction};
+use crate::game::poker::agent::Agent;
+use crate::game::poker::event::{HandEvent, HandEventKind, HandEventListener};
+use crate::game::poker::view::PlayerView;
+
+/// What spectators learn about hole cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoleCards {
+    /// Only cards shown down are ever seen
+    Hidden,
+    /// Every deal is sent once the hand is over
+    AfterHand,
+}
+
+/// Sent by the server as JSON text frames, e.g. `{"type":"turn","view":{...}}`.
+/// Events and views use the shapes of the `serde` feature.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// First message on every connection; `seat` is empty for spectators
+    Welcome { seat: Option<u32> },
+    Event { event: HandEvent },
+    /// The seat must answer with an action, or with a discard when `view.drawing` is set
+    Turn { view: PlayerView },
+    HandEnded,
+}
+
+/// Sent by a seat after a `turn`: either JSON, `{"type":"action","action":{"type":"raise","amount":40}}`
+/// or `{"type":"discard","cards":["7s","Th"]}`, or plain text such as `raise 40` or `discard 7s Th`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Action { action: PokerAction },
+    Discard { cards: Vec<Card> },
+}
+
+impl ClientMessage {
+    pub fn parse(text: &str) -> Option<ClientMessage> {
+        serde_json::from_str(text).ok()
+            .or_else(|| PokerAction::parse(text).map(|action| ClientMessage::Action { action }))
+            .or_else(|| parse_discards(text).map(|cards| ClientMessage::Discard { cards }))
+    }
+}
+
+type Socket = WebSocket<TcpStream>;
+
+fn send(socket: &mut Socket, message: &ServerMessage) -> bool {
+    let text = serde_json::to_string(message).expect("Expect messages to serialise");
+    socket.send(Message::text(text)).is_ok()
+}
+
+#[derive(Default)]
+struct HubState {
+    spectators: Vec<Socket>,
+    seats: BTreeMap<u32, Socket>,
+    /// What a client must give as `?token=` to take each remote seat
+    tokens: BTreeMap<u32, String>,
+    /// The seat whose socket is out of `seats` while it answers its turn
+    answering: Option<u32>,
+    /// Hole cards held back from spectators until the hand ends
+    delayed: Vec<HandEvent>,
+}
+
+/// The connections of one table. Feed it the hand's events as a listener and seat
+/// `RemoteAgent`s on it; everything is relayed to whoever is connected at the time.
+#[derive(Clone)]
+pub struct Hub {
+    pub hole_cards: HoleCards,
+    state: Arc<Mutex<HubState>>,
+}
+
+impl Hub {
+    pub fn new(hole_cards: HoleCards) -> Hub {
+        Hub { hole_cards, state: Arc::default() }
+    }
+
+    fn state(&self) -> MutexGuard<'_, HubState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn spectator_count(&self) -> usize {
+        self.state().spectators.len()
+    }
+
+    pub fn connected_seats(&self) -> Vec<u32> {
+        self.state().seats.keys().copied().collect()
+    }
+
+    /// Waits until every seat in `seats` is connected, for at most `timeout`
+    pub fn wait_for_seats(&self, seats: &[u32], timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let connected = self.connected_seats();
+            if seats.iter().all(|seat| connected.contains(seat)) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// The token a client needs to connect to `seat`, once a `RemoteAgent` plays it
+    pub fn token(&self, seat: u32) -> Option<String> {
+        self.state().tokens.get(&seat).cloned()
+    }
+
+    /// Whether a client giving `token` may take `seat` now: the seat must be played remotely,
+    /// the token its own and the seat not connected already
+    fn admit(&self, seat: u32, token: Option<&str>) -> Result<(), StatusCode> {
+        let state = self.state();
+        match state.tokens.get(&seat) {
+            None => Err(StatusCode::NOT_FOUND),
+            Some(expected) if token != Some(expected.as_str()) => Err(StatusCode::UNAUTHORIZED),
+            Some(_) if state.seats.contains_key(&seat) || state.answering == Some(seat) => Err(StatusCode::CONFLICT),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Joins an accepted connection, `seat` for a player admitted by `admit`, which lost the
+    /// seat if another client took it during the handshake
+    fn join(&self, seat: Option<u32>, mut socket: Socket) {
+        let mut state = self.state();
+        if seat.is_some_and(|seat| state.seats.contains_key(&seat) || state.answering == Some(seat)) {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            return;
+        }
+        if !send(&mut socket, &ServerMessage::Welcome { seat }) {
+            return;
+        }
+        match seat {
+            Some(seat) => { state.seats.insert(seat, socket); }
+            None => state.spectators.push(socket),
+        }
+    }
+
+    /// Sends the held-back hole cards to spectators and tells everyone the hand is over
+    pub fn end_hand(&self) {
+        let mut state = self.state();
+        let delayed: Vec<HandEvent> = state.delayed.drain(..).collect();
+        state.spectators.retain_mut(|socket| {
+            delayed.iter().all(|event| send(socket, &ServerMessage::Event { event: event.clone() }))
+                && send(socket, &ServerMessage::HandEnded)
+        });
+        state.seats.retain(|_, socket| send(socket, &ServerMessage::HandEnded));
+    }
+
+    /// Closes every connection
+    pub fn close(&self) {
+        let state = &mut *self.state();
+        for socket in state.spectators.iter_mut().chain(state.seats.values_mut()) {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+        }
+        state.spectators.clear();
+        state.seats.clear();
+    }
+
+    /// Plays `seat` remotely, issuing the seat's token
+    pub fn agent(&self, seat: u32, name: &str, timeout: Duration) -> RemoteAgent {
+        self.state().tokens.entry(seat).or_insert_with(|| format!("{:032x}", rand::random::<u128>()));
+        RemoteAgent { seat, name: name.to_string(), timeout, hub: self.clone() }
+    }
+}
+
+impl HandEventListener for Hub {
+    fn on_event(&mut self, event: &HandEvent) {
+        let mut state = self.state();
+        let message = ServerMessage::Event { event: event.clone() };
+
+        let dealt_to = match &event.kind {
+            HandEventKind::HoleCardsDealt { seat, .. } | HandEventKind::CardsDrawn { seat, .. } => Some(*seat),
+            _ => None,
+        };
+        match dealt_to {
+            None => state.spectators.retain_mut(|socket| send(socket, &message)),
+            Some(_) if self.hole_cards == HoleCards::AfterHand => state.delayed.push(event.clone()),
+            Some(_) => {}
+        }
+        state.seats.retain(|seat, socket| dealt_to.is_some_and(|to| to != *seat) || send(socket, &message));
+    }
+}
+
+/// A seat played over the network. On its turn the connected client gets its `PlayerView`
+/// and has `timeout` to answer; a missing seat or a late answer checks or folds, or stands
+/// pat at a draw, and a late seat is dropped until it connects again.
+pub struct RemoteAgent {
+    pub seat: u32,
+    pub name: String,
+    pub timeout: Duration,
+    hub: Hub,
+}
+
+impl RemoteAgent {
+    fn ask(&self, socket: &mut Socket, view: &PlayerView) -> Option<ClientMessage> {
+        if !send(socket, &ServerMessage::Turn { view: view.clone() }) {
+            return None;
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.checked_duration_since(Instant::now()).filter(|d| !d.is_zero())?;
+            socket.get_ref().set_read_timeout(Some(remaining)).ok()?;
+            match socket.read().ok()? {
+                Message::Text(text) => match ClientMessage::parse(&text) {
+                    Some(message) => return Some(message),
+                    None => continue,
+                },
+                Message::Close(_) => return None,
+                _ => continue,
+            }
+        }
+    }
+
+    /// The answer of the connected seat to `view`, if it connects and answers in time
+    fn turn(&mut self, view: &PlayerView) -> Option<ClientMessage> {
+        if !self.hub.wait_for_seats(&[self.seat], self.timeout) {
+            return None;
+        }
+
+        // Waiting for the answer happens outside the lock, so other connections keep joining
+        let mut socket = {
+            let mut state = self.hub.state();
+            let socket = state.seats.remove(&self.seat)?;
+            state.answering = Some(self.seat);
+            socket
+        };
+        let answer = self.ask(&mut socket, view);
+        let mut state = self.hub.state();
+        state.answering = None;
+        if answer.is_some() {
+            let _ = socket.get_ref().set_read_timeout(None);
+            state.seats.insert(self.seat, socket);
+        }
+
+        answer
+    }
+}
+
+impl Agent for RemoteAgent {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, view: &PlayerView) -> PokerAction {
+        match self.turn(view) {
+            Some(ClientMessage::Action { action }) => action,
+            _ if view.legal_actions.can_check => PokerAction::Check,
+            _ => PokerAction::Fold,
+        }
+    }
+
+    fn discard(&mut self, view: &PlayerView) -> Vec<Card> {
+        match self.turn(view) {
+            Some(ClientMessage::Discard { cards }) => cards,
+            _ => vec![],
+        }
+    }
+}
+
+/// Accepts WebSocket connections for a table: spectators on `/spectate`, seats on
+/// `/seat/<n>?token=<token>` with the token `Hub::agent` issued for the seat. A seat takes one
+/// connection at a time; it is free again once its client drops.
+pub struct TableServer {
+    pub address: SocketAddr,
+    pub hub: Hub,
+}
+
+impl TableServer {
+    /// Listens on `address`, e.g. `127.0.0.1:9000`, accepting connections in the background
+    pub fn bind<A: ToSocketAddrs>(address: A, hole_cards: HoleCards) -> io::Result<TableServer> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let hub = Hub::new(hole_cards);
+
+        let accepting = hub.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let hub = accepting.clone();
+                thread::spawn(move || {
+                    let mut seat = None;
+                    // The error type is set by tungstenite's handshake callback
+                    #[allow(clippy::result_large_err)]
+                    let route = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+                        let path = request.uri().path();
+                        let status = match path.strip_prefix("/seat/").map(|n| n.parse::<u32>()) {
+                            None if path == "/spectate" => return Ok(response),
+                            Some(Ok(number)) => {
+                                let token = request.uri().query().and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("token=")));
+                                match hub.admit(number, token) {
+                                    Ok(()) => {
+                                        seat = Some(number);
+                                        return Ok(response);
+                                    }
+                                    Err(status) => status,
+                                }
+                            }
+                            _ => StatusCode::NOT_FOUND,
+                        };
+                        let mut error = ErrorResponse::new(Some(format!("cannot join {}", path)));
+                        *error.status_mut() = status;
+                        Err(error)
+                    };
+                    if let Ok(socket) = tungstenite::accept_hdr(stream, route) {
+                        hub.join(seat, socket);
+                    }
+                });
+            }
+        });
+
+        Ok(TableServer { address, hub })
+    }
+}
+
+/// Connects `agent` to a seat of a remote table and plays until the server closes the
+/// connection. `on_message` sees every message first, for logging or display.
+pub fn play_remote(url: &str, agent: &mut dyn Agent, mut on_message: impl FnMut(&ServerMessage)) -> tungstenite::Result<()> {
+    let (mut socket, _) = tungstenite::connect(url)?;
+    loop {
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Ok(_) => continue,
+            Err(error) => return Err(error),
+        };
+        let message: ServerMessage = match serde_json::from_str(&text) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+        on_message(&message);
+
+        if let ServerMessage::Turn { view } = message {
+            let answer = if view.drawing {
+                ClientMessage::Discard { cards: agent.discard(&view) }
+            } else {
+                ClientMessage::Action { action: agent.decide(&view) }
+            };
+            let text = serde_json::to_string(&answer).expect("Expect messages to serialise");
+            socket.send(Message::text(text))?;
+        }
+    }
+}
+
+/// Watches a remote table as a spectator until the server closes the connection
+pub fn spectate(url: &str, mut on_message: impl FnMut(&ServerMessage)) -> tungstenite::Result<()> {
+    let (mut socket, _) = tungstenite::connect(url)?;
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if let Ok(message) = serde_json::from_str(&text) {
+                    on_message(&message);
+                }
+            }
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Ok(_) => {}
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use std::sync::mpsc;
+    use crate::game::poker::bots::CallingStation;
+    use crate::game::poker::dealer::play_hand;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::StartHandParams;
+
+    fn params() -> StartHandParams {
+        let players = funded_players(&[(1, "P1"), (2, "P2")], 100);
+        StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 21, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }
+    }
+
+    /// Plays one hand with seat 2 connected remotely, returning what the spectator and the seat received
+    fn play_on_localhost(hole_cards: HoleCards) -> (Vec<ServerMessage>, Vec<ServerMessage>) {
+        let server = TableServer::bind("127.0.0.1:0", hole_cards).unwrap();
+        let base = format!("ws://{}", server.address);
+
+        let (spectator_tx, spectator_rx) = mpsc::channel();
+        let url = format!("{}/spectate", base);
+        let spectator = thread::spawn(move || spectate(&url, |m| spectator_tx.send(m.clone()).unwrap()).unwrap());
+        let hub = server.hub.clone();
+        let remote = hub.agent(2, "P2", Duration::from_secs(5));
+        let (seat_tx, seat_rx) = mpsc::channel();
+        let url = format!("{}/seat/2?token={}", base, hub.token(2).unwrap());
+        let seat = thread::spawn(move || {
+            let mut agent = CallingStation { name: String::from("P2") };
+            play_remote(&url, &mut agent, |m| seat_tx.send(m.clone()).unwrap()).unwrap()
+        });
+
+        assert!(hub.wait_for_seats(&[2], Duration::from_secs(5)));
+        while hub.spectator_count() == 0 {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut agents: BTreeMap<usize, Box<dyn Agent>> = BTreeMap::new();
+        agents.insert(1, Box::new(CallingStation { name: String::from("P1") }));
+        agents.insert(2, Box::new(remote));
+        let played = play_hand(params(), &mut agents, None, vec![Box::new(hub.clone())]).unwrap();
+        hub.end_hand();
+        hub.close();
+        spectator.join().unwrap();
+        seat.join().unwrap();
+
+        assert!(played.hand.events.iter().any(|e| matches!(e.kind, HandEventKind::ActionTaken { seat: 2, action: PokerAction::Check, .. })));
+        (spectator_rx.try_iter().collect(), seat_rx.try_iter().collect())
+    }
+
+    fn dealt(messages: &[ServerMessage]) -> Vec<u32> {
+        messages.iter().filter_map(|m| match m {
+            ServerMessage::Event { event: HandEvent { kind: HandEventKind::HoleCardsDealt { seat, .. }, .. } } => Some(*seat),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_remote_seat_plays_and_spectator_never_sees_hole_cards() {
+        let (spectator, seat) = play_on_localhost(HoleCards::Hidden);
+
+        assert!(matches!(spectator[0], ServerMessage::Welcome { seat: None }));
+        assert!(dealt(&spectator).is_empty());
+        assert!(spectator.iter().any(|m| matches!(m, ServerMessage::Event { event: HandEvent { kind: HandEventKind::PotAwarded { .. }, .. } })));
+        assert!(matches!(spectator.last(), Some(ServerMessage::HandEnded)));
+
+        assert!(matches!(seat[0], ServerMessage::Welcome { seat: Some(2) }));
+        assert_eq!(dealt(&seat), vec![2]);
+        let turn = seat.iter().find_map(|m| match m { ServerMessage::Turn { view } => Some(view), _ => None }).unwrap();
+        assert_eq!(turn.seat, 2);
+        assert_eq!(turn.hole_cards.len(), 2);
+    }
+
+    #[test]
+    fn test_hole_cards_reach_spectators_after_the_hand() {
+        let (spectator, _) = play_on_localhost(HoleCards::AfterHand);
+
+        let awarded = spectator.iter().rposition(|m| matches!(m, ServerMessage::Event { event: HandEvent { kind: HandEventKind::PotAwarded { .. }, .. } })).unwrap();
+        assert_eq!(dealt(&spectator[awarded..]), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_seat_takes_its_token_and_one_client() {
+        let server = TableServer::bind("127.0.0.1:0", HoleCards::Hidden).unwrap();
+        let _remote = server.hub.agent(2, "P2", Duration::from_secs(5));
+        let token = server.hub.token(2).unwrap();
+        let seat = |path: &str| tungstenite::connect(format!("ws://{}{}", server.address, path)).map(|(socket, _)| socket);
+        let status = |path: &str| match seat(path) {
+            Err(tungstenite::Error::Http(response)) => response.status(),
+            other => panic!("{} was let in: {:?}", path, other.map(|_| ())),
+        };
+
+        assert_eq!(status("/seat/2"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/seat/2?token=guess"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&format!("/seat/1?token={}", token)), StatusCode::NOT_FOUND);
+
+        let mut first = seat(&format!("/seat/2?token={}", token)).unwrap();
+        assert!(server.hub.wait_for_seats(&[2], Duration::from_secs(5)));
+        assert_eq!(status(&format!("/seat/2?token={}", token)), StatusCode::CONFLICT);
+        assert!(matches!(first.read().unwrap(), Message::Text(_)));
+        server.hub.close();
+    }
+
+    #[test]
+    fn test_client_message_accepts_json_or_text() {
+        assert!(matches!(ClientMessage::parse("raise 40"), Some(ClientMessage::Action { action: PokerAction::Raise(40) })));
+        assert!(matches!(
+            ClientMessage::parse(r#"{"type":"action","action":{"type":"call","amount":10}}"#),
+            Some(ClientMessage::Action { action: PokerAction::Call(10) })
+        ));
+        assert!(matches!(ClientMessage::parse("discard 7s"), Some(ClientMessage::Discard { cards }) if cards == vec![Card::from_code("7s").unwrap()]));
+        assert!(matches!(ClientMessage::parse(r#"{"type":"discard","cards":[]}"#), Some(ClientMessage::Discard { cards }) if cards.is_empty()));
+        assert!(ClientMessage::parse("shove").is_none());
+    }
+}
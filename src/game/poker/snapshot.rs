@@ -0,0 +1,282 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use toml::{Table, Value};
+use crate::game::poker::betting::{BettingStructure, DEFAULT_RAISE_CAP};
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::game_player::PokerGamePlayer;
+use crate::game::poker::stats::{PlayerStats, Position, StatsTracker};
+use crate::game::poker::{CreatePokerGame, PokerGame};
+use crate::player::Player;
+
+const POSITIONS: [Position; 6] = [
+    Position::Button, Position::SmallBlind, Position::BigBlind,
+    Position::UnderTheGun, Position::Middle, Position::Cutoff,
+];
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid snapshot: {}", message))
+}
+
+fn counters(stats: &PlayerStats) -> [u32; 14] {
+    [
+        stats.hands, stats.vpip, stats.pfr, stats.three_bet_opportunities, stats.three_bets,
+        stats.faced_three_bet, stats.folded_to_three_bet, stats.cbet_opportunities, stats.cbets,
+        stats.postflop_bets_raises, stats.postflop_calls, stats.saw_flop, stats.showdowns,
+        stats.won_at_showdown,
+    ]
+}
+
+fn from_counters(c: [u32; 14]) -> PlayerStats {
+    PlayerStats {
+        hands: c[0], vpip: c[1], pfr: c[2], three_bet_opportunities: c[3], three_bets: c[4],
+        faced_three_bet: c[5], folded_to_three_bet: c[6], cbet_opportunities: c[7], cbets: c[8],
+        postflop_bets_raises: c[9], postflop_calls: c[10], saw_flop: c[11], showdowns: c[12],
+        won_at_showdown: c[13],
+    }
+}
+
+fn integer(table: &Table, key: &str) -> io::Result<i64> {
+    table.get(key).and_then(Value::as_integer).ok_or_else(|| invalid(&format!("missing `{}`", key)))
+}
+
+fn number(table: &Table, key: &str) -> io::Result<u32> {
+    u32::try_from(integer(table, key)?).map_err(|_| invalid(&format!("`{}` out of range", key)))
+}
+
+fn string(table: &Table, key: &str) -> io::Result<String> {
+    table.get(key).and_then(Value::as_str).map(String::from).ok_or_else(|| invalid(&format!("missing `{}`", key)))
+}
+
+fn betting(table: &Table) -> io::Result<BettingStructure> {
+    let code = string(table, "betting")?;
+    // Only fixed-limit tables store a raise cap
+    let raise_cap = match table.get("raise_cap") {
+        Some(_) => number(table, "raise_cap")?,
+        None => DEFAULT_RAISE_CAP,
+    };
+    BettingStructure::from_code(&code, raise_cap).ok_or_else(|| invalid(&format!("unknown betting `{}`", code)))
+}
+
+fn variant(table: &Table) -> io::Result<GameVariant> {
+    let code = string(table, "variant")?;
+    GameVariant::from_code(&code).ok_or_else(|| invalid(&format!("unknown variant `{}`", code)))
+}
+
+fn tables<'a>(table: &'a Table, key: &str) -> io::Result<Vec<&'a Table>> {
+    match table.get(key) {
+        None => Ok(vec![]),
+        Some(Value::Array(values)) => values.iter()
+            .map(|v| v.as_table().ok_or_else(|| invalid(&format!("`{}` entries must be tables", key))))
+            .collect(),
+        Some(_) => Err(invalid(&format!("`{}` must be an array of tables", key))),
+    }
+}
+
+impl PokerGame {
+    /// TOML snapshot of the table between hands: settings, players with their stacks and
+    /// buy-ins, button, hand counter, seed and HUD statistics. Restoring it and playing on
+    /// deals exactly the hands the original table would have, and the crate's bots play them the
+    /// same way; humans, LLMs and other outside agents may not.
+    pub fn snapshot(&self) -> String {
+        let mut table = Table::new();
+        table.insert("seats".into(), Value::Integer(self.seats as i64));
+        table.insert("default_buy_in_value".into(), Value::Integer(self.default_buy_in_value as i64));
+        table.insert("buy_in_limit".into(), Value::Integer(self.buy_in_limit as i64));
+        table.insert("blind_price".into(), Value::Integer(self.blind_price as i64));
+        table.insert("betting".into(), Value::String(self.betting.code().into()));
+        if let BettingStructure::FixedLimit { raise_cap } = self.betting {
+            table.insert("raise_cap".into(), Value::Integer(raise_cap as i64));
+        }
+        table.insert("variant".into(), Value::String(self.variant.code().into()));
+        table.insert("ante".into(), Value::Integer(self.ante as i64));
+        table.insert("show_hud".into(), Value::Boolean(self.show_hud));
+        table.insert("hand_number".into(), Value::Integer(self.hand_number as i64));
+        // TOML integers are signed, so the seed is stored bit for bit
+        table.insert("seed".into(), Value::Integer(self.seed as i64));
+        if let Some(dealer_seat) = self.dealer_seat {
+            table.insert("dealer_seat".into(), Value::Integer(dealer_seat as i64));
+        }
+
+        let players = self.players.iter().map(|p| {
+            let mut player = Table::new();
+            player.insert("seat".into(), Value::Integer(p.seat as i64));
+            player.insert("id".into(), Value::Integer(p.player.id as i64));
+            player.insert("name".into(), Value::String(p.player.name.clone()));
+            player.insert("logo".into(), Value::String(p.player.logo.clone()));
+            player.insert("stack".into(), Value::Integer(p.cash_amount as i64));
+            player.insert("buy_ins".into(), Value::Integer(p.buy_ins as i64));
+            Value::Table(player)
+        }).collect();
+        table.insert("players".into(), Value::Array(players));
+
+        let mut stats = vec![];
+        for (&player_id, positions) in &self.stats.stats {
+            for (position, player_stats) in positions {
+                let mut row = Table::new();
+                row.insert("player_id".into(), Value::Integer(player_id as i64));
+                row.insert("position".into(), Value::String(position.to_string()));
+                let values = counters(player_stats).iter().map(|&c| Value::Integer(c as i64)).collect();
+                row.insert("counters".into(), Value::Array(values));
+                stats.push(Value::Table(row));
+            }
+        }
+        table.insert("stats".into(), Value::Array(stats));
+
+        table.to_string()
+    }
+
+    pub fn restore(text: &str) -> io::Result<PokerGame> {
+        let table: Table = text.parse().map_err(|e: toml::de::Error| invalid(&e.to_string()))?;
+
+        let mut game = PokerGame::new(CreatePokerGame {
+            seats: number(&table, "seats")?,
+            default_buy_in_value: number(&table, "default_buy_in_value")?,
+            buy_in_limit: number(&table, "buy_in_limit")?,
+            blind_price: number(&table, "blind_price")?,
+            betting: betting(&table)?,
+            variant: variant(&table)?,
+            ante: number(&table, "ante")?,
+            show_hud: table.get("show_hud").and_then(Value::as_bool).ok_or_else(|| invalid("missing `show_hud`"))?,
+        });
+        game.hand_number = number(&table, "hand_number")?;
+        game.seed = integer(&table, "seed")? as u64;
+        if table.contains_key("dealer_seat") {
+            game.dealer_seat = Some(number(&table, "dealer_seat")?);
+        }
+
+        for player in tables(&table, "players")? {
+            let id = usize::try_from(integer(player, "id")?).map_err(|_| invalid("`id` out of range"))?;
+            let mut game_player = PokerGamePlayer::create(
+                number(player, "seat")?,
+                Player::create(id, &string(player, "name")?, &string(player, "logo")?),
+            );
+            game_player.cash_amount = number(player, "stack")?;
+            game_player.buy_ins = number(player, "buy_ins")?;
+            game.players.push(game_player);
+        }
+
+        let mut stats = StatsTracker::new();
+        for row in tables(&table, "stats")? {
+            let player_id = usize::try_from(integer(row, "player_id")?).map_err(|_| invalid("`player_id` out of range"))?;
+            let code = string(row, "position")?;
+            let position = POSITIONS.into_iter()
+                .find(|p| p.to_string() == code)
+                .ok_or_else(|| invalid(&format!("unknown position `{}`", code)))?;
+
+            let values: Vec<u32> = row.get("counters").and_then(Value::as_array)
+                .ok_or_else(|| invalid("missing `counters`"))?
+                .iter()
+                .map(|v| v.as_integer().and_then(|c| u32::try_from(c).ok()).ok_or_else(|| invalid("bad counter")))
+                .collect::<io::Result<_>>()?;
+            let values: [u32; 14] = values.try_into().map_err(|_| invalid("`counters` must hold 14 values"))?;
+
+            stats.stats.entry(player_id).or_default().insert(position, from_counters(values));
+        }
+        game.stats = stats;
+
+        Ok(game)
+    }
+
+    /// Writes the snapshot through a temporary file, so a crash mid-write keeps the previous one.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.snapshot())?;
+        fs::rename(&temporary, path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<PokerGame> {
+        PokerGame::restore(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::game::poker::error::PokerGameError;
+    use super::*;
+    use crate::game::poker::match_runner::{HandResult, MatchRunner};
+    use crate::game::poker::stats::HandRecord;
+
+    fn table() -> PokerGame {
+        let mut game = PokerGame::new(CreatePokerGame {
+            seats: 3,
+            default_buy_in_value: 100,
+            buy_in_limit: 2,
+            blind_price: 5,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: true,
+        });
+        game.seed = u64::MAX - 7;
+        game.add_player(Player::create(1, "Alice \"A\"", "a.png"), 1).unwrap();
+        game.add_player(Player::create(2, "Bob", "b.png"), 2).unwrap();
+        game.add_player(Player::create(3, "Carol", "c.png"), 3).unwrap();
+        game
+    }
+
+    /// A stand-in for real hands: the seed decides who takes 10 chips from whom
+    fn play(runner: &MatchRunner, game: &mut PokerGame) -> Vec<HandResult> {
+        runner.run::<_, PokerGameError>(game, |params| {
+            let winner = params.players[(params.seed % params.players.len() as u64) as usize].player.id;
+            Ok(params.players.iter()
+                .map(|p| (p.player.id, if p.player.id == winner { 20 } else { -10 }))
+                .collect::<BTreeMap<_, _>>())
+        }).unwrap().results
+    }
+
+    #[test]
+    fn test_resumed_run_matches_uninterrupted_run() {
+        let mut uninterrupted = table();
+        let all = play(&MatchRunner { hands: 12, auto_rebuy: true }, &mut uninterrupted);
+
+        let mut first = table();
+        let mut results = play(&MatchRunner { hands: 5, auto_rebuy: true }, &mut first);
+        let mut resumed = PokerGame::restore(&first.snapshot()).unwrap();
+        results.extend(play(&MatchRunner { hands: 7, auto_rebuy: true }, &mut resumed));
+
+        assert_eq!(results, all);
+        assert_eq!(resumed.dealer_seat, uninterrupted.dealer_seat);
+        assert_eq!(resumed.hand_number, 12);
+        let stacks = |game: &PokerGame| game.players.iter().map(|p| (p.seat, p.cash_amount, p.buy_ins)).collect::<Vec<_>>();
+        assert_eq!(stacks(&resumed), stacks(&uninterrupted));
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_keeps_players_and_stats() {
+        let mut game = table();
+        game.betting = BettingStructure::FixedLimit { raise_cap: 3 };
+        game.variant = GameVariant::OmahaHiLo;
+        game.ante = 2;
+        game.next_hand();
+        game.record_hand(&HandRecord { seats: vec![(1, 1), (2, 2), (3, 3)], ..HandRecord::default() });
+
+        let restored = PokerGame::restore(&game.snapshot()).unwrap();
+
+        assert_eq!(restored.seed, u64::MAX - 7);
+        assert_eq!(restored.dealer_seat, Some(1));
+        assert_eq!(restored.players[0].player.name, "Alice \"A\"");
+        assert_eq!(restored.players[2].cash_amount, 100);
+        assert_eq!(restored.stats.stats, game.stats.stats);
+        assert!(restored.show_hud);
+        assert_eq!(restored.betting, BettingStructure::FixedLimit { raise_cap: 3 });
+        assert_eq!(restored.variant, GameVariant::OmahaHiLo);
+        assert_eq!(restored.ante, 2);
+        assert_eq!(restored.snapshot(), game.snapshot());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("llm-holdem-snapshot-{}.toml", std::process::id()));
+        let game = table();
+
+        game.save(&path).unwrap();
+        let loaded = PokerGame::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.players.len(), 3);
+        assert_eq!(loaded.dealer_seat, None);
+        assert!(PokerGame::restore("seats = 3").is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use std::f64::consts::PI;
+use std::io;
+use std::time::{Duration, Instant};
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::canvas::{Canvas, Circle};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use crate::game::poker::event::{HandEvent, HandEventListener};
+use crate::game::poker::table::{TableSeat, TableState};
+
+const SEAT_WIDTH: u16 = 22;
+const SEAT_HEIGHT: u16 = 5;
+
+/// Boxes for `count` seats spread around an oval inside `area`, the first one at the bottom
+/// and the rest clockwise.
+pub fn seat_areas(count: usize, area: Rect) -> Vec<Rect> {
+    let width = SEAT_WIDTH.min(area.width);
+    let height = SEAT_HEIGHT.min(area.height);
+    let center_x = area.x as f64 + area.width as f64 / 2.0;
+    let center_y = area.y as f64 + area.height as f64 / 2.0;
+    let radius_x = (area.width - width) as f64 / 2.0;
+    let radius_y = (area.height - height) as f64 / 2.0;
+
+    (0..count).map(|i| {
+        let angle = PI / 2.0 + 2.0 * PI * i as f64 / count as f64;
+        let x = center_x + radius_x * angle.cos() - width as f64 / 2.0;
+        let y = center_y + radius_y * angle.sin() - height as f64 / 2.0;
+        Rect {
+            x: (x.round() as u16).clamp(area.x, area.right() - width),
+            y: (y.round() as u16).clamp(area.y, area.bottom() - height),
+            width,
+            height,
+        }
+    }).collect()
+}
+
+fn seat_box(table: &TableState, seat: &TableSeat, god_mode: bool) -> Paragraph<'static> {
+    let status = if seat.folded {
+        String::from("folded")
+    } else if seat.stack == 0 {
+        format!("all-in {}", seat.bet)
+    } else if seat.bet > 0 {
+        format!("bet {}", seat.bet)
+    } else {
+        String::new()
+    };
+    let style = if seat.folded { Style::default().fg(Color::DarkGray) } else { Style::default() };
+
+    Paragraph::new(vec![
+        Line::from(format!("{:<6}{:>13}", table.markers(seat), seat.stack)),
+        Line::from(status),
+        Line::from(table.visible_cards(seat, None, god_mode)),
+    ])
+        .style(style)
+        .block(Block::bordered().title(format!(" {} {} ", seat.seat, seat.name)))
+}
+
+/// Draws the whole screen: the table with its seats, the action log and a key legend.
+/// `god_mode` shows every hole card; otherwise only shown-down cards are face up.
+pub fn draw(frame: &mut Frame, table: &TableState, god_mode: bool) {
+    let [main, legend] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [felt, log] = Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)]).areas(main);
+
+    frame.render_widget(
+        Canvas::default()
+            .x_bounds([-1.0, 1.0])
+            .y_bounds([-1.0, 1.0])
+            .paint(|ctx| ctx.draw(&Circle { x: 0.0, y: 0.0, radius: 0.6, color: Color::Green })),
+        felt,
+    );
+
+    let board = if table.board.is_empty() {
+        String::new()
+    } else {
+        table.board.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+    };
+    let [_, middle, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(3), Constraint::Fill(1)]).areas(felt);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(table.stage.to_string()).style(Style::default().add_modifier(Modifier::BOLD)),
+            Line::from(board),
+            Line::from(format!("Pot: {}", table.pot)),
+        ]).alignment(Alignment::Center),
+        middle,
+    );
+
+    let mut seats: Vec<&TableSeat> = table.seats.iter().collect();
+    seats.sort_by_key(|s| s.seat);
+    for (seat, area) in seats.iter().zip(seat_areas(seats.len(), felt)) {
+        frame.render_widget(seat_box(table, seat, god_mode), area);
+    }
+
+    let lines = log.height.saturating_sub(2) as usize;
+    let start = table.history.len().saturating_sub(lines);
+    let history: Vec<Line> = table.history[start..].iter().map(|line| Line::from(line.clone())).collect();
+    frame.render_widget(Paragraph::new(history).block(Block::bordered().title(" Actions ")), log);
+
+    let legend_text = format!(" g god mode ({})   space pause   q quit", if god_mode { "on" } else { "off" });
+    frame.render_widget(Paragraph::new(legend_text).style(Style::default().fg(Color::DarkGray)), legend);
+}
+
+/// A full-screen view of the table that redraws on every event and waits `delay` after
+/// each, so a hand can be followed as it is played or replayed. Restores the terminal
+/// when dropped.
+pub struct Spectator {
+    pub table: TableState,
+    pub god_mode: bool,
+    pub delay: Duration,
+    pub paused: bool,
+    /// Set once the viewer presses `q`; events are no longer shown
+    pub quit: bool,
+    terminal: DefaultTerminal,
+    error: Option<io::Error>,
+}
+
+impl Spectator {
+    pub fn open(delay: Duration, god_mode: bool) -> io::Result<Spectator> {
+        Ok(Spectator {
+            table: TableState::new(),
+            god_mode,
+            delay,
+            paused: false,
+            quit: false,
+            terminal: ratatui::try_init()?,
+            error: None,
+        })
+    }
+
+    pub fn show(&mut self, event: &HandEvent) -> io::Result<()> {
+        self.table.apply(event);
+        self.redraw()?;
+        self.wait(self.delay)
+    }
+
+    fn redraw(&mut self) -> io::Result<()> {
+        let Spectator { terminal, table, god_mode, .. } = self;
+        terminal.draw(|frame| draw(frame, table, *god_mode))?;
+        Ok(())
+    }
+
+    fn handle_keys(&mut self, timeout: Duration) -> io::Result<()> {
+        if !event::poll(timeout)? {
+            return Ok(());
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                return Ok(());
+            }
+            match key.code {
+                KeyCode::Char('g') => {
+                    self.god_mode = !self.god_mode;
+                    self.redraw()?;
+                }
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits `duration`, and for as long as the view is paused, answering keys meanwhile
+    pub fn wait(&mut self, duration: Duration) -> io::Result<()> {
+        let deadline = Instant::now() + duration;
+        while !self.quit {
+            let now = Instant::now();
+            if !self.paused && now >= deadline {
+                break;
+            }
+            let timeout = if self.paused { Duration::from_millis(100) } else { deadline - now };
+            self.handle_keys(timeout)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the last table on screen until the viewer presses `q`
+    pub fn hold(&mut self) -> io::Result<()> {
+        self.redraw()?;
+        while !self.quit {
+            self.handle_keys(Duration::from_millis(100))?;
+        }
+        Ok(())
+    }
+
+    /// The first error met while showing events as a listener
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl HandEventListener for Spectator {
+    fn on_event(&mut self, event: &HandEvent) {
+        if self.quit || self.error.is_some() {
+            return;
+        }
+        if let Err(error) = self.show(event) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl Drop for Spectator {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use crate::game::poker::action::PokerAction;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+    fn hand(seats: usize) -> PokerHand {
+        let players = funded_players(&[(1, "P1"), (2, "P2"), (3, "P3"), (4, "P4")][..seats], 1000);
+
+        PokerHand::start(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 5, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap()
+    }
+
+    fn screen(table: &TableState, god_mode: bool) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 36)).unwrap();
+        terminal.draw(|frame| draw(frame, table, god_mode)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_seat_areas_go_around_without_overlapping() {
+        let area = Rect::new(0, 0, 84, 35);
+        let areas = seat_areas(6, area);
+
+        assert_eq!(areas.len(), 6);
+        assert!(areas[0].y > areas[3].y, "the first seat sits at the bottom");
+        for (i, a) in areas.iter().enumerate() {
+            assert!(area.contains(a.as_position()) && a.right() <= area.right() && a.bottom() <= area.bottom());
+            for b in &areas[i + 1..] {
+                assert!(!a.intersects(*b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw_shows_seats_board_pot_and_actions() {
+        let mut hand = hand(4);
+        hand.act(4, PokerAction::Raise(60)).unwrap();
+        hand.act(1, PokerAction::Fold).unwrap();
+        let table = TableState::from_events(&hand.events);
+
+        let screen = screen(&table, false);
+
+        for name in ["1 P1", "2 P2", "3 P3", "4 P4"] {
+            assert!(screen.contains(name), "{} is missing", name);
+        }
+        assert!(screen.contains("PRE-FLOP"));
+        assert!(screen.contains("Pot: 90"));
+        assert!(screen.contains("P4 raises 60"));
+        assert!(screen.contains("folded"));
+        assert!(screen.contains("SB"));
+        assert!(screen.contains("god mode (off)"));
+    }
+
+    #[test]
+    fn test_god_mode_reveals_hole_cards() {
+        let hand = hand(3);
+        let table = TableState::from_events(&hand.events);
+        let card = hand.active[1].hand[0].to_string();
+
+        assert!(!screen(&table, false).contains(&card));
+        assert!(screen(&table, true).contains(&card));
+    }
+}
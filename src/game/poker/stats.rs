@@ -0,0 +1,444 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::Formatter;
+use crate::game::poker::action::{PlayerAction, PokerAction};
+use crate::game::poker::event::{HandEvent, HandEventKind};
+use crate::game::poker::hand::HandStage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Position {
+    Button,
+    SmallBlind,
+    BigBlind,
+    UnderTheGun,
+    Middle,
+    Cutoff,
+}
+
+impl Position {
+    /// Position of the player at `index` in dealing order, the dealer being index 0.
+    /// Heads-up the dealer posts the small blind but is counted as the button.
+    pub fn from_dealing_order(index: usize, players: usize) -> Position {
+        match (index, players) {
+            (0, _) => Position::Button,
+            (1, 2) => Position::BigBlind,
+            (1, _) => Position::SmallBlind,
+            (2, _) => Position::BigBlind,
+            (i, n) if i == n - 1 => Position::Cutoff,
+            (3, _) => Position::UnderTheGun,
+            _ => Position::Middle,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            Position::Button => "BTN",
+            Position::SmallBlind => "SB",
+            Position::BigBlind => "BB",
+            Position::UnderTheGun => "UTG",
+            Position::Middle => "MP",
+            Position::Cutoff => "CO",
+        };
+
+        write!(f, "{}", res)
+    }
+}
+
+/// What the stats tracker needs to know about a finished hand.
+#[derive(Debug, Clone, Default)]
+pub struct HandRecord {
+    /// `(seat, player id)` in dealing order, starting from the dealer
+    pub seats: Vec<(u32, usize)>,
+    pub actions: Vec<PlayerAction>,
+    /// Seats still in the hand when the cards were shown
+    pub showdown: Vec<u32>,
+    pub winners: Vec<u32>,
+}
+
+impl HandRecord {
+    pub fn from_events(events: &[HandEvent]) -> HandRecord {
+        let mut record = HandRecord::default();
+
+        for event in events {
+            match &event.kind {
+                HandEventKind::HandStarted { players, .. } =>
+                    record.seats = players.iter().map(|p| (p.seat, p.player_id)).collect(),
+                HandEventKind::ActionTaken { seat, stage, action } =>
+                    record.actions.push(PlayerAction { seat: *seat, stage: *stage, action: *action }),
+                HandEventKind::Showdown { seat, .. } => record.showdown.push(*seat),
+                HandEventKind::PotAwarded { seat, amount } if *amount > 0 && !record.winners.contains(seat) =>
+                    record.winners.push(*seat),
+                _ => {}
+            }
+        }
+
+        record
+    }
+}
+
+/// Raw HUD counters; the percentage accessors divide them by their opportunities.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerStats {
+    pub hands: u32,
+    pub vpip: u32,
+    pub pfr: u32,
+    pub three_bet_opportunities: u32,
+    pub three_bets: u32,
+    pub faced_three_bet: u32,
+    pub folded_to_three_bet: u32,
+    pub cbet_opportunities: u32,
+    pub cbets: u32,
+    pub postflop_bets_raises: u32,
+    pub postflop_calls: u32,
+    pub saw_flop: u32,
+    pub showdowns: u32,
+    pub won_at_showdown: u32,
+}
+
+fn percentage(count: u32, opportunities: u32) -> f64 {
+    if opportunities == 0 { return 0.0; }
+    100.0 * count as f64 / opportunities as f64
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> f64 { percentage(self.vpip, self.hands) }
+    pub fn pfr(&self) -> f64 { percentage(self.pfr, self.hands) }
+    pub fn three_bet(&self) -> f64 { percentage(self.three_bets, self.three_bet_opportunities) }
+    pub fn fold_to_three_bet(&self) -> f64 { percentage(self.folded_to_three_bet, self.faced_three_bet) }
+    pub fn cbet(&self) -> f64 { percentage(self.cbets, self.cbet_opportunities) }
+    pub fn wtsd(&self) -> f64 { percentage(self.showdowns, self.saw_flop) }
+    pub fn wsd(&self) -> f64 { percentage(self.won_at_showdown, self.showdowns) }
+
+    /// Postflop (bets + raises) / calls; with no calls it is the number of bets and raises
+    pub fn aggression_factor(&self) -> f64 {
+        if self.postflop_calls == 0 { return self.postflop_bets_raises as f64; }
+        self.postflop_bets_raises as f64 / self.postflop_calls as f64
+    }
+
+    pub fn merge(&mut self, other: &PlayerStats) {
+        self.hands += other.hands;
+        self.vpip += other.vpip;
+        self.pfr += other.pfr;
+        self.three_bet_opportunities += other.three_bet_opportunities;
+        self.three_bets += other.three_bets;
+        self.faced_three_bet += other.faced_three_bet;
+        self.folded_to_three_bet += other.folded_to_three_bet;
+        self.cbet_opportunities += other.cbet_opportunities;
+        self.cbets += other.cbets;
+        self.postflop_bets_raises += other.postflop_bets_raises;
+        self.postflop_calls += other.postflop_calls;
+        self.saw_flop += other.saw_flop;
+        self.showdowns += other.showdowns;
+        self.won_at_showdown += other.won_at_showdown;
+    }
+}
+
+/// Accumulates HUD statistics per player id and position over many hands.
+#[derive(Debug, Clone, Default)]
+pub struct StatsTracker {
+    pub stats: BTreeMap<usize, BTreeMap<Position, PlayerStats>>,
+}
+
+impl StatsTracker {
+    pub fn new() -> Self {
+        StatsTracker { stats: BTreeMap::new() }
+    }
+
+    pub fn record_hand(&mut self, hand: &HandRecord) {
+        let mut hand_stats: BTreeMap<u32, PlayerStats> = hand.seats.iter()
+            .map(|&(seat, _)| (seat, PlayerStats { hands: 1, ..PlayerStats::default() }))
+            .collect();
+
+        let mut vpip = BTreeSet::new();
+        let mut pfr = BTreeSet::new();
+        let mut three_bet_spots = BTreeSet::new();
+        let mut three_bets = BTreeSet::new();
+        let mut folded_preflop = BTreeSet::new();
+        let mut raises = 0;
+        let mut open_raiser = None;
+        let mut last_raiser = None;
+        let mut open_raiser_faced_three_bet = false;
+
+        for action in hand.actions.iter().filter(|a| a.stage == HandStage::INIT) {
+            let seat = action.seat;
+            if matches!(action.action, PokerAction::Call(_) | PokerAction::Bet(_) | PokerAction::Raise(_)) {
+                vpip.insert(seat);
+            }
+            if action.action.is_aggressive() {
+                pfr.insert(seat);
+            }
+            if raises == 1 && open_raiser != Some(seat) {
+                three_bet_spots.insert(seat);
+                if action.action.is_aggressive() {
+                    three_bets.insert(seat);
+                }
+            }
+            if raises == 2 && open_raiser == Some(seat) && !open_raiser_faced_three_bet {
+                open_raiser_faced_three_bet = true;
+                let stats = hand_stats.entry(seat).or_default();
+                stats.faced_three_bet += 1;
+                if action.action == PokerAction::Fold {
+                    stats.folded_to_three_bet += 1;
+                }
+            }
+            if action.action == PokerAction::Fold {
+                folded_preflop.insert(seat);
+            }
+            if action.action.is_aggressive() {
+                raises += 1;
+                if raises == 1 {
+                    open_raiser = Some(seat);
+                }
+                last_raiser = Some(seat);
+            }
+        }
+
+        let mut flop_bet_made = false;
+        let mut cbet_spot_seen = false;
+        for action in hand.actions.iter().filter(|a| a.stage == HandStage::FLOP) {
+            if Some(action.seat) == last_raiser && !flop_bet_made && !cbet_spot_seen {
+                cbet_spot_seen = true;
+                let stats = hand_stats.entry(action.seat).or_default();
+                stats.cbet_opportunities += 1;
+                if action.action.is_aggressive() {
+                    stats.cbets += 1;
+                }
+            }
+            if action.action.is_aggressive() {
+                flop_bet_made = true;
+            }
+        }
+
+        for action in hand.actions.iter().filter(|a| a.stage != HandStage::INIT) {
+            let stats = hand_stats.entry(action.seat).or_default();
+            match action.action {
+                PokerAction::Bet(_) | PokerAction::Raise(_) => stats.postflop_bets_raises += 1,
+                PokerAction::Call(_) => stats.postflop_calls += 1,
+                PokerAction::Fold | PokerAction::Check => {}
+            }
+        }
+
+        let reached_flop = hand.actions.iter().any(|a| a.stage != HandStage::INIT) || !hand.showdown.is_empty();
+
+        for (&seat, stats) in hand_stats.iter_mut() {
+            stats.vpip += vpip.contains(&seat) as u32;
+            stats.pfr += pfr.contains(&seat) as u32;
+            stats.three_bet_opportunities += three_bet_spots.contains(&seat) as u32;
+            stats.three_bets += three_bets.contains(&seat) as u32;
+
+            if reached_flop && !folded_preflop.contains(&seat) {
+                stats.saw_flop += 1;
+                if hand.showdown.contains(&seat) {
+                    stats.showdowns += 1;
+                    stats.won_at_showdown += hand.winners.contains(&seat) as u32;
+                }
+            }
+        }
+
+        for (index, &(seat, player_id)) in hand.seats.iter().enumerate() {
+            let position = Position::from_dealing_order(index, hand.seats.len());
+            if let Some(stats) = hand_stats.get(&seat) {
+                self.stats.entry(player_id).or_default().entry(position).or_default().merge(stats);
+            }
+        }
+    }
+
+    /// Stats of a player over every position
+    pub fn totals(&self, player_id: usize) -> PlayerStats {
+        let mut totals = PlayerStats::default();
+        if let Some(positions) = self.stats.get(&player_id) {
+            for stats in positions.values() {
+                totals.merge(stats);
+            }
+        }
+        totals
+    }
+
+    /// One row per player for every position they played, plus an `ALL` row
+    fn rows(&self) -> Vec<(usize, String, PlayerStats)> {
+        let mut rows = vec![];
+        for (&player_id, positions) in &self.stats {
+            rows.push((player_id, String::from("ALL"), self.totals(player_id)));
+            for (position, stats) in positions {
+                rows.push((player_id, position.to_string(), *stats));
+            }
+        }
+        rows
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("player_id,position,hands,vpip,pfr,three_bet,fold_to_three_bet,cbet,af,wtsd,wsd\n");
+        for (player_id, position, s) in self.rows() {
+            csv.push_str(&format!(
+                "{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}\n",
+                player_id, position, s.hands, s.vpip(), s.pfr(), s.three_bet(), s.fold_to_three_bet(),
+                s.cbet(), s.aggression_factor(), s.wtsd(), s.wsd()
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self.rows().iter().map(|(player_id, position, s)| format!(
+            "{{\"player_id\":{},\"position\":\"{}\",\"hands\":{},\"vpip\":{:.4},\"pfr\":{:.4},\"three_bet\":{:.4},\"fold_to_three_bet\":{:.4},\"cbet\":{:.4},\"af\":{:.4},\"wtsd\":{:.4},\"wsd\":{:.4}}}",
+            player_id, position, s.hands, s.vpip(), s.pfr(), s.three_bet(), s.fold_to_three_bet(),
+            s.cbet(), s.aggression_factor(), s.wtsd(), s.wsd()
+        )).collect();
+
+        format!("[{}]", rows.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+
+    fn act(seat: u32, stage: HandStage, action: PokerAction) -> PlayerAction {
+        PlayerAction { seat, stage, action }
+    }
+
+    /// Three-handed: seat 1 dealer, seat 2 small blind, seat 3 big blind
+    fn three_handed(actions: Vec<PlayerAction>, showdown: Vec<u32>, winners: Vec<u32>) -> HandRecord {
+        HandRecord { seats: vec![(1, 10), (2, 20), (3, 30)], actions, showdown, winners }
+    }
+
+    #[test]
+    fn test_positions_from_dealing_order() {
+        assert_eq!(Position::from_dealing_order(0, 2), Position::Button);
+        assert_eq!(Position::from_dealing_order(1, 2), Position::BigBlind);
+        assert_eq!(Position::from_dealing_order(1, 6), Position::SmallBlind);
+        assert_eq!(Position::from_dealing_order(3, 6), Position::UnderTheGun);
+        assert_eq!(Position::from_dealing_order(4, 6), Position::Middle);
+        assert_eq!(Position::from_dealing_order(5, 6), Position::Cutoff);
+    }
+
+    #[test]
+    fn test_vpip_pfr_and_three_bet() {
+        let mut tracker = StatsTracker::new();
+        tracker.record_hand(&three_handed(vec![
+            act(1, HandStage::INIT, PokerAction::Raise(30)),
+            act(2, HandStage::INIT, PokerAction::Raise(90)),
+            act(3, HandStage::INIT, PokerAction::Fold),
+            act(1, HandStage::INIT, PokerAction::Fold),
+        ], vec![], vec![]));
+
+        let button = tracker.totals(10);
+        assert_eq!((button.vpip, button.pfr), (1, 1));
+        assert_eq!((button.faced_three_bet, button.folded_to_three_bet), (1, 1));
+        assert_eq!(button.saw_flop, 0);
+
+        let small_blind = tracker.totals(20);
+        assert_eq!((small_blind.three_bet_opportunities, small_blind.three_bets), (1, 1));
+        assert_eq!(small_blind.three_bet(), 100.0);
+
+        let big_blind = tracker.totals(30);
+        assert_eq!((big_blind.vpip, big_blind.three_bet_opportunities, big_blind.three_bets), (0, 0, 0));
+        assert_eq!(tracker.stats[&30].keys().copied().collect::<Vec<_>>(), vec![Position::BigBlind]);
+    }
+
+    #[test]
+    fn test_cbet_aggression_and_showdown() {
+        let mut tracker = StatsTracker::new();
+        tracker.record_hand(&three_handed(vec![
+            act(1, HandStage::INIT, PokerAction::Raise(30)),
+            act(2, HandStage::INIT, PokerAction::Fold),
+            act(3, HandStage::INIT, PokerAction::Call(20)),
+            act(3, HandStage::FLOP, PokerAction::Check),
+            act(1, HandStage::FLOP, PokerAction::Bet(40)),
+            act(3, HandStage::FLOP, PokerAction::Call(40)),
+            act(3, HandStage::TURN, PokerAction::Bet(80)),
+            act(1, HandStage::TURN, PokerAction::Raise(240)),
+            act(3, HandStage::TURN, PokerAction::Call(160)),
+            act(3, HandStage::RIVER, PokerAction::Check),
+            act(1, HandStage::RIVER, PokerAction::Check),
+        ], vec![1, 3], vec![3]));
+
+        let button = tracker.totals(10);
+        assert_eq!((button.cbet_opportunities, button.cbets), (1, 1));
+        assert_eq!(button.aggression_factor(), 2.0);
+        assert_eq!((button.saw_flop, button.showdowns, button.won_at_showdown), (1, 1, 0));
+
+        let big_blind = tracker.totals(30);
+        assert_eq!(big_blind.vpip, 1);
+        assert_eq!(big_blind.aggression_factor(), 0.5);
+        assert_eq!(big_blind.wtsd(), 100.0);
+        assert_eq!(big_blind.wsd(), 100.0);
+
+        assert_eq!(tracker.totals(20).saw_flop, 0);
+    }
+
+    #[test]
+    fn test_stats_accumulate_across_hands() {
+        let mut tracker = StatsTracker::new();
+        tracker.record_hand(&three_handed(vec![
+            act(1, HandStage::INIT, PokerAction::Call(20)),
+            act(2, HandStage::INIT, PokerAction::Fold),
+            act(3, HandStage::INIT, PokerAction::Check),
+        ], vec![1, 3], vec![1]));
+        tracker.record_hand(&three_handed(vec![
+            act(1, HandStage::INIT, PokerAction::Fold),
+            act(2, HandStage::INIT, PokerAction::Fold),
+        ], vec![], vec![3]));
+
+        let button = tracker.totals(10);
+        assert_eq!(button.hands, 2);
+        assert_eq!(button.vpip(), 50.0);
+        assert_eq!(button.pfr(), 0.0);
+    }
+
+    #[test]
+    fn test_record_from_hand_events() {
+        use crate::game::poker::game_player::funded_players;
+        use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+        let mut players = funded_players(&[(1, "P"), (2, "P"), (3, "P")], 1000);
+        for player in &mut players {
+            player.player.id = player.seat as usize * 10;
+        }
+        let mut hand = PokerHand::start(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 1, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap();
+        hand.act(1, PokerAction::Raise(60)).unwrap();
+        hand.act(2, PokerAction::Fold).unwrap();
+        hand.act(3, PokerAction::Call(40)).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.act(1, PokerAction::Bet(50)).unwrap();
+        hand.act(3, PokerAction::Call(50)).unwrap();
+        hand.show_down();
+        hand.award_pot(1, hand.pot).unwrap();
+
+        let record = HandRecord::from_events(&hand.events);
+        assert_eq!(record.seats, vec![(1, 10), (2, 20), (3, 30)]);
+        assert_eq!(record.actions.len(), 6);
+        assert_eq!(record.showdown, vec![1, 3]);
+        assert_eq!(record.winners, vec![1]);
+
+        let mut tracker = StatsTracker::new();
+        tracker.record_hand(&record);
+        assert_eq!(tracker.totals(10).cbets, 1);
+        assert_eq!(tracker.totals(10).won_at_showdown, 1);
+    }
+
+    #[test]
+    fn test_csv_and_json_export() {
+        let mut tracker = StatsTracker::new();
+        tracker.record_hand(&three_handed(vec![
+            act(1, HandStage::INIT, PokerAction::Raise(30)),
+            act(2, HandStage::INIT, PokerAction::Fold),
+            act(3, HandStage::INIT, PokerAction::Fold),
+        ], vec![], vec![1]));
+
+        let csv = tracker.to_csv();
+        assert!(csv.starts_with("player_id,position,hands,"));
+        assert!(csv.contains("\n10,ALL,1,100.00,100.00,"));
+        assert!(csv.contains("\n10,BTN,1,100.00,100.00,"));
+        assert_eq!(csv.lines().count(), 7);
+
+        let json = tracker.to_json();
+        assert!(json.starts_with("[{\"player_id\":10,\"position\":\"ALL\",\"hands\":1,\"vpip\":100.0000"));
+    }
+}
@@ -0,0 +1,518 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::deck::Card;
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::evaluator::is_nuts;
+use crate::game::poker::event::{HandEvent, HandEventKind, HandEventListener};
+use crate::game::poker::hand::HandStage;
+use crate::game::poker::variant::GameVariant;
+
+/// Tables of the embedded database. Cards are stored as space-separated codes (`As Kd`),
+/// stages by their display name (`PRE-FLOP`, `FLOP`, ...) and games and betting structures by
+/// their codes (`omaha`, `pot-limit`).
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    started_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tables (
+    id INTEGER PRIMARY KEY,
+    session_id INTEGER NOT NULL REFERENCES sessions(id),
+    name TEXT NOT NULL,
+    seats INTEGER NOT NULL,
+    blind_price INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS hands (
+    id INTEGER PRIMARY KEY,
+    table_id INTEGER NOT NULL REFERENCES tables(id),
+    hand_number INTEGER NOT NULL,
+    seed TEXT NOT NULL,
+    dealer_seat INTEGER NOT NULL,
+    blind_price INTEGER NOT NULL,
+    variant TEXT NOT NULL,
+    betting TEXT NOT NULL,
+    started_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS hand_players (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    starting_stack INTEGER NOT NULL,
+    hole_cards TEXT,
+    PRIMARY KEY (hand_id, seat)
+);
+CREATE TABLE IF NOT EXISTS blinds (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    blind TEXT NOT NULL,
+    amount INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS actions (
+    id INTEGER PRIMARY KEY,
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    stage TEXT NOT NULL,
+    action TEXT NOT NULL,
+    amount INTEGER,
+    timestamp_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS boards (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    stage TEXT NOT NULL,
+    cards TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS showdowns (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    cards TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    amount INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS decisions (
+    id INTEGER PRIMARY KEY,
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    stage TEXT NOT NULL,
+    model TEXT NOT NULL,
+    prompt TEXT NOT NULL,
+    response TEXT NOT NULL,
+    latency_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    sequence INTEGER NOT NULL,
+    json TEXT NOT NULL,
+    PRIMARY KEY (hand_id, sequence)
+);
+CREATE INDEX IF NOT EXISTS actions_by_hand ON actions(hand_id);
+CREATE INDEX IF NOT EXISTS hand_players_by_name ON hand_players(name);
+";
+
+/// One prompt sent to an agent and what came back
+#[derive(Debug, Clone)]
+pub struct DecisionRecord {
+    pub hand_id: i64,
+    pub seat: u32,
+    pub stage: HandStage,
+    pub model: String,
+    pub prompt: String,
+    pub response: String,
+    pub latency_ms: u64,
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(|c| c.code()).collect::<Vec<_>>().join(" ")
+}
+
+fn parse_cards(text: &str) -> Vec<Card> {
+    text.split_whitespace().filter_map(Card::from_code).collect()
+}
+
+fn action_columns(action: &PokerAction) -> (&'static str, Option<u32>) {
+    match action {
+        PokerAction::Fold => ("fold", None),
+        PokerAction::Check => ("check", None),
+        PokerAction::Call(amount) => ("call", Some(*amount)),
+        PokerAction::Bet(amount) => ("bet", Some(*amount)),
+        PokerAction::Raise(amount) => ("raise", Some(*amount)),
+    }
+}
+
+fn now_ms() -> u64 {
+    HandEvent::now(HandEventKind::PotAwarded { seat: 0, amount: 0 }).timestamp_ms
+}
+
+/// Embedded SQLite store for sessions, tables, hands and agent decisions.
+pub struct Storage {
+    connection: Rc<Connection>,
+}
+
+impl Storage {
+    pub fn open(path: &Path) -> rusqlite::Result<Storage> {
+        Storage::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> rusqlite::Result<Storage> {
+        Storage::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Storage> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Storage { connection: Rc::new(connection) })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub fn create_session(&self, name: &str) -> rusqlite::Result<i64> {
+        self.connection.execute("INSERT INTO sessions (name, started_ms) VALUES (?1, ?2)", params![name, now_ms() as i64])?;
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    pub fn create_table(&self, session_id: i64, name: &str, seats: u32, blind_price: u32) -> rusqlite::Result<i64> {
+        self.connection.execute(
+            "INSERT INTO tables (session_id, name, seats, blind_price) VALUES (?1, ?2, ?3, ?4)",
+            params![session_id, name, seats, blind_price],
+        )?;
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    /// A listener writing one hand of `table_id` to the store; subscribe a clone to the hand and
+    /// keep the original to read the hand id and any write error.
+    pub fn recorder(&self, table_id: i64, hand_number: u32) -> HandRecorder {
+        HandRecorder {
+            connection: Rc::clone(&self.connection),
+            table_id,
+            hand_number,
+            state: Rc::new(RefCell::new(RecorderState::default())),
+        }
+    }
+
+    pub fn record_decision(&self, decision: &DecisionRecord) -> rusqlite::Result<i64> {
+        self.connection.execute(
+            "INSERT INTO decisions (hand_id, seat, stage, model, prompt, response, latency_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                decision.hand_id, decision.seat, decision.stage.to_string(), decision.model,
+                decision.prompt, decision.response, decision.latency_ms as i64
+            ],
+        )?;
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    /// Ids of the hands `name` was dealt into, oldest first
+    pub fn hands_of(&self, name: &str) -> rusqlite::Result<Vec<i64>> {
+        let mut statement = self.connection.prepare("SELECT hand_id FROM hand_players WHERE name = ?1 ORDER BY hand_id")?;
+        statement.query_map([name], |row| row.get(0))?.collect()
+    }
+
+    /// Chips won minus chips put in by `name` over every stored hand
+    pub fn net_chips(&self, name: &str) -> rusqlite::Result<i64> {
+        self.connection.query_row(
+            "SELECT
+                 COALESCE((SELECT SUM(r.amount) FROM results r JOIN hand_players p ON p.hand_id = r.hand_id AND p.seat = r.seat WHERE p.name = ?1), 0)
+               - COALESCE((SELECT SUM(b.amount) FROM blinds b JOIN hand_players p ON p.hand_id = b.hand_id AND p.seat = b.seat WHERE p.name = ?1), 0)
+               - COALESCE((SELECT SUM(a.amount) FROM actions a JOIN hand_players p ON p.hand_id = a.hand_id AND p.seat = a.seat WHERE p.name = ?1), 0)",
+            [name],
+            |row| row.get(0),
+        )
+    }
+
+    /// Mean response time of `model` in milliseconds, `None` before its first decision
+    pub fn average_latency(&self, model: &str) -> rusqlite::Result<Option<f64>> {
+        self.connection.query_row("SELECT AVG(latency_ms) FROM decisions WHERE model = ?1", [model], |row| row.get(0))
+    }
+
+    /// Hold'em hands where `name` folded after the flop while holding the nuts on the board so
+    /// far. Other games, short-deck included, rank and make hands differently and are left out.
+    pub fn folded_the_nuts(&self, name: &str) -> rusqlite::Result<Vec<i64>> {
+        let mut folds = self.connection.prepare(
+            "SELECT a.hand_id, a.stage, p.hole_cards FROM actions a
+             JOIN hand_players p ON p.hand_id = a.hand_id AND p.seat = a.seat
+             JOIN hands h ON h.id = a.hand_id
+             WHERE p.name = ?1 AND a.action = 'fold' AND a.stage != ?2 AND p.hole_cards IS NOT NULL AND h.variant = ?3
+             ORDER BY a.hand_id",
+        )?;
+        let folds: Vec<(i64, String, String)> = folds
+            .query_map(params![name, HandStage::INIT.to_string(), GameVariant::Holdem.code()], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut boards = self.connection.prepare("SELECT stage, cards FROM boards WHERE hand_id = ?1 ORDER BY rowid")?;
+        let mut hands = vec![];
+        for (hand_id, stage, hole_cards) in folds {
+            let mut board = vec![];
+            for street in boards.query_map([hand_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+                let (board_stage, cards) = street?;
+                board.extend(parse_cards(&cards));
+                if board_stage == stage {
+                    break;
+                }
+            }
+
+            if board.len() >= 3 && is_nuts(&parse_cards(&hole_cards), &board) {
+                hands.push(hand_id);
+            }
+        }
+
+        Ok(hands)
+    }
+
+    /// The stored event log of a hand, one JSON object per event
+    pub fn hand_events(&self, hand_id: i64) -> rusqlite::Result<Vec<String>> {
+        let mut statement = self.connection.prepare("SELECT json FROM events WHERE hand_id = ?1 ORDER BY sequence")?;
+        statement.query_map([hand_id], |row| row.get(0))?.collect()
+    }
+
+    /// Removes the hands of a table numbered after `hand_number`, with everything recorded
+    /// about them
+    pub fn delete_hands_after(&self, table_id: i64, hand_number: u32) -> rusqlite::Result<()> {
+        let hands = "SELECT id FROM hands WHERE table_id = ?1 AND hand_number > ?2";
+        for child in ["hand_players", "blinds", "actions", "boards", "showdowns", "results", "decisions", "events"] {
+            self.connection.execute(&format!("DELETE FROM {} WHERE hand_id IN ({})", child, hands), params![table_id, hand_number])?;
+        }
+        self.connection.execute("DELETE FROM hands WHERE table_id = ?1 AND hand_number > ?2", params![table_id, hand_number])?;
+        Ok(())
+    }
+
+    /// Id of the hand `hand_number` of a table
+    pub fn find_hand(&self, table_id: i64, hand_number: u32) -> rusqlite::Result<Option<i64>> {
+        self.connection.query_row(
+            "SELECT id FROM hands WHERE table_id = ?1 AND hand_number = ?2",
+            params![table_id, hand_number],
+            |row| row.get(0),
+        ).optional()
+    }
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    hand_id: Option<i64>,
+    sequence: u32,
+    error: Option<rusqlite::Error>,
+}
+
+/// Writes the events of one hand to the store as the engine emits them.
+#[derive(Clone)]
+pub struct HandRecorder {
+    connection: Rc<Connection>,
+    table_id: i64,
+    hand_number: u32,
+    state: Rc<RefCell<RecorderState>>,
+}
+
+impl HandRecorder {
+    /// Row id of the hand, once its start has been recorded
+    pub fn hand_id(&self) -> Option<i64> {
+        self.state.borrow().hand_id
+    }
+
+    /// The first write that failed; later events of the hand are not recorded
+    pub fn take_error(&self) -> Option<rusqlite::Error> {
+        self.state.borrow_mut().error.take()
+    }
+
+    fn write(&self, event: &HandEvent) -> rusqlite::Result<()> {
+        let connection = &self.connection;
+
+        if let HandEventKind::HandStarted { seed, dealer_seat, blind_price, betting, variant, players, .. } = &event.kind {
+            connection.execute(
+                "INSERT INTO hands (table_id, hand_number, seed, dealer_seat, blind_price, variant, betting, started_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![self.table_id, self.hand_number, seed.to_string(), dealer_seat, blind_price, variant.code(), betting.code(), event.timestamp_ms as i64],
+            )?;
+            let hand_id = connection.last_insert_rowid();
+            for player in players {
+                connection.execute(
+                    "INSERT INTO hand_players (hand_id, seat, player_id, name, starting_stack) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![hand_id, player.seat, player.player_id as i64, player.name, player.stack],
+                )?;
+            }
+            let mut state = self.state.borrow_mut();
+            state.hand_id = Some(hand_id);
+            state.sequence = 0;
+        }
+
+        let hand_id = match self.state.borrow().hand_id {
+            Some(hand_id) => hand_id,
+            None => return Ok(()),
+        };
+
+        match &event.kind {
+            HandEventKind::HandStarted { .. } => {}
+            HandEventKind::AntePosted { seat, amount } => {
+                connection.execute(
+                    "INSERT INTO blinds (hand_id, seat, blind, amount) VALUES (?1, ?2, 'ANTE', ?3)",
+                    params![hand_id, seat, amount],
+                )?;
+            }
+            HandEventKind::BlindPosted { seat, blind, amount } => {
+                connection.execute(
+                    "INSERT INTO blinds (hand_id, seat, blind, amount) VALUES (?1, ?2, ?3, ?4)",
+                    params![hand_id, seat, blind.to_string(), amount],
+                )?;
+            }
+            HandEventKind::BringInPosted { seat, amount } => {
+                connection.execute(
+                    "INSERT INTO blinds (hand_id, seat, blind, amount) VALUES (?1, ?2, 'BRING-IN', ?3)",
+                    params![hand_id, seat, amount],
+                )?;
+            }
+            // Stud games deal a player's cards over several streets, face up or down, and draw
+            // games deal replacements; the draws themselves are kept in the event log only
+            HandEventKind::DrawTaken { .. } => {}
+            HandEventKind::HoleCardsDealt { seat, cards } | HandEventKind::UpCardsDealt { seat, cards } | HandEventKind::CardsDrawn { seat, drawn: cards, .. } => {
+                connection.execute(
+                    "UPDATE hand_players SET hole_cards = TRIM(COALESCE(hole_cards, '') || ' ' || ?3) WHERE hand_id = ?1 AND seat = ?2",
+                    params![hand_id, seat, cards_text(cards)],
+                )?;
+            }
+            HandEventKind::ActionTaken { seat, stage, action } => {
+                let (name, amount) = action_columns(action);
+                connection.execute(
+                    "INSERT INTO actions (hand_id, seat, stage, action, amount, timestamp_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![hand_id, seat, stage.to_string(), name, amount, event.timestamp_ms as i64],
+                )?;
+            }
+            HandEventKind::BoardDealt { stage, cards } => {
+                connection.execute(
+                    "INSERT INTO boards (hand_id, stage, cards) VALUES (?1, ?2, ?3)",
+                    params![hand_id, stage.to_string(), cards_text(cards)],
+                )?;
+            }
+            HandEventKind::Showdown { seat, cards } => {
+                connection.execute(
+                    "INSERT INTO showdowns (hand_id, seat, cards) VALUES (?1, ?2, ?3)",
+                    params![hand_id, seat, cards_text(cards)],
+                )?;
+            }
+            HandEventKind::PotAwarded { seat, amount } => {
+                connection.execute(
+                    "INSERT INTO results (hand_id, seat, amount) VALUES (?1, ?2, ?3)",
+                    params![hand_id, seat, amount],
+                )?;
+            }
+        }
+
+        let sequence = self.state.borrow().sequence;
+        connection.execute(
+            "INSERT INTO events (hand_id, sequence, json) VALUES (?1, ?2, ?3)",
+            params![hand_id, sequence, event.to_json()],
+        )?;
+        self.state.borrow_mut().sequence += 1;
+
+        Ok(())
+    }
+}
+
+impl HandEventListener for HandRecorder {
+    fn on_event(&mut self, event: &HandEvent) {
+        if self.state.borrow().error.is_some() {
+            return;
+        }
+        if let Err(error) = self.write(event) {
+            self.state.borrow_mut().error = Some(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::deck::Deck;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+    /// Heads-up hand dealt from `order` where Alice bets the flop and Bob folds
+    fn record(storage: &Storage, variant: GameVariant, order: &str) -> HandRecorder {
+        let players = funded_players(&[(1, "Alice"), (2, "Bob")], 1000);
+        let deck = Deck::from_cards(order.split_whitespace().map(|c| Card::from_code(c).unwrap()).collect());
+
+        let session = storage.create_session("nightly").unwrap();
+        let table = storage.create_table(session, "Table 1", 2, 5).unwrap();
+        let recorder = storage.recorder(table, 1);
+
+        let mut hand = PokerHand::start_with_deck(StartHandParams { blind_price: 5, players, dealer_seat: 1, seed: 3, betting: BettingStructure::NoLimit, variant, ante: 0 }, deck).unwrap();
+        hand.subscribe(Box::new(recorder.clone()));
+        hand.act(1, PokerAction::Call(5)).unwrap();
+        hand.act(2, PokerAction::Check).unwrap();
+        hand.deal_next_stage().unwrap();
+        hand.act(2, PokerAction::Check).unwrap();
+        hand.act(1, PokerAction::Bet(20)).unwrap();
+        hand.act(2, PokerAction::Fold).unwrap();
+        hand.award_pot(1, hand.pot).unwrap();
+        recorder
+    }
+
+    /// Bob flops the nut flush and folds it
+    fn recorded_hand(storage: &Storage) -> HandRecorder {
+        // Hole cards alternate from the small blind; each round and street burns one card
+        record(storage, GameVariant::Holdem, "Kd As 2c Kh 5s 3c 4d Qs 8s 2s 4c 7h 5c 8d")
+    }
+
+    #[test]
+    fn test_recorder_writes_hand_from_event_stream() {
+        let storage = Storage::open_in_memory().unwrap();
+        let recorder = recorded_hand(&storage);
+
+        assert!(recorder.take_error().is_none());
+        let hand_id = recorder.hand_id().unwrap();
+        assert_eq!(storage.find_hand(1, 1).unwrap(), Some(hand_id));
+        assert_eq!(storage.hands_of("Bob").unwrap(), vec![hand_id]);
+
+        let actions: i64 = storage.connection().query_row("SELECT COUNT(*) FROM actions WHERE hand_id = ?1", [hand_id], |r| r.get(0)).unwrap();
+        assert_eq!(actions, 5);
+        assert_eq!(storage.net_chips("Alice").unwrap(), 10);
+        assert_eq!(storage.net_chips("Bob").unwrap(), -10);
+
+        let events = storage.hand_events(hand_id).unwrap();
+        assert!(events[0].contains("\"event\":\"HandStarted\""));
+        assert!(events.last().unwrap().contains("\"event\":\"PotAwarded\""));
+    }
+
+    #[test]
+    fn test_delete_hands_after() {
+        let storage = Storage::open_in_memory().unwrap();
+        let hand_id = recorded_hand(&storage).hand_id().unwrap();
+
+        storage.delete_hands_after(1, 1).unwrap();
+        assert_eq!(storage.find_hand(1, 1).unwrap(), Some(hand_id));
+
+        storage.delete_hands_after(1, 0).unwrap();
+        assert_eq!(storage.find_hand(1, 1).unwrap(), None);
+        assert!(storage.hands_of("Bob").unwrap().is_empty());
+        assert_eq!(storage.net_chips("Alice").unwrap(), 0);
+        assert!(storage.hand_events(hand_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_folded_the_nuts() {
+        let storage = Storage::open_in_memory().unwrap();
+        let recorder = recorded_hand(&storage);
+        let hand_id = recorder.hand_id().unwrap();
+
+        assert_eq!(storage.folded_the_nuts("Bob").unwrap(), vec![hand_id]);
+        assert!(storage.folded_the_nuts("Alice").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_folded_the_nuts_leaves_out_omaha() {
+        let storage = Storage::open_in_memory().unwrap();
+        // Four spades to the flopped ten would be a royal flush, but Omaha plays two of them
+        let recorder = record(&storage, GameVariant::Omaha, "Kd As 2c Kh Ks 3c Kc Qs 4c 7h Js 5c 6c Ts 2h 3d");
+        let hand_id = recorder.hand_id().unwrap();
+
+        let game: (String, String) = storage.connection()
+            .query_row("SELECT variant, betting FROM hands WHERE id = ?1", [hand_id], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        assert_eq!(game, (String::from("omaha"), String::from("no-limit")));
+        assert!(storage.folded_the_nuts("Bob").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decisions_and_latency() {
+        let storage = Storage::open_in_memory().unwrap();
+        let hand_id = recorded_hand(&storage).hand_id().unwrap();
+
+        assert_eq!(storage.average_latency("gpt").unwrap(), None);
+        for latency_ms in [100, 300] {
+            storage.record_decision(&DecisionRecord {
+                hand_id,
+                seat: 1,
+                stage: HandStage::INIT,
+                model: String::from("gpt"),
+                prompt: String::from("You are on the button"),
+                response: String::from("call"),
+                latency_ms,
+            }).unwrap();
+        }
+        assert_eq!(storage.average_latency("gpt").unwrap(), Some(200.0));
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::deck::Card;
+use crate::game::poker::action::PokerAction;
+use crate::game::poker::blind::Blind;
+use crate::game::poker::event::{HandEvent, HandEventKind};
+use crate::game::poker::hand::HandStage;
+use crate::game::poker::variant::GameVariant;
+
+/// Action lines kept on screen by `TableState::render`
+const HISTORY_LINES: usize = 12;
+
+/// One line per event, naming players by seat and streets as `variant` does
+pub fn describe(event: &HandEvent, names: &BTreeMap<u32, String>, variant: GameVariant) -> String {
+    let name = |seat: &u32| names.get(seat).cloned().unwrap_or_else(|| format!("Seat {}", seat));
+
+    match &event.kind {
+        HandEventKind::HandStarted { seed, dealer_seat, .. } => format!("*** Hand seed {}, {} has the button ***", seed, name(dealer_seat)),
+        HandEventKind::AntePosted { seat, amount } => format!("{} posts ante {}", name(seat), amount),
+        HandEventKind::BlindPosted { seat, blind, amount } => format!("{} posts {} {}", name(seat), blind, amount),
+        HandEventKind::BringInPosted { seat, amount } => format!("{} brings in for {}", name(seat), amount),
+        HandEventKind::HoleCardsDealt { seat, cards: dealt } => format!("{} is dealt {}", name(seat), cards(dealt)),
+        HandEventKind::UpCardsDealt { seat, cards: dealt } => format!("{} is dealt {} face up", name(seat), cards(dealt)),
+        HandEventKind::ActionTaken { seat, action, .. } => format!("{} {}", name(seat), action),
+        HandEventKind::BoardDealt { stage, cards: board } if board.is_empty() => format!("*** {} ***", variant.stage_name(*stage)),
+        HandEventKind::BoardDealt { stage, cards: board } => format!("*** {} *** {}", variant.stage_name(*stage), cards(board)),
+        HandEventKind::DrawTaken { seat, count: 0, .. } => format!("{} stands pat", name(seat)),
+        HandEventKind::DrawTaken { seat, count, .. } => format!("{} draws {}", name(seat), count),
+        HandEventKind::CardsDrawn { seat, discarded, drawn } => format!("{} discards {} and draws {}", name(seat), cards(discarded), cards(drawn)),
+        HandEventKind::Showdown { seat, cards: shown } => format!("{} shows {}", name(seat), cards(shown)),
+        HandEventKind::PotAwarded { seat, amount } => format!("{} wins {}", name(seat), amount),
+    }
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSeat {
+    pub seat: u32,
+    pub name: String,
+    pub stack: u32,
+    /// Chips put in during the current street
+    pub bet: u32,
+    pub blind: Blind,
+    /// Empty until dealt; hidden from other seats until shown down
+    pub cards: Vec<Card>,
+    /// Cards of `cards` dealt face up, which every seat sees
+    pub up_cards: Vec<Card>,
+    pub shown: bool,
+    pub folded: bool,
+}
+
+/// The table as a spectator sees it, rebuilt from the event stream, so live hands and
+/// replays render the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableState {
+    pub variant: GameVariant,
+    pub dealer_seat: u32,
+    pub stage: HandStage,
+    pub board: Vec<Card>,
+    pub pot: u32,
+    /// In dealing order, dealer first
+    pub seats: Vec<TableSeat>,
+    /// Blinds, actions, board cards, showdowns and awards of the current hand
+    pub history: Vec<String>,
+}
+
+impl TableState {
+    pub fn new() -> TableState {
+        TableState {
+            variant: GameVariant::Holdem,
+            dealer_seat: 0,
+            stage: HandStage::INIT,
+            board: vec![],
+            pot: 0,
+            seats: vec![],
+            history: vec![],
+        }
+    }
+
+    pub fn from_events(events: &[HandEvent]) -> TableState {
+        let mut table = TableState::new();
+        for event in events {
+            table.apply(event);
+        }
+        table
+    }
+
+    fn seat_mut(&mut self, seat: u32) -> Option<&mut TableSeat> {
+        self.seats.iter_mut().find(|s| s.seat == seat)
+    }
+
+    fn put_in(&mut self, seat: u32, amount: u32) {
+        if let Some(player) = self.seat_mut(seat) {
+            player.stack = player.stack.saturating_sub(amount);
+            player.bet += amount;
+        }
+        self.pot += amount;
+    }
+
+    /// Moves the table on by one event. A `HandStarted` clears the previous hand.
+    pub fn apply(&mut self, event: &HandEvent) {
+        let names: BTreeMap<u32, String> = self.seats.iter().map(|s| (s.seat, s.name.clone())).collect();
+
+        match &event.kind {
+            HandEventKind::HandStarted { dealer_seat, variant, players, .. } => {
+                *self = TableState::new();
+                self.variant = *variant;
+                self.dealer_seat = *dealer_seat;
+                self.seats = players.iter().map(|p| TableSeat {
+                    seat: p.seat,
+                    name: p.name.clone(),
+                    stack: p.stack,
+                    bet: 0,
+                    blind: Blind::NONE,
+                    cards: vec![],
+                    up_cards: vec![],
+                    shown: false,
+                    folded: false,
+                }).collect();
+                return;
+            }
+            HandEventKind::AntePosted { seat, amount } => {
+                // Antes are not bets of the street, so they go straight to the pot
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.stack = player.stack.saturating_sub(*amount);
+                }
+                self.pot += amount;
+            }
+            HandEventKind::BlindPosted { seat, blind, amount } => {
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.blind = *blind;
+                }
+                self.put_in(*seat, *amount);
+            }
+            HandEventKind::BringInPosted { seat, amount } => self.put_in(*seat, *amount),
+            HandEventKind::HoleCardsDealt { seat, cards } => {
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.cards.extend(cards.iter().copied());
+                }
+                // Dealt cards stay out of the history, which any seat may read
+                return;
+            }
+            HandEventKind::UpCardsDealt { seat, cards } => {
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.cards.extend(cards.iter().copied());
+                    player.up_cards.extend(cards.iter().copied());
+                }
+            }
+            HandEventKind::ActionTaken { seat, action, .. } => match *action {
+                PokerAction::Fold => {
+                    if let Some(player) = self.seat_mut(*seat) {
+                        player.folded = true;
+                    }
+                }
+                PokerAction::Check => {}
+                PokerAction::Call(amount) | PokerAction::Bet(amount) | PokerAction::Raise(amount) => self.put_in(*seat, amount),
+            },
+            HandEventKind::BoardDealt { stage, cards } => {
+                self.stage = *stage;
+                self.board.extend(cards.iter().copied());
+                for player in &mut self.seats {
+                    player.bet = 0;
+                }
+            }
+            HandEventKind::DrawTaken { .. } => {}
+            HandEventKind::CardsDrawn { seat, discarded, drawn } => {
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.cards.retain(|c| !discarded.contains(c));
+                    player.cards.extend(drawn.iter().copied());
+                }
+                // Like dealt cards, the replacements stay out of the history
+                return;
+            }
+            HandEventKind::Showdown { seat, cards } => {
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.cards = cards.clone();
+                    player.shown = true;
+                }
+            }
+            HandEventKind::PotAwarded { seat, amount } => {
+                if let Some(player) = self.seat_mut(*seat) {
+                    player.stack += amount;
+                }
+                self.pot = self.pot.saturating_sub(*amount);
+            }
+        }
+
+        self.history.push(describe(event, &names, self.variant));
+    }
+
+    /// Dealer and blind markers of a seat, e.g. `D SB` heads-up
+    pub fn markers(&self, seat: &TableSeat) -> String {
+        let mut markers = vec![];
+        if seat.seat == self.dealer_seat {
+            markers.push("D");
+        }
+        match seat.blind {
+            Blind::SMALL => markers.push("SB"),
+            Blind::BIG => markers.push("BB"),
+            Blind::NONE => {}
+        }
+        markers.join(" ")
+    }
+
+    /// Hole cards of `seat` as `viewer` may see them: their own, shown-down ones, or every
+    /// one when `reveal` is set. Others are face down but for up-cards.
+    pub fn visible_cards(&self, seat: &TableSeat, viewer: Option<u32>, reveal: bool) -> String {
+        if seat.cards.is_empty() || (seat.folded && !reveal && viewer != Some(seat.seat)) {
+            return String::new();
+        }
+        if reveal || seat.shown || viewer == Some(seat.seat) {
+            return cards(&seat.cards);
+        }
+        seat.cards.iter()
+            .map(|card| if seat.up_cards.contains(card) { card.to_string() } else { String::from("🂠") })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Plain-text table for a terminal: stage, pot, board, one line per seat and the latest actions.
+    pub fn render(&self, viewer: Option<u32>, reveal: bool) -> String {
+        let mut text = String::new();
+
+        writeln!(text, "=== {} === Pot: {}", self.variant.stage_name(self.stage), self.pot).unwrap();
+        writeln!(text, "Board: {}", if self.board.is_empty() { String::from("-") } else { cards(&self.board) }).unwrap();
+        for seat in &self.seats {
+            let mut status = String::new();
+            if seat.folded {
+                status.push_str(" folded");
+            } else if seat.stack == 0 {
+                status.push_str(" all-in");
+            }
+            if seat.bet > 0 {
+                write!(status, " bet {}", seat.bet).unwrap();
+            }
+            writeln!(
+                text, "{:>5} Seat {} {:<12} {:>7}{:<14} {}",
+                self.markers(seat), seat.seat, seat.name, seat.stack, status, self.visible_cards(seat, viewer, reveal)
+            ).unwrap();
+        }
+        let me = viewer.and_then(|viewer| self.seats.iter().find(|s| s.seat == viewer));
+        if let Some(me) = me.filter(|me| !me.cards.is_empty()) {
+            writeln!(text, "🧠 {}: {}", me.name, cards(&me.cards)).unwrap();
+        }
+
+        let start = self.history.len().saturating_sub(HISTORY_LINES);
+        for line in &self.history[start..] {
+            writeln!(text, "  {}", line).unwrap();
+        }
+
+        text
+    }
+}
+
+impl Default for TableState {
+    fn default() -> Self {
+        TableState::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::{PokerHand, StartHandParams};
+
+    fn hand() -> PokerHand {
+        let players = funded_players(&[(1, "P1"), (2, "P2"), (3, "P3")], 1000);
+
+        PokerHand::start(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 3, betting: BettingStructure::NoLimit, variant: GameVariant::Holdem, ante: 0 }).unwrap()
+    }
+
+    #[test]
+    fn test_state_follows_blinds_actions_and_board() {
+        let mut hand = hand();
+        hand.act(1, PokerAction::Raise(60)).unwrap();
+        hand.act(2, PokerAction::Fold).unwrap();
+        hand.act(3, PokerAction::Call(40)).unwrap();
+        hand.deal_next_stage().unwrap();
+
+        let table = TableState::from_events(&hand.events);
+
+        assert_eq!(table.stage, HandStage::FLOP);
+        assert_eq!(table.board, hand.board);
+        assert_eq!(table.pot, hand.pot);
+        assert_eq!(table.pot, 130);
+        let stacks: Vec<(u32, u32, bool)> = table.seats.iter().map(|s| (s.seat, s.stack, s.folded)).collect();
+        assert_eq!(stacks, vec![(1, 940, false), (2, 990, true), (3, 940, false)]);
+        assert!(table.seats.iter().all(|s| s.bet == 0));
+        assert_eq!(table.history.first().unwrap(), "P2 posts SMALL BLIND 10");
+        assert_eq!(table.history.last().unwrap(), &format!("*** FLOP *** {}", cards(&hand.board)));
+    }
+
+    #[test]
+    fn test_render_hides_other_hole_cards() {
+        let hand = hand();
+        let table = TableState::from_events(&hand.events);
+
+        let text = table.render(Some(2), false);
+        assert!(text.contains(&format!("🧠 P2: {}", cards(&hand.active[1].hand))));
+        for opponent in [&hand.active[0], &hand.active[2]] {
+            for card in &opponent.hand {
+                assert!(!text.contains(&card.to_string()));
+            }
+        }
+        assert!(text.contains("SB Seat 2"));
+        assert!(text.contains("BB Seat 3"));
+        assert!(text.contains("D Seat 1"));
+
+        let revealed = table.render(None, true);
+        assert!(hand.active.iter().all(|p| revealed.contains(&cards(&p.hand))));
+    }
+
+    #[test]
+    fn test_settled_hand_returns_pot_to_stacks() {
+        let mut hand = hand();
+        hand.act(1, PokerAction::Fold).unwrap();
+        hand.act(2, PokerAction::Fold).unwrap();
+        hand.settle().unwrap();
+
+        let table = TableState::from_events(&hand.events);
+
+        assert_eq!(table.pot, 0);
+        assert_eq!(table.seats.iter().map(|s| s.stack).sum::<u32>(), 3000);
+        assert_eq!(table.history.last().unwrap(), "P3 wins 30");
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::hand::StartHandParams;
+use crate::game::poker::PokerGame;
+
+/// A freezeout: no rebuys, blinds going up a level every `hands_per_level` hands, played
+/// until one player holds every chip or `max_hands` hands have been dealt.
+pub struct Tournament {
+    pub blind_levels: Vec<u32>,
+    pub hands_per_level: u32,
+    pub max_hands: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TournamentResult {
+    /// Player id and finishing rank, winner first; players busting on the same hand share a
+    /// rank, and those still in when the hand limit is reached are ranked by stack
+    pub finishing_order: Vec<(usize, u32)>,
+    pub hands: u32,
+}
+
+impl Tournament {
+    pub fn run<F, E>(&self, game: &mut PokerGame, mut play_hand: F) -> Result<TournamentResult, E>
+    where
+        F: FnMut(StartHandParams) -> Result<BTreeMap<usize, i64>, E>,
+        E: From<PokerGameError>,
+    {
+        let mut eliminated: Vec<(usize, u32)> = vec![];
+        let mut hands = 0;
+
+        while hands < self.max_hands {
+            let level = (hands / self.hands_per_level.max(1)) as usize;
+            if let Some(&blind_price) = self.blind_levels.get(level).or(self.blind_levels.last()) {
+                game.blind_price = blind_price;
+            }
+
+            let in_play: Vec<usize> = game.players.iter().filter(|p| p.cash_amount > 0).map(|p| p.player.id).collect();
+            let params = match game.next_hand() {
+                Some(params) => params,
+                None => break,
+            };
+            let winnings = play_hand(params)?;
+            game.apply_winnings(&winnings);
+            hands += 1;
+
+            let remaining = game.players.iter().filter(|p| p.cash_amount > 0).count() as u32;
+            for player_id in in_play {
+                if game.players.iter().any(|p| p.player.id == player_id && p.cash_amount == 0) {
+                    eliminated.push((player_id, remaining + 1));
+                }
+            }
+        }
+
+        let mut survivors: Vec<(usize, u32)> = game.players.iter()
+            .filter(|p| p.cash_amount > 0)
+            .map(|p| (p.player.id, p.cash_amount))
+            .collect();
+        survivors.sort_by_key(|&(_, stack)| std::cmp::Reverse(stack));
+
+        let mut finishing_order: Vec<(usize, u32)> = survivors.iter().enumerate()
+            .map(|(index, &(player_id, _))| (player_id, index as u32 + 1))
+            .collect();
+        finishing_order.extend(eliminated.iter().rev());
+
+        Ok(TournamentResult { finishing_order, hands })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::CreatePokerGame;
+    use crate::player::Player;
+
+    fn game() -> PokerGame {
+        let mut game = PokerGame::new(CreatePokerGame {
+            seats: 3,
+            default_buy_in_value: 100,
+            buy_in_limit: 1,
+            blind_price: 1,
+            betting: BettingStructure::NoLimit,
+            variant: GameVariant::Holdem,
+            ante: 0,
+            show_hud: false,
+        });
+        for id in 1..=3 {
+            game.add_player(Player::create(id, &format!("P{}", id), "l.png"), id as u32).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn test_blinds_rise_and_players_bust_in_order() {
+        let tournament = Tournament { blind_levels: vec![5, 10, 20], hands_per_level: 2, max_hands: 50 };
+        let mut blinds = vec![];
+
+        // Player 1 takes everything from player 3 first, then from player 2
+        let result = tournament.run::<_, PokerGameError>(&mut game(), |params| {
+            blinds.push(params.blind_price);
+            let victim = params.players.iter().map(|p| p.player.id).filter(|&id| id != 1).max().unwrap();
+            let stack = params.players.iter().find(|p| p.player.id == victim).unwrap().cash_amount as i64;
+            Ok(BTreeMap::from([(1, stack.min(60)), (victim, -stack.min(60))]))
+        }).unwrap();
+
+        assert_eq!(blinds, vec![5, 5, 10, 10]);
+        assert_eq!(result.hands, 4);
+        assert_eq!(result.finishing_order, vec![(1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_hand_limit_ranks_survivors_by_stack() {
+        let tournament = Tournament { blind_levels: vec![5], hands_per_level: 10, max_hands: 1 };
+
+        let result = tournament.run::<_, PokerGameError>(&mut game(), |_| Ok(BTreeMap::from([(2, 30), (3, -30)]))).unwrap();
+
+        assert_eq!(result.finishing_order, vec![(2, 1), (1, 2), (3, 3)]);
+    }
+}
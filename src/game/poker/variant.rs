@@ -0,0 +1,306 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Formatter;
+use crate::deck::{Card, Deck, Suit};
+use crate::game::poker::evaluator::{best_hand, best_low, best_omaha_hand, best_omaha_low, best_short_deck_hand, compare_short_deck, evaluate_deuce_to_seven, evaluate_showing, evaluate_showing_low, low_rank, HandValue, LowValue};
+use crate::game::poker::hand::HandStage;
+
+/// Which game a table deals, chosen per table alongside its betting structure.
+///
+/// With the `serde` feature a variant is `"holdem"`, `"short-deck"`, `"omaha"`, `"omaha-hi-lo"`,
+/// `"stud"`, `"razz"`, `"five-card-draw"` or `"2-7-triple-draw"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum GameVariant {
+    /// Two hole cards, the best five of the seven cards play
+    Holdem,
+    /// Hold'em dealt from the 36 cards six and up: a flush beats a full house and A-6-7-8-9 is
+    /// the lowest straight
+    ShortDeck,
+    /// Four hole cards, exactly two of them with three of the board play
+    Omaha,
+    /// Omaha where half of every pot goes to the best eight-or-better low
+    OmahaHiLo,
+    /// Seven-card stud: two cards down and one up, three more up and a last one down, no board.
+    /// The lowest up-card brings it in, then the best hand showing acts first.
+    Stud,
+    /// Seven-card stud for the best ace-to-five low, without qualifier: the highest up-card
+    /// brings it in, then the best low showing acts first
+    Razz,
+    /// Five cards down and one draw, the best high hand wins
+    FiveCardDraw,
+    /// Five cards down and three draws for the best deuce-to-seven low, aces high and
+    /// straights and flushes counting against the hand
+    #[cfg_attr(feature = "serde", serde(rename = "2-7-triple-draw"))]
+    TripleDraw,
+}
+
+/// Cards one street deals before its betting round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Street {
+    pub stage: HandStage,
+    /// Cards dealt face down to every player still in the hand
+    pub down: usize,
+    /// Cards dealt face up to every player still in the hand, after the face-down ones
+    pub up: usize,
+    /// Community cards turned face up
+    pub board: usize,
+    /// Whether players still in the hand may discard and draw replacements before the betting
+    pub draw: bool,
+}
+
+/// Bridge order of the suits, which breaks ties between up-cards of the same rank
+fn suit_order(suit: Suit) -> u8 {
+    match suit {
+        Suit::Clubs => 0,
+        Suit::Diamonds => 1,
+        Suit::Hearts => 2,
+        Suit::Spades => 3,
+    }
+}
+
+impl GameVariant {
+    /// The streets of a hand in dealing order, the first one dealt before any betting
+    pub fn streets(&self) -> Vec<Street> {
+        let street = |stage, down, up, board| Street { stage, down, up, board, draw: false };
+        let draw = |stage| Street { stage, down: 0, up: 0, board: 0, draw: true };
+        match self {
+            GameVariant::Holdem | GameVariant::ShortDeck | GameVariant::Omaha | GameVariant::OmahaHiLo => vec![
+                street(HandStage::INIT, if self.is_omaha() { 4 } else { 2 }, 0, 0),
+                street(HandStage::FLOP, 0, 0, 3),
+                street(HandStage::TURN, 0, 0, 1),
+                street(HandStage::RIVER, 0, 0, 1),
+            ],
+            GameVariant::Stud | GameVariant::Razz => vec![
+                street(HandStage::INIT, 2, 1, 0),
+                street(HandStage::FLOP, 0, 1, 0),
+                street(HandStage::TURN, 0, 1, 0),
+                street(HandStage::SIXTH, 0, 1, 0),
+                street(HandStage::RIVER, 1, 0, 0),
+            ],
+            GameVariant::FiveCardDraw => vec![street(HandStage::INIT, 5, 0, 0), draw(HandStage::RIVER)],
+            GameVariant::TripleDraw => vec![
+                street(HandStage::INIT, 5, 0, 0),
+                draw(HandStage::FLOP),
+                draw(HandStage::TURN),
+                draw(HandStage::RIVER),
+            ],
+        }
+    }
+
+    fn is_omaha(&self) -> bool {
+        matches!(self, GameVariant::Omaha | GameVariant::OmahaHiLo)
+    }
+
+    /// Cards every player is dealt over a whole hand, face up ones included
+    pub fn hole_cards(&self) -> usize {
+        self.streets().iter().map(|s| s.down + s.up).sum()
+    }
+
+    /// Community cards of a whole hand, none in stud games
+    pub fn board_cards(&self) -> usize {
+        self.streets().iter().map(|s| s.board).sum()
+    }
+
+    /// Whether the dealer burns a card after every round of hole cards and before every board
+    /// street. Games without a board do not, so that seven stud players can be dealt seven cards.
+    pub fn burns_cards(&self) -> bool {
+        self.board_cards() > 0
+    }
+
+    /// Most players the deck can deal a whole hand to. Draw games keep a whole hand of
+    /// replacements for the last player to draw, the discards of the others reshuffled.
+    pub fn max_players(&self) -> usize {
+        let streets = self.streets();
+        let burns = if self.burns_cards() {
+            streets.iter().map(|s| s.down + s.up + if s.board > 0 { 1 } else { 0 }).sum()
+        } else {
+            0
+        };
+        let replacements = if self.has_draws() { self.hole_cards() } else { 0 };
+        (self.deck().len() - self.board_cards() - burns - replacements) / self.hole_cards()
+    }
+
+    /// Whether any street lets players exchange cards
+    pub fn has_draws(&self) -> bool {
+        self.streets().iter().any(|s| s.draw)
+    }
+
+    /// Whether players exchange cards at `stage` before its betting
+    pub fn draws_at(&self, stage: HandStage) -> bool {
+        self.streets().iter().any(|s| s.stage == stage && s.draw)
+    }
+
+    /// Stud games post no blinds: the worst up-card brings it in and on later streets the best
+    /// hand showing acts first
+    pub fn is_stud(&self) -> bool {
+        matches!(self, GameVariant::Stud | GameVariant::Razz)
+    }
+
+    /// A fresh, unshuffled deck for the variant
+    pub fn deck(&self) -> Deck {
+        match self {
+            GameVariant::ShortDeck => Deck::create_short(),
+            _ => Deck::create(),
+        }
+    }
+
+    /// Whether pots are split between the best high and the best qualifying low hand
+    pub fn is_hi_lo(&self) -> bool {
+        *self == GameVariant::OmahaHiLo
+    }
+
+    /// Best high hand `hole` makes with `board` under the rules of the variant, none in
+    /// lowball games
+    pub fn high_hand(&self, hole: &[Card], board: &[Card]) -> Option<HandValue> {
+        match self {
+            GameVariant::Holdem | GameVariant::Stud | GameVariant::FiveCardDraw => Some(best_hand(&[hole, board].concat())),
+            GameVariant::ShortDeck => Some(best_short_deck_hand(&[hole, board].concat())),
+            GameVariant::Omaha | GameVariant::OmahaHiLo => Some(best_omaha_hand(hole, board)),
+            GameVariant::Razz | GameVariant::TripleDraw => None,
+        }
+    }
+
+    /// Orders two high hands under the hand rankings of the variant
+    pub fn compare_high(&self, a: &HandValue, b: &HandValue) -> Ordering {
+        match self {
+            GameVariant::ShortDeck => compare_short_deck(a, b),
+            _ => a.cmp(b),
+        }
+    }
+
+    /// Best low hand: the qualifying one in hi-lo games, any in lowball games
+    pub fn low_hand(&self, hole: &[Card], board: &[Card]) -> Option<LowValue> {
+        match self {
+            GameVariant::OmahaHiLo => best_omaha_low(hole, board),
+            GameVariant::Razz => Some(best_low(&[hole, board].concat())),
+            GameVariant::TripleDraw => Some(evaluate_deuce_to_seven(hole)),
+            GameVariant::Holdem | GameVariant::ShortDeck | GameVariant::Omaha | GameVariant::Stud | GameVariant::FiveCardDraw => None,
+        }
+    }
+
+    /// Orders up-cards for the bring-in, the smallest bringing it in: the lowest card in stud,
+    /// the highest in Razz, suits ranking clubs, diamonds, hearts then spades
+    pub fn compare_bring_in(&self, a: &Card, b: &Card) -> Ordering {
+        match self {
+            GameVariant::Razz => (low_rank(b.rank), suit_order(b.suit)).cmp(&(low_rank(a.rank), suit_order(a.suit))),
+            _ => (a.rank, suit_order(a.suit)).cmp(&(b.rank, suit_order(b.suit))),
+        }
+    }
+
+    /// Orders the up-cards of two stud players, the greater acting first after the bring-in
+    /// street: the best high hand showing in stud, the best low in Razz
+    pub fn compare_showing(&self, a: &[Card], b: &[Card]) -> Ordering {
+        match self {
+            GameVariant::Razz => evaluate_showing_low(b).cmp(&evaluate_showing_low(a)),
+            _ => evaluate_showing(a).cmp(&evaluate_showing(b)),
+        }
+    }
+
+    /// Name of the street at `stage`, such as `FLOP`, `FOURTH STREET` or `SECOND DRAW`
+    pub fn stage_name(&self, stage: HandStage) -> String {
+        let res = match (self, stage) {
+            (GameVariant::Stud | GameVariant::Razz, HandStage::INIT) => "THIRD STREET",
+            (GameVariant::Stud | GameVariant::Razz, HandStage::FLOP) => "FOURTH STREET",
+            (GameVariant::Stud | GameVariant::Razz, HandStage::TURN) => "FIFTH STREET",
+            (GameVariant::Stud | GameVariant::Razz, HandStage::SIXTH) => "SIXTH STREET",
+            (GameVariant::Stud | GameVariant::Razz, HandStage::RIVER) => "SEVENTH STREET",
+            (GameVariant::FiveCardDraw | GameVariant::TripleDraw, HandStage::INIT) => "PRE-DRAW",
+            (GameVariant::FiveCardDraw, _) => "DRAW",
+            (GameVariant::TripleDraw, HandStage::FLOP) => "FIRST DRAW",
+            (GameVariant::TripleDraw, HandStage::TURN | HandStage::SIXTH) => "SECOND DRAW",
+            (GameVariant::TripleDraw, HandStage::RIVER) => "THIRD DRAW",
+            _ => return stage.to_string(),
+        };
+        String::from(res)
+    }
+
+    /// `holdem`, `short-deck`, `omaha`, `omaha-hi-lo`, `stud`, `razz`, `five-card-draw` or
+    /// `2-7-triple-draw`, as written in table configs and snapshots
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameVariant::Holdem => "holdem",
+            GameVariant::ShortDeck => "short-deck",
+            GameVariant::Omaha => "omaha",
+            GameVariant::OmahaHiLo => "omaha-hi-lo",
+            GameVariant::Stud => "stud",
+            GameVariant::Razz => "razz",
+            GameVariant::FiveCardDraw => "five-card-draw",
+            GameVariant::TripleDraw => "2-7-triple-draw",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<GameVariant> {
+        match code {
+            "holdem" => Some(GameVariant::Holdem),
+            "short-deck" => Some(GameVariant::ShortDeck),
+            "omaha" => Some(GameVariant::Omaha),
+            "omaha-hi-lo" => Some(GameVariant::OmahaHiLo),
+            "stud" => Some(GameVariant::Stud),
+            "razz" => Some(GameVariant::Razz),
+            "five-card-draw" => Some(GameVariant::FiveCardDraw),
+            "2-7-triple-draw" => Some(GameVariant::TripleDraw),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GameVariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            GameVariant::Holdem => "Hold'em",
+            GameVariant::ShortDeck => "Short-Deck Hold'em",
+            GameVariant::Omaha => "Omaha",
+            GameVariant::OmahaHiLo => "Omaha Hi-Lo",
+            GameVariant::Stud => "Seven-Card Stud",
+            GameVariant::Razz => "Razz",
+            GameVariant::FiveCardDraw => "Five-Card Draw",
+            GameVariant::TripleDraw => "2-7 Triple Draw",
+        };
+        write!(f, "{}", res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(code: &str) -> Card {
+        Card::from_code(code).unwrap()
+    }
+
+    #[test]
+    fn test_dealing_patterns() {
+        assert_eq!((GameVariant::Holdem.hole_cards(), GameVariant::Holdem.board_cards()), (2, 5));
+        assert_eq!((GameVariant::Omaha.hole_cards(), GameVariant::Omaha.board_cards()), (4, 5));
+        assert_eq!((GameVariant::Stud.hole_cards(), GameVariant::Stud.board_cards()), (7, 0));
+        assert!(GameVariant::Holdem.burns_cards() && !GameVariant::Razz.burns_cards());
+
+        assert_eq!(GameVariant::Holdem.max_players(), 21);
+        assert_eq!(GameVariant::ShortDeck.max_players(), 13);
+        assert_eq!(GameVariant::Omaha.max_players(), 10);
+        assert_eq!(GameVariant::Stud.max_players(), 7);
+
+        assert_eq!((GameVariant::TripleDraw.hole_cards(), GameVariant::TripleDraw.board_cards()), (5, 0));
+        assert!(GameVariant::TripleDraw.draws_at(HandStage::TURN) && !GameVariant::FiveCardDraw.draws_at(HandStage::TURN));
+        assert!(GameVariant::FiveCardDraw.has_draws() && !GameVariant::Stud.has_draws());
+        assert_eq!(GameVariant::FiveCardDraw.max_players(), 9);
+        assert_eq!(GameVariant::TripleDraw.stage_name(HandStage::RIVER), "THIRD DRAW");
+    }
+
+    #[test]
+    fn test_bring_in_and_first_to_act() {
+        // Stud: the lowest card brings it in, clubs below spades; Razz: the highest, aces low
+        assert_eq!(GameVariant::Stud.compare_bring_in(&card("2c"), &card("2s")), Ordering::Less);
+        assert_eq!(GameVariant::Stud.compare_bring_in(&card("Ah"), &card("3d")), Ordering::Greater);
+        assert_eq!(GameVariant::Razz.compare_bring_in(&card("Kc"), &card("Ah")), Ordering::Less);
+        assert_eq!(GameVariant::Razz.compare_bring_in(&card("Qs"), &card("Qc")), Ordering::Less);
+
+        let pair = [card("5c"), card("5d")];
+        let high = [card("Ac"), card("Kd")];
+        assert_eq!(GameVariant::Stud.compare_showing(&pair, &high), Ordering::Greater);
+        assert_eq!(GameVariant::Razz.compare_showing(&pair, &high), Ordering::Less);
+        assert_eq!(GameVariant::Razz.compare_showing(&[card("Ac"), card("4d")], &[card("2c"), card("5d")]), Ordering::Greater);
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt::Write;
+use crate::deck::Card;
+use crate::game::poker::action::LegalActions;
+use crate::game::poker::betting::BettingStructure;
+use crate::game::poker::variant::GameVariant;
+use crate::game::poker::blind::Blind;
+use crate::game::poker::error::PokerGameError;
+use crate::game::poker::event::HandEventKind;
+use crate::game::poker::hand::{HandStage, PokerHand};
+use crate::game::poker::stats::{PlayerStats, StatsTracker};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeatView {
+    pub seat: u32,
+    pub name: String,
+    pub stack: u32,
+    pub blind: Blind,
+    /// Cards the player was dealt face up, in stud games
+    pub up_cards: Vec<Card>,
+    /// Cards the player exchanged at each draw so far, in draw games
+    pub draws: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpponentStats {
+    pub seat: u32,
+    pub name: String,
+    pub stats: PlayerStats,
+}
+
+/// Everything one seat is allowed to see when it has to act, rendered into the LLM prompt.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerView {
+    pub seat: u32,
+    pub name: String,
+    pub stage: HandStage,
+    pub betting: BettingStructure,
+    pub variant: GameVariant,
+    pub ante: u32,
+    pub hole_cards: Vec<Card>,
+    pub board: Vec<Card>,
+    pub pot: u32,
+    pub stack: u32,
+    pub blind: Blind,
+    pub to_call: u32,
+    pub legal_actions: LegalActions,
+    pub players: Vec<SeatView>,
+    /// Empty unless the table shows the HUD
+    pub opponent_stats: Vec<OpponentStats>,
+    /// Why the previous answer to this same decision was rejected, when it was
+    pub feedback: Option<String>,
+    /// Whether the decision is which cards to discard, the betting waiting for the draw
+    pub drawing: bool,
+}
+
+impl PlayerView {
+    pub fn create(hand: &PokerHand, seat: u32, stage: HandStage, hud: Option<&StatsTracker>) -> Result<PlayerView, PokerGameError> {
+        let me = hand.active.iter().find(|p| p.seat == seat);
+        if me.is_none() {
+            return Err(PokerGameError::PlayerSeatNotFound(seat));
+        }
+        let me = me.expect("Expect to find the seat");
+
+        let turned: usize = hand.variant.streets().iter().filter(|s| s.stage <= stage).map(|s| s.board).sum();
+        let board = hand.board.iter().take(turned).copied().collect();
+
+        let players = hand.active.iter().map(|p| SeatView {
+            seat: p.seat,
+            name: p.player.name.clone(),
+            stack: p.cash_amount,
+            blind: p.blind,
+            up_cards: p.up_cards.clone(),
+            draws: hand.events.iter().filter_map(|e| match e.kind {
+                HandEventKind::DrawTaken { seat, count, .. } if seat == p.seat => Some(count),
+                _ => None,
+            }).collect(),
+        }).collect();
+
+        let opponent_stats = match hud {
+            Some(tracker) => hand.active.iter()
+                .filter(|p| p.seat != seat)
+                .map(|p| OpponentStats {
+                    seat: p.seat,
+                    name: p.player.name.clone(),
+                    stats: tracker.totals(p.player.id),
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        Ok(PlayerView {
+            seat,
+            name: me.player.name.clone(),
+            stage,
+            betting: hand.betting,
+            variant: hand.variant,
+            ante: hand.ante,
+            hole_cards: me.hand.clone(),
+            board,
+            pot: hand.pot,
+            stack: me.cash_amount,
+            blind: me.blind,
+            to_call: hand.to_call(seat),
+            legal_actions: hand.legal_actions(seat)?,
+            players,
+            opponent_stats,
+            feedback: None,
+            drawing: hand.next_to_draw() == Some(seat),
+        })
+    }
+
+    pub fn prompt(&self) -> String {
+        let cards = |cards: &[Card]| cards.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
+        let mut prompt = String::new();
+
+        writeln!(prompt, "You are {} in seat {}.", self.name, self.seat).unwrap();
+        writeln!(prompt, "Stage: {}", self.variant.stage_name(self.stage)).unwrap();
+        writeln!(prompt, "Game: {}", self.variant).unwrap();
+        match self.variant {
+            GameVariant::Holdem => {}
+            GameVariant::ShortDeck => writeln!(prompt, "The deck has only sixes to aces: a flush beats a full house and A-6-7-8-9 is a straight.").unwrap(),
+            GameVariant::Omaha | GameVariant::OmahaHiLo => writeln!(prompt, "Hands use exactly two of your cards and three from the board.").unwrap(),
+            GameVariant::Stud => writeln!(prompt, "No board: the best five of your seven cards play. The lowest up-card brings it in, then the best hand showing acts first.").unwrap(),
+            GameVariant::Razz => writeln!(prompt, "No board: the lowest five of your seven cards win, aces low, straights and flushes not counting. The highest up-card brings it in, then the best low showing acts first.").unwrap(),
+            GameVariant::FiveCardDraw => writeln!(prompt, "No board: after one draw, where you may exchange any of your five cards, the best hand wins.").unwrap(),
+            GameVariant::TripleDraw => writeln!(prompt, "No board: after three draws the lowest hand wins. Aces are high and straights and flushes count against you, so 7-5-4-3-2 of mixed suits is the best hand.").unwrap(),
+        }
+        if self.variant.is_hi_lo() {
+            writeln!(prompt, "Half of the pot goes to the best low of five cards 8 or lower, aces low.").unwrap();
+        }
+        writeln!(prompt, "Betting: {}", self.betting).unwrap();
+        if self.ante > 0 {
+            writeln!(prompt, "Ante: {} from every player, no blinds", self.ante).unwrap();
+        }
+        writeln!(prompt, "Your cards: {}", cards(&self.hole_cards)).unwrap();
+        if !self.board.is_empty() {
+            writeln!(prompt, "Board: {}", cards(&self.board)).unwrap();
+        }
+        writeln!(prompt, "Pot: {}", self.pot).unwrap();
+        writeln!(prompt, "Your stack: {}", self.stack).unwrap();
+        if self.drawing {
+            let codes: Vec<String> = self.hole_cards.iter().map(|c| c.code()).collect();
+            writeln!(prompt, "Draw: throw away any of your cards ({}) for as many new ones.", codes.join(" ")).unwrap();
+            writeln!(prompt, "Answer `discard` with the codes of the cards to throw away, e.g. `discard {}`, or `stand pat`.", codes[0]).unwrap();
+        } else {
+            writeln!(prompt, "To call: {}", self.to_call).unwrap();
+            writeln!(prompt, "Legal actions: {}", self.legal_actions).unwrap();
+        }
+
+        writeln!(prompt, "Players:").unwrap();
+        for player in &self.players {
+            write!(prompt, "  Seat {} {}: {} chips {}", player.seat, player.name, player.stack, player.blind).unwrap();
+            if !player.up_cards.is_empty() {
+                write!(prompt, " showing {}", cards(&player.up_cards)).unwrap();
+            }
+            if !player.draws.is_empty() {
+                let draws: Vec<String> = player.draws.iter().map(|count| count.to_string()).collect();
+                write!(prompt, " drew {}", draws.join(", ")).unwrap();
+            }
+            writeln!(prompt).unwrap();
+        }
+
+        if !self.opponent_stats.is_empty() {
+            writeln!(prompt, "Opponent statistics from earlier hands this session:").unwrap();
+            for opponent in &self.opponent_stats {
+                let s = &opponent.stats;
+                writeln!(
+                    prompt, "  Seat {} {}: VPIP {:.1}% PFR {:.1}% over {} hands, AF {:.2} over {} postflop actions",
+                    opponent.seat, opponent.name, s.vpip(), s.pfr(), s.hands,
+                    s.aggression_factor(), s.postflop_bets_raises + s.postflop_calls
+                ).unwrap();
+            }
+        }
+
+        if let Some(feedback) = &self.feedback {
+            writeln!(prompt, "Your previous answer was rejected: {}", feedback).unwrap();
+        }
+
+        prompt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::poker::betting::BettingStructure;
+    use crate::game::poker::variant::GameVariant;
+    use crate::game::poker::action::{PlayerAction, PokerAction};
+    use crate::game::poker::game_player::funded_players;
+    use crate::game::poker::hand::StartHandParams;
+    use crate::game::poker::stats::HandRecord;
+
+    fn hand(betting: BettingStructure, variant: GameVariant) -> PokerHand {
+        let players = funded_players(&[(1, "P1"), (2, "P2"), (3, "P3")], 1000);
+        PokerHand::start(StartHandParams { blind_price: 10, players, dealer_seat: 1, seed: 3, betting, variant, ante: 0 }).unwrap()
+    }
+
+    #[test]
+    fn test_view_shows_own_cards_and_board_by_stage() {
+        let hand = hand(BettingStructure::NoLimit, GameVariant::Holdem);
+
+        let preflop = PlayerView::create(&hand, 2, HandStage::INIT, None).unwrap();
+        assert_eq!(preflop.hole_cards, hand.active[1].hand);
+        assert!(preflop.board.is_empty());
+        assert_eq!(preflop.blind, Blind::SMALL);
+        assert_eq!(preflop.stack, 990);
+        assert_eq!(preflop.to_call, 10);
+        assert!(preflop.prompt().contains("Legal actions: fold, call 10, raise 30-990"));
+
+        let mut hand = hand;
+        for _ in 0..3 {
+            hand.deal_next_stage().unwrap();
+        }
+        let turn = PlayerView::create(&hand, 2, HandStage::TURN, None).unwrap();
+        assert_eq!(turn.board.len(), 4);
+        assert_eq!(turn.board[3], hand.board[3]);
+
+        let river = PlayerView::create(&hand, 2, HandStage::RIVER, None).unwrap();
+        assert_eq!(river.board, hand.board);
+    }
+
+    #[test]
+    fn test_view_unknown_seat() {
+        let result = PlayerView::create(&hand(BettingStructure::NoLimit, GameVariant::Holdem), 9, HandStage::INIT, None);
+        assert!(matches!(result, Err(PokerGameError::PlayerSeatNotFound(9))));
+    }
+
+    #[test]
+    fn test_prompt_never_reveals_opponent_cards() {
+        let hand = hand(BettingStructure::NoLimit, GameVariant::Holdem);
+        let prompt = PlayerView::create(&hand, 1, HandStage::INIT, None).unwrap().prompt();
+
+        assert!(prompt.contains(&hand.active[0].hand[0].to_string()));
+        for opponent in &hand.active[1..] {
+            for card in &opponent.hand {
+                assert!(!prompt.contains(&card.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_prompt_includes_hud_with_sample_sizes() {
+        let hand = hand(BettingStructure::NoLimit, GameVariant::Holdem);
+        let mut tracker = StatsTracker::new();
+        tracker.record_hand(&HandRecord {
+            seats: vec![(1, 1), (2, 2), (3, 3)],
+            actions: vec![
+                PlayerAction { seat: 1, stage: HandStage::INIT, action: PokerAction::Raise(30) },
+                PlayerAction { seat: 2, stage: HandStage::INIT, action: PokerAction::Fold },
+                PlayerAction { seat: 3, stage: HandStage::INIT, action: PokerAction::Fold },
+            ],
+            ..HandRecord::default()
+        });
+
+        let view = PlayerView::create(&hand, 3, HandStage::INIT, Some(&tracker)).unwrap();
+        assert_eq!(view.opponent_stats.len(), 2);
+
+        let prompt = view.prompt();
+        assert!(prompt.contains("Seat 1 P1: VPIP 100.0% PFR 100.0% over 1 hands, AF 0.00 over 0 postflop actions"));
+        assert!(prompt.contains("Seat 2 P2: VPIP 0.0% PFR 0.0% over 1 hands"));
+        assert!(!prompt.contains("Seat 3 P3: VPIP"));
+    }
+
+    #[test]
+    fn test_omaha_prompt_explains_the_rules() {
+        let hand = hand(BettingStructure::PotLimit, GameVariant::OmahaHiLo);
+
+        let view = PlayerView::create(&hand, 2, HandStage::INIT, None).unwrap();
+        assert_eq!(view.hole_cards.len(), 4);
+        let prompt = view.prompt();
+        assert!(prompt.contains("Game: Omaha Hi-Lo"));
+        assert!(prompt.contains("exactly two of your cards"));
+        assert!(prompt.contains("best low"));
+    }
+
+    #[test]
+    fn test_stud_prompt_shows_every_up_card() {
+        let mut hand = hand(BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::Razz);
+        hand.deal_next_stage().unwrap();
+
+        let view = PlayerView::create(&hand, 2, HandStage::FLOP, None).unwrap();
+        assert_eq!(view.hole_cards.len(), 4);
+        assert!(view.board.is_empty());
+        assert!(view.players.iter().all(|p| p.up_cards.len() == 2));
+        let prompt = view.prompt();
+        assert!(prompt.contains("Stage: FOURTH STREET"));
+        assert!(prompt.contains("aces low"));
+        let showing = format!("Seat 3 P3: 1000 chips {} showing {} {}", hand.active[2].blind, hand.active[2].up_cards[0], hand.active[2].up_cards[1]);
+        assert!(prompt.lines().any(|line| line.trim_start().starts_with(&showing)));
+    }
+
+    #[test]
+    fn test_draw_prompt_asks_for_discards() {
+        let mut hand = hand(BettingStructure::FixedLimit { raise_cap: 4 }, GameVariant::FiveCardDraw);
+        hand.act(1, PokerAction::Call(20)).unwrap();
+        hand.act(2, PokerAction::Call(10)).unwrap();
+        hand.act(3, PokerAction::Check).unwrap();
+        hand.deal_next_stage().unwrap();
+        let discards = hand.active[1].hand[..3].to_vec();
+        hand.draw(2, &discards).unwrap();
+
+        let view = PlayerView::create(&hand, 3, hand.stage, None).unwrap();
+        assert!(view.drawing);
+        let prompt = view.prompt();
+        assert!(prompt.contains("Stage: DRAW"));
+        assert!(prompt.contains(&format!("e.g. `discard {}`, or `stand pat`", view.hole_cards[0].code())));
+        assert!(!prompt.contains("Legal actions"));
+        assert!(prompt.lines().any(|line| line.trim_start().starts_with("Seat 2 P2") && line.ends_with(" drew 3")));
+
+        assert!(!PlayerView::create(&hand, 1, hand.stage, None).unwrap().drawing);
+    }
+}
+
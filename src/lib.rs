@@ -0,0 +1,49 @@
+//! Texas Hold'em engine for pitting LLMs and bots against each other.
+//!
+//! - [`deck`]: cards and seeded shuffling
+//! - [`game::poker::hand`]: the hand engine, emitting a [`HandEvent`](game::poker::event::HandEvent) log
+//! - [`game::poker::evaluator`]: hand ranking and Monte Carlo equity
+//! - [`game::poker::PokerGame`]: a table with seats, stacks and the button
+//! - [`game::poker::agent`]: the [`Agent`](game::poker::agent::Agent) trait and built-in [`bots`](game::poker::bots)
+//! - [`error`]: a crate-wide [`Error`] wrapping the error of each module
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use llm_holdem::game::poker::agent::Agent;
+//! use llm_holdem::game::poker::betting::BettingStructure;
+//! use llm_holdem::game::poker::variant::GameVariant;
+//! use llm_holdem::game::poker::bots::CallingStation;
+//! use llm_holdem::game::poker::dealer::play_hand;
+//! use llm_holdem::game::poker::{CreatePokerGame, PokerGame};
+//! use llm_holdem::player::Player;
+//!
+//! let mut game = PokerGame::new(CreatePokerGame {
+//!     seats: 2,
+//!     default_buy_in_value: 1000,
+//!     buy_in_limit: 1,
+//!     blind_price: 5,
+//!     betting: BettingStructure::NoLimit,
+//!     variant: GameVariant::Holdem,
+//!     ante: 0,
+//!     show_hud: false,
+//! });
+//! game.add_player(Player::create(1, "Alice", "alice.png"), 1).unwrap();
+//! game.add_player(Player::create(2, "Bob", "bob.png"), 2).unwrap();
+//!
+//! let mut agents: BTreeMap<usize, Box<dyn Agent>> = BTreeMap::new();
+//! agents.insert(1, Box::new(CallingStation { name: String::from("Alice") }));
+//! agents.insert(2, Box::new(CallingStation { name: String::from("Bob") }));
+//!
+//! let params = game.next_hand().unwrap();
+//! let played = play_hand(params, &mut agents, None, vec![]).unwrap();
+//! game.apply_winnings(&played.winnings);
+//!
+//! assert_eq!(game.players.iter().map(|p| p.cash_amount).sum::<u32>(), 2000);
+//! ```
+
+pub mod error;
+pub mod deck;
+pub mod player;
+pub mod game;
+
+pub use error::{Error, Result};
@@ -1,6 +1,5 @@
-use crate::deck::Card;
-
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub id: usize,
     pub name: String,
@@ -20,15 +19,6 @@ impl Player {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::deck::{Card, Rank, Suit};
-
-    fn sample_card() -> Card {
-        Card { rank: Rank::Ace, suit: Suit::Spades }
-    }
-
-    fn second_card() -> Card {
-        Card { rank: Rank::King, suit: Suit::Hearts }
-    }
 
     #[test]
     fn test_create_player() {